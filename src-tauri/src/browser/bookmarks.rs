@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
@@ -33,6 +33,70 @@ pub struct BookmarkTree {
     pub root_folder_id: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BookmarkImportFormat {
+    CrateJson,
+    NetscapeHtml,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkImportSource {
+    pub name: String,
+    pub format: BookmarkImportFormat,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkImportResult {
+    pub name: String,
+    pub folder_id: Option<String>,
+    pub bookmarks_imported: usize,
+    pub folders_imported: usize,
+    pub error: Option<String>,
+}
+
+/// Format-independent shape of an imported bookmark hierarchy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportedBookmark {
+    Folder { name: String, children: Vec<ImportedBookmark>, created_at: Option<chrono::DateTime<chrono::Utc>> },
+    Link { title: String, url: String, created_at: Option<chrono::DateTime<chrono::Utc>> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorizationAction {
+    pub bookmark_id: String,
+    pub title: String,
+    pub url: String,
+    pub category: String,
+    pub folder_id: Option<String>,
+    pub creates_folder: bool,
+}
+
+/// Topic folders used by auto-categorization. A bookmark matches a category
+/// when its host is one of the listed domains or a subdomain of one.
+const BOOKMARK_CATEGORIES: &[(&str, &[&str])] = &[
+    ("Development", &["github.com", "gitlab.com", "bitbucket.org", "stackoverflow.com", "stackexchange.com", "crates.io", "docs.rs", "rust-lang.org", "npmjs.com", "pypi.org", "developer.mozilla.org", "dev.to"]),
+    ("News", &["bbc.co.uk", "bbc.com", "cnn.com", "nytimes.com", "theguardian.com", "reuters.com", "apnews.com", "washingtonpost.com", "news.ycombinator.com"]),
+    ("Social", &["facebook.com", "twitter.com", "x.com", "instagram.com", "linkedin.com", "reddit.com", "mastodon.social", "tiktok.com", "pinterest.com"]),
+    ("Shopping", &["amazon.com", "amazon.co.uk", "ebay.com", "etsy.com", "aliexpress.com", "walmart.com", "bestbuy.com"]),
+    ("Video", &["youtube.com", "vimeo.com", "twitch.tv", "netflix.com"]),
+    ("Reference", &["wikipedia.org", "wiktionary.org", "britannica.com", "archive.org"]),
+];
+
+pub fn bookmark_category(url: &str) -> Option<&'static str> {
+    let host = super::network::url_host(url)?;
+    BOOKMARK_CATEGORIES.iter()
+        .find(|(_, domains)| domains.iter().any(|domain| super::network::host_matches_domain(&host, domain)))
+        .map(|(category, _)| *category)
+}
+
+static NETSCAPE_TOKEN_SELECTOR: Lazy<scraper::Selector> = Lazy::new(|| {
+    scraper::Selector::parse("h3, a, dl").unwrap()
+});
+
+/// Guards against cyclic folder references in imported JSON.
+const MAX_IMPORT_DEPTH: usize = 64;
+
 static BOOKMARK_MANAGER: Lazy<RwLock<BookmarkManager>> = Lazy::new(|| {
     RwLock::new(BookmarkManager::new())
 });
@@ -65,7 +129,20 @@ impl BookmarkManager {
         Self { tree }
     }
 
-    pub fn add_bookmark(&mut self, title: &str, url: &str, folder_id: Option<&str>) -> Result<String, String> {
+    /// Bookmarks `url` unless it is already bookmarked, in which case the
+    /// existing bookmark's id is returned, or an error if `error_on_duplicate` is set.
+    pub fn add_bookmark(&mut self, title: &str, url: &str, folder_id: Option<&str>, error_on_duplicate: bool) -> Result<String, String> {
+        if let Some(existing_id) = self.is_bookmarked(url) {
+            if error_on_duplicate {
+                return Err("URL is already bookmarked".to_string());
+            }
+            return Ok(existing_id);
+        }
+        
+        self.insert_bookmark(title, url, folder_id)
+    }
+
+    fn insert_bookmark(&mut self, title: &str, url: &str, folder_id: Option<&str>) -> Result<String, String> {
         let bookmark_id = Uuid::new_v4().to_string();
         let folder_id = folder_id.map(|s| s.to_string());
         
@@ -97,6 +174,42 @@ impl BookmarkManager {
         Ok(bookmark_id)
     }
 
+    /// Id of the bookmark for `url`, compared after normalization.
+    pub fn is_bookmarked(&self, url: &str) -> Option<String> {
+        let url = normalize_bookmark_url(url);
+        self.tree.bookmarks.values()
+            .filter(|bookmark| normalize_bookmark_url(&bookmark.url) == url)
+            .min_by_key(|bookmark| bookmark.created_at)
+            .map(|bookmark| bookmark.id.clone())
+    }
+
+    /// Counts a visit to every bookmark for `url`. Returns whether any matched.
+    pub fn record_visit(&mut self, url: &str) -> bool {
+        let url = normalize_bookmark_url(url);
+        let now = chrono::Utc::now();
+        let mut matched = false;
+        
+        for bookmark in self.tree.bookmarks.values_mut() {
+            if normalize_bookmark_url(&bookmark.url) == url {
+                bookmark.visit_count += 1;
+                bookmark.last_accessed = Some(now);
+                matched = true;
+            }
+        }
+        
+        matched
+    }
+
+    /// Visited bookmarks by visit count, most recently accessed first among ties.
+    pub fn get_most_visited(&self, limit: usize) -> Vec<&Bookmark> {
+        let mut bookmarks: Vec<&Bookmark> = self.tree.bookmarks.values()
+            .filter(|bookmark| bookmark.visit_count > 0)
+            .collect();
+        bookmarks.sort_by(|a, b| b.visit_count.cmp(&a.visit_count).then_with(|| b.last_accessed.cmp(&a.last_accessed)));
+        bookmarks.truncate(limit);
+        bookmarks
+    }
+
     pub fn create_folder(&mut self, name: &str, parent_id: Option<&str>) -> Result<String, String> {
         let folder_id = Uuid::new_v4().to_string();
         let parent_id = parent_id.map(|s| s.to_string());
@@ -143,24 +256,24 @@ impl BookmarkManager {
             return Err("Cannot delete root folder".to_string());
         }
         
-        let folder = self.tree.folders.get(folder_id).ok_or("Folder not found")?;
-        let parent_id = folder.parent_id.clone();
-        let children = folder.children.clone();
-        
-        for child_id in children {
-            if self.tree.bookmarks.contains_key(&child_id) {
-                self.delete_bookmark(&child_id)?;
-            } else if self.tree.folders.contains_key(&child_id) {
-                self.delete_folder(&child_id)?;
+        let parent_id = self.tree.folders.get(folder_id).ok_or("Folder not found")?.parent_id.clone();
+        let subfolders = folder_subtree(&self.tree, folder_id)?;
+        
+        for subfolder_id in subfolders.iter().rev() {
+            let children = self.tree.folders.get(subfolder_id)
+                .map(|folder| folder.children.clone())
+                .unwrap_or_default();
+            for child_id in children {
+                if self.tree.bookmarks.contains_key(&child_id) {
+                    self.delete_bookmark(&child_id)?;
+                }
             }
+            self.tree.folders.remove(subfolder_id);
         }
         
-        self.tree.folders.remove(folder_id);
-        
-        if let Some(parent_id) = parent_id {
-            if let Some(parent_folder) = self.tree.folders.get_mut(&parent_id) {
-                parent_folder.children.retain(|id| id != folder_id);
-            }
+        let parent_id = parent_id.unwrap_or_else(|| self.tree.root_folder_id.clone());
+        if let Some(parent_folder) = self.tree.folders.get_mut(&parent_id) {
+            parent_folder.children.retain(|id| id != folder_id);
         }
         
         Ok(())
@@ -201,6 +314,41 @@ impl BookmarkManager {
         Ok(())
     }
 
+    /// Moves a folder under `new_parent_id` at `index` among its children (the
+    /// end when omitted or out of range). Reorders when the parent is unchanged.
+    pub fn move_folder(&mut self, folder_id: &str, new_parent_id: &str, index: Option<usize>) -> Result<(), String> {
+        if folder_id == self.tree.root_folder_id {
+            return Err("Cannot move root folder".to_string());
+        }
+        if !self.tree.folders.contains_key(new_parent_id) {
+            return Err("Target folder not found".to_string());
+        }
+        
+        let old_parent_id = self.tree.folders.get(folder_id)
+            .ok_or("Folder not found")?
+            .parent_id
+            .clone()
+            .unwrap_or_else(|| self.tree.root_folder_id.clone());
+        if folder_subtree(&self.tree, folder_id)?.iter().any(|id| id == new_parent_id) {
+            return Err("Cannot move a folder into itself or one of its subfolders".to_string());
+        }
+        
+        if let Some(old_parent) = self.tree.folders.get_mut(&old_parent_id) {
+            old_parent.children.retain(|id| id != folder_id);
+        }
+        
+        if let Some(new_parent) = self.tree.folders.get_mut(new_parent_id) {
+            let index = index.unwrap_or(new_parent.children.len()).min(new_parent.children.len());
+            new_parent.children.insert(index, folder_id.to_string());
+        }
+        
+        if let Some(folder) = self.tree.folders.get_mut(folder_id) {
+            folder.parent_id = Some(new_parent_id.to_string());
+        }
+        
+        Ok(())
+    }
+
     pub fn search_bookmarks(&self, query: &str) -> Vec<&Bookmark> {
         let query = query.to_lowercase();
         self.tree.bookmarks.values()
@@ -212,6 +360,59 @@ impl BookmarkManager {
             .collect()
     }
 
+    pub fn add_bookmark_tag(&mut self, bookmark_id: &str, tag: &str) -> Result<(), String> {
+        let tag = super::tags::normalize_tag(tag)?;
+        let bookmark = self.tree.bookmarks.get_mut(bookmark_id).ok_or("Bookmark not found")?;
+        
+        if !bookmark.tags.iter().any(|existing| tag_matches(existing, &tag)) {
+            bookmark.tags.push(tag);
+        }
+        
+        Ok(())
+    }
+
+    pub fn remove_bookmark_tag(&mut self, bookmark_id: &str, tag: &str) -> Result<(), String> {
+        let tag = super::tags::normalize_tag(tag)?;
+        let bookmark = self.tree.bookmarks.get_mut(bookmark_id).ok_or("Bookmark not found")?;
+        
+        let original_len = bookmark.tags.len();
+        bookmark.tags.retain(|existing| !tag_matches(existing, &tag));
+        if bookmark.tags.len() == original_len {
+            return Err("Bookmark does not have this tag".to_string());
+        }
+        
+        Ok(())
+    }
+
+    /// Every tag in use with the number of bookmarks carrying it, most used first.
+    pub fn get_all_tags(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for bookmark in self.tree.bookmarks.values() {
+            let tags: HashSet<String> = bookmark.tags.iter()
+                .filter_map(|tag| super::tags::normalize_tag(tag).ok())
+                .collect();
+            for tag in tags {
+                *counts.entry(tag).or_insert(0) += 1;
+            }
+        }
+        
+        let mut tags: Vec<(String, usize)> = counts.into_iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tags
+    }
+
+    pub fn get_bookmarks_by_tag(&self, tag: &str) -> Vec<&Bookmark> {
+        let Ok(tag) = super::tags::normalize_tag(tag) else {
+            return Vec::new();
+        };
+        
+        let mut bookmarks: Vec<&Bookmark> = self.tree.bookmarks.values()
+            .filter(|bookmark| bookmark.tags.iter().any(|existing| tag_matches(existing, &tag)))
+            .collect();
+        bookmarks.sort_by_key(|bookmark| bookmark.created_at);
+        bookmarks
+    }
+
     pub fn get_folder_contents(&self, folder_id: &str) -> Result<(Vec<&BookmarkFolder>, Vec<&Bookmark>), String> {
         let folder = self.tree.folders.get(folder_id).ok_or("Folder not found")?;
         
@@ -234,19 +435,409 @@ impl BookmarkManager {
             .map_err(|e| format!("Failed to export bookmarks: {}", e))
     }
 
+    /// Writes the tree as a Netscape bookmark file, the format other browsers
+    /// import. The root folder's contents become the top-level list.
+    pub fn export_bookmarks_html(&self) -> String {
+        fn write_folder(tree: &BookmarkTree, folder_id: &str, depth: usize, out: &mut String) {
+            let Some(folder) = tree.folders.get(folder_id).filter(|_| depth <= MAX_IMPORT_DEPTH) else {
+                return;
+            };
+            let indent = "    ".repeat(depth + 1);
+            
+            for child_id in &folder.children {
+                if let Some(bookmark) = tree.bookmarks.get(child_id) {
+                    out.push_str(&format!(
+                        "{}<DT><A HREF=\"{}\" ADD_DATE=\"{}\">{}</A>\n",
+                        indent,
+                        super::reader::escape_html(&bookmark.url),
+                        bookmark.created_at.timestamp(),
+                        super::reader::escape_html(&bookmark.title)
+                    ));
+                } else if let Some(child) = tree.folders.get(child_id) {
+                    out.push_str(&format!(
+                        "{}<DT><H3 ADD_DATE=\"{}\">{}</H3>\n{}<DL><p>\n",
+                        indent,
+                        child.created_at.timestamp(),
+                        super::reader::escape_html(&child.name),
+                        indent
+                    ));
+                    write_folder(tree, child_id, depth + 1, out);
+                    out.push_str(&format!("{}</DL><p>\n", indent));
+                }
+            }
+        }
+        
+        let mut out = String::from(
+            "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+             <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+             <TITLE>Bookmarks</TITLE>\n\
+             <H1>Bookmarks</H1>\n\
+             <DL><p>\n"
+        );
+        write_folder(&self.tree, &self.tree.root_folder_id, 0, &mut out);
+        out.push_str("</DL><p>\n");
+        out
+    }
+
+    /// Parses a Netscape bookmark file (the HTML export every major browser
+    /// produces). `<H3>` names the folder opened by the next `<DL>`.
+    pub fn parse_netscape_bookmarks(html: &str) -> Result<Vec<ImportedBookmark>, String> {
+        if !html.to_uppercase().contains("NETSCAPE-BOOKMARK-FILE") && !html.to_lowercase().contains("<dl") {
+            return Err("Not a Netscape bookmark file".to_string());
+        }
+        
+        let document = scraper::Html::parse_document(html);
+        let mut stack: Vec<(Option<ImportFolder>, Vec<ImportedBookmark>)> = vec![(None, Vec::new())];
+        let mut open_lists = Vec::new();
+        let mut pending_folder: Option<ImportFolder> = None;
+        
+        for element in document.select(&NETSCAPE_TOKEN_SELECTOR) {
+            // Close every list the parser ended before this element.
+            while open_lists.last().is_some_and(|list_id| !element.ancestors().any(|ancestor| ancestor.id() == *list_id)) {
+                open_lists.pop();
+                close_import_frame(&mut stack);
+            }
+            
+            match element.value().name() {
+                "h3" => pending_folder = Some((element_text(element), netscape_add_date(element))),
+                "a" => {
+                    let Some(url) = element.value().attr("href").map(|href| href.trim().to_string()) else {
+                        continue;
+                    };
+                    if url::Url::parse(&url).is_err() || url.starts_with("place:") {
+                        continue;
+                    }
+                    
+                    let title = Some(element_text(element))
+                        .filter(|title| !title.is_empty())
+                        .unwrap_or_else(|| url.clone());
+                    let created_at = netscape_add_date(element);
+                    
+                    if let Some((_, children)) = stack.last_mut() {
+                        children.push(ImportedBookmark::Link { title, url, created_at });
+                    }
+                }
+                _ => {
+                    open_lists.push(element.id());
+                    stack.push((pending_folder.take(), Vec::new()));
+                }
+            }
+        }
+        
+        while stack.len() > 1 {
+            close_import_frame(&mut stack);
+        }
+        
+        Ok(stack.pop().map(|(_, children)| children).unwrap_or_default())
+    }
+
+    /// Reads an exported `BookmarkTree` and returns the contents of its root folder.
+    pub fn parse_crate_json_bookmarks(data: &str) -> Result<Vec<ImportedBookmark>, String> {
+        let tree: BookmarkTree = serde_json::from_str(data)
+            .map_err(|e| format!("Failed to parse bookmark data: {}", e))?;
+
+        fn collect(tree: &BookmarkTree, folder_id: &str, depth: usize) -> Vec<ImportedBookmark> {
+            let folder = match tree.folders.get(folder_id) {
+                Some(folder) if depth <= MAX_IMPORT_DEPTH => folder,
+                _ => return Vec::new(),
+            };
+            
+            folder.children.iter()
+                .filter_map(|child_id| {
+                    if let Some(bookmark) = tree.bookmarks.get(child_id) {
+                        Some(ImportedBookmark::Link {
+                            title: bookmark.title.clone(),
+                            url: bookmark.url.clone(),
+                            created_at: Some(bookmark.created_at),
+                        })
+                    } else {
+                        tree.folders.get(child_id).map(|child| ImportedBookmark::Folder {
+                            name: child.name.clone(),
+                            children: collect(tree, child_id, depth + 1),
+                            created_at: Some(child.created_at),
+                        })
+                    }
+                })
+                .collect()
+        }
+        
+        if !tree.folders.contains_key(&tree.root_folder_id) {
+            return Err("Bookmark data has no root folder".to_string());
+        }
+        
+        Ok(collect(&tree, &tree.root_folder_id, 0))
+    }
+
+    /// Adds parsed bookmarks under `folder_id`, returning (bookmarks, folders) created.
+    pub fn add_imported(&mut self, folder_id: &str, items: &[ImportedBookmark]) -> Result<(usize, usize), String> {
+        let mut bookmarks = 0;
+        let mut folders = 0;
+        
+        for item in items {
+            match item {
+                ImportedBookmark::Link { title, url, created_at } => {
+                    let bookmark_id = self.insert_bookmark(title, url, Some(folder_id))?;
+                    if let (Some(created_at), Some(bookmark)) = (created_at, self.tree.bookmarks.get_mut(&bookmark_id)) {
+                        bookmark.created_at = *created_at;
+                    }
+                    bookmarks += 1;
+                }
+                ImportedBookmark::Folder { name, children, created_at } => {
+                    let child_id = self.create_folder(name, Some(folder_id))?;
+                    if let (Some(created_at), Some(folder)) = (created_at, self.tree.folders.get_mut(&child_id)) {
+                        folder.created_at = *created_at;
+                    }
+                    let (child_bookmarks, child_folders) = self.add_imported(&child_id, children)?;
+                    bookmarks += child_bookmarks;
+                    folders += child_folders + 1;
+                }
+            }
+        }
+        
+        Ok((bookmarks, folders))
+    }
+
+    /// Imports a Netscape bookmark file straight into the root folder, keeping
+    /// its folder nesting. Returns the number of (bookmarks, folders) created.
+    pub fn import_bookmarks_html(&mut self, html: &str) -> Result<(usize, usize), String> {
+        let items = Self::parse_netscape_bookmarks(html)?;
+        let root_folder_id = self.tree.root_folder_id.clone();
+        self.add_imported(&root_folder_id, &items)
+    }
+
+    /// Imports each source into its own folder under the root. A source that
+    /// fails to parse is reported and skipped without affecting the others.
+    pub fn import_bookmarks_batch(&mut self, sources: &[BookmarkImportSource]) -> Vec<BookmarkImportResult> {
+        sources.iter()
+            .map(|source| {
+                let parsed = match source.format {
+                    BookmarkImportFormat::CrateJson => Self::parse_crate_json_bookmarks(&source.data),
+                    BookmarkImportFormat::NetscapeHtml => Self::parse_netscape_bookmarks(&source.data),
+                };
+                
+                let imported = parsed.and_then(|items| {
+                    let folder_id = self.create_folder(&source.name, None)?;
+                    let (bookmarks, folders) = self.add_imported(&folder_id, &items)?;
+                    Ok((folder_id, bookmarks, folders))
+                });
+                
+                match imported {
+                    Ok((folder_id, bookmarks_imported, folders_imported)) => BookmarkImportResult {
+                        name: source.name.clone(),
+                        folder_id: Some(folder_id),
+                        bookmarks_imported,
+                        folders_imported,
+                        error: None,
+                    },
+                    Err(error) => BookmarkImportResult {
+                        name: source.name.clone(),
+                        folder_id: None,
+                        bookmarks_imported: 0,
+                        folders_imported: 0,
+                        error: Some(error),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Proposes moving bookmarks that sit directly in the root folder into topic
+    /// folders under the root, reusing a folder with the same name when one exists.
+    /// Unless `dry_run` is set, missing folders are created and the moves applied.
+    pub fn auto_categorize_bookmarks(&mut self, dry_run: bool) -> Result<Vec<CategorizationAction>, String> {
+        let root_folder_id = self.tree.root_folder_id.clone();
+        let root_children = self.tree.folders.get(&root_folder_id)
+            .map(|folder| folder.children.clone())
+            .unwrap_or_default();
+        
+        // `None` marks a folder a dry run has already planned to create.
+        let mut category_folders: HashMap<String, Option<String>> = root_children.iter()
+            .filter_map(|id| self.tree.folders.get(id))
+            .map(|folder| (folder.name.to_lowercase(), Some(folder.id.clone())))
+            .collect();
+        
+        let mut actions = Vec::new();
+        for bookmark_id in &root_children {
+            let Some(bookmark) = self.tree.bookmarks.get(bookmark_id) else {
+                continue;
+            };
+            let Some(category) = bookmark_category(&bookmark.url) else {
+                continue;
+            };
+            
+            let key = category.to_lowercase();
+            let planned_folder = category_folders.get(&key).cloned();
+            let mut action = CategorizationAction {
+                bookmark_id: bookmark.id.clone(),
+                title: bookmark.title.clone(),
+                url: bookmark.url.clone(),
+                category: category.to_string(),
+                creates_folder: planned_folder.is_none(),
+                folder_id: planned_folder.flatten(),
+            };
+            
+            if dry_run {
+                category_folders.entry(key).or_insert(None);
+            } else {
+                let folder_id = match action.folder_id.clone() {
+                    Some(folder_id) => folder_id,
+                    None => {
+                        let folder_id = self.create_folder(category, None)?;
+                        category_folders.insert(key, Some(folder_id.clone()));
+                        folder_id
+                    }
+                };
+                self.move_bookmark(&action.bookmark_id, &folder_id)?;
+                action.folder_id = Some(folder_id);
+            }
+            
+            actions.push(action);
+        }
+        
+        Ok(actions)
+    }
+
+    /// The folder a new bookmark for `url` most likely belongs in: the folder
+    /// holding the most bookmarks from the same host (ties go to the one filed
+    /// most recently), otherwise the top-level folder named after its category.
+    pub fn suggest_bookmark_folder(&self, url: &str) -> Option<String> {
+        let host = super::network::url_host(url)?;
+        let host = host.trim_start_matches("www.");
+        
+        let mut votes: HashMap<&str, (usize, chrono::DateTime<chrono::Utc>)> = HashMap::new();
+        for bookmark in self.tree.bookmarks.values() {
+            let Some(folder_id) = bookmark.folder_id.as_deref() else {
+                continue;
+            };
+            if folder_id == self.tree.root_folder_id || !self.tree.folders.contains_key(folder_id) {
+                continue;
+            }
+            let same_host = super::network::url_host(&bookmark.url)
+                .is_some_and(|bookmark_host| bookmark_host.trim_start_matches("www.") == host);
+            if same_host {
+                let vote = votes.entry(folder_id).or_insert((0, bookmark.created_at));
+                vote.0 += 1;
+                vote.1 = vote.1.max(bookmark.created_at);
+            }
+        }
+        
+        if let Some((folder_id, _)) = votes.into_iter().max_by_key(|(_, vote)| *vote) {
+            return Some(folder_id.to_string());
+        }
+        
+        let category = bookmark_category(url)?.to_lowercase();
+        self.tree.folders.get(&self.tree.root_folder_id)?
+            .children
+            .iter()
+            .filter_map(|id| self.tree.folders.get(id))
+            .find(|folder| folder.name.to_lowercase() == category)
+            .map(|folder| folder.id.clone())
+    }
+
     pub fn import_bookmarks(&mut self, data: &str) -> Result<(), String> {
         let imported_tree: BookmarkTree = serde_json::from_str(data)
             .map_err(|e| format!("Failed to parse bookmark data: {}", e))?;
         
+        if !imported_tree.folders.contains_key(&imported_tree.root_folder_id) {
+            return Err("Bookmark data has no root folder".to_string());
+        }
+        folder_subtree(&imported_tree, &imported_tree.root_folder_id)?;
+        
         self.tree = imported_tree;
         Ok(())
     }
 }
 
+/// `folder_id` and every folder below it, parents before children. Fails when
+/// a folder is reachable twice (a cycle or shared child) or nesting runs deeper
+/// than `MAX_IMPORT_DEPTH`, both of which only malformed imported trees contain.
+fn folder_subtree(tree: &BookmarkTree, folder_id: &str) -> Result<Vec<String>, String> {
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::new();
+    let mut pending = vec![(folder_id.to_string(), 0)];
+    
+    while let Some((id, depth)) = pending.pop() {
+        if depth > MAX_IMPORT_DEPTH {
+            return Err("Bookmark folders are nested too deeply".to_string());
+        }
+        if !visited.insert(id.clone()) {
+            return Err(format!("Bookmark folder {} appears more than once in the tree", id));
+        }
+        
+        if let Some(folder) = tree.folders.get(&id) {
+            pending.extend(folder.children.iter()
+                .filter(|child_id| tree.folders.contains_key(*child_id))
+                .map(|child_id| (child_id.clone(), depth + 1)));
+        }
+        ordered.push(id);
+    }
+    
+    Ok(ordered)
+}
+
+/// The form URLs are compared in for duplicate detection: lowercase scheme
+/// and host, and no trailing slash on the path.
+fn normalize_bookmark_url(url: &str) -> String {
+    let url = url.trim();
+    match url::Url::parse(url) {
+        Ok(parsed) => format!(
+            "{}{}",
+            parsed[..url::Position::AfterPath].trim_end_matches('/'),
+            &parsed[url::Position::AfterPath..]
+        ),
+        Err(_) => url.trim_end_matches('/').to_string(),
+    }
+}
+
+fn tag_matches(existing: &str, normalized: &str) -> bool {
+    existing.trim().to_lowercase() == normalized
+}
+
+/// Name and `ADD_DATE` of a folder whose `<DL>` is open during import.
+type ImportFolder = (String, Option<chrono::DateTime<chrono::Utc>>);
+
+/// Text content of an element with runs of whitespace collapsed.
+fn element_text(element: scraper::ElementRef) -> String {
+    element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+}
+
+/// `ADD_DATE` is seconds since the Unix epoch.
+fn netscape_add_date(element: scraper::ElementRef) -> Option<chrono::DateTime<chrono::Utc>> {
+    let seconds = element.value().attr("add_date")?.trim().parse::<i64>().ok()?;
+    chrono::DateTime::from_timestamp(seconds, 0)
+}
+
+/// Closes the innermost open `<DL>`. Named frames become folders in their
+/// parent; unnamed ones (the outer list) are flattened into it.
+fn close_import_frame(stack: &mut Vec<(Option<ImportFolder>, Vec<ImportedBookmark>)>) {
+    if stack.len() < 2 {
+        return;
+    }
+    
+    if let Some((folder, children)) = stack.pop() {
+        if let Some((_, parent)) = stack.last_mut() {
+            match folder {
+                Some((name, created_at)) => parent.push(ImportedBookmark::Folder { name, children, created_at }),
+                None => parent.extend(children),
+            }
+        }
+    }
+}
+
+pub async fn record_visit(url: &str) -> bool {
+    BOOKMARK_MANAGER.write().await.record_visit(url)
+}
+
 #[tauri::command]
-pub async fn add_bookmark(title: String, url: String, folder_id: Option<String>) -> Result<String, String> {
+pub async fn add_bookmark(title: String, url: String, folder_id: Option<String>, error_on_duplicate: Option<bool>) -> Result<String, String> {
     let mut manager = BOOKMARK_MANAGER.write().await;
-    manager.add_bookmark(&title, &url, folder_id.as_deref())
+    manager.add_bookmark(&title, &url, folder_id.as_deref(), error_on_duplicate.unwrap_or(false))
+}
+
+#[tauri::command]
+pub async fn is_bookmarked(url: String) -> Result<Option<String>, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.is_bookmarked(&url))
 }
 
 #[tauri::command]
@@ -279,6 +870,12 @@ pub async fn move_bookmark(bookmark_id: String, new_folder_id: String) -> Result
     manager.move_bookmark(&bookmark_id, &new_folder_id)
 }
 
+#[tauri::command]
+pub async fn move_bookmark_folder(folder_id: String, new_parent_id: String, index: Option<usize>) -> Result<(), String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    manager.move_folder(&folder_id, &new_parent_id, index)
+}
+
 #[tauri::command]
 pub async fn search_bookmarks(query: String) -> Result<Vec<Bookmark>, String> {
     let manager = BOOKMARK_MANAGER.read().await;
@@ -304,8 +901,311 @@ pub async fn export_bookmarks() -> Result<String, String> {
     manager.export_bookmarks()
 }
 
+#[tauri::command]
+pub async fn export_bookmarks_html() -> Result<String, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.export_bookmarks_html())
+}
+
 #[tauri::command]
 pub async fn import_bookmarks(data: String) -> Result<(), String> {
     let mut manager = BOOKMARK_MANAGER.write().await;
     manager.import_bookmarks(&data)
+}
+
+#[tauri::command]
+pub async fn import_bookmarks_html(data: String) -> Result<(usize, usize), String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    manager.import_bookmarks_html(&data)
+}
+
+#[tauri::command]
+pub async fn import_bookmarks_batch(sources: Vec<BookmarkImportSource>) -> Result<Vec<BookmarkImportResult>, String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    Ok(manager.import_bookmarks_batch(&sources))
+}
+
+#[tauri::command]
+pub async fn auto_categorize_bookmarks(dry_run: bool) -> Result<Vec<CategorizationAction>, String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    manager.auto_categorize_bookmarks(dry_run)
+}
+
+#[tauri::command]
+pub async fn suggest_bookmark_folder(url: String) -> Result<Option<String>, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.suggest_bookmark_folder(&url))
+}
+
+#[tauri::command]
+pub async fn add_bookmark_tag(bookmark_id: String, tag: String) -> Result<(), String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    manager.add_bookmark_tag(&bookmark_id, &tag)
+}
+
+#[tauri::command]
+pub async fn remove_bookmark_tag(bookmark_id: String, tag: String) -> Result<(), String> {
+    let mut manager = BOOKMARK_MANAGER.write().await;
+    manager.remove_bookmark_tag(&bookmark_id, &tag)
+}
+
+#[tauri::command]
+pub async fn get_all_tags() -> Result<Vec<(String, usize)>, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.get_all_tags())
+}
+
+#[tauri::command]
+pub async fn get_bookmarks_by_tag(tag: String) -> Result<Vec<Bookmark>, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.get_bookmarks_by_tag(&tag).into_iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn record_bookmark_visit(url: String) -> Result<bool, String> {
+    Ok(record_visit(&url).await)
+}
+
+#[tauri::command]
+pub async fn get_most_visited_bookmarks(limit: usize) -> Result<Vec<Bookmark>, String> {
+    let manager = BOOKMARK_MANAGER.read().await;
+    Ok(manager.get_most_visited(limit).into_iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn netscape_import_keeps_folder_nesting() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">Work &amp; Play</H3>
+    <DL><p>
+        <DT><A HREF="https://example.com/" ADD_DATE="1700000001">Example</A>
+        <DT><H3>Empty</H3>
+        <DL><p>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://rust-lang.org/">  Rust
+        Lang </A>
+    <DT><A HREF="place:sort=8">Recent</A>
+</DL><p>"#;
+        
+        let items = BookmarkManager::parse_netscape_bookmarks(html).unwrap();
+        
+        assert_eq!(items, vec![
+            ImportedBookmark::Folder {
+                name: "Work & Play".to_string(),
+                children: vec![
+                    ImportedBookmark::Link {
+                        title: "Example".to_string(),
+                        url: "https://example.com/".to_string(),
+                        created_at: chrono::DateTime::from_timestamp(1700000001, 0),
+                    },
+                    ImportedBookmark::Folder { name: "Empty".to_string(), children: Vec::new(), created_at: None },
+                ],
+                created_at: chrono::DateTime::from_timestamp(1700000000, 0),
+            },
+            ImportedBookmark::Link {
+                title: "Rust Lang".to_string(),
+                url: "https://rust-lang.org/".to_string(),
+                created_at: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn tags_are_matched_case_insensitively() {
+        let mut manager = BookmarkManager::new();
+        let rust = manager.add_bookmark("Rust", "https://rust-lang.org/", None, false).unwrap();
+        let docs = manager.add_bookmark("Docs", "https://docs.rs/", None, false).unwrap();
+        manager.add_bookmark_tag(&rust, " Programming ").unwrap();
+        manager.add_bookmark_tag(&rust, "PROGRAMMING").unwrap();
+        manager.add_bookmark_tag(&docs, "programming").unwrap();
+        manager.add_bookmark_tag(&docs, "Reference").unwrap();
+        
+        assert_eq!(manager.tree.bookmarks[&rust].tags, vec!["programming".to_string()]);
+        assert!(manager.add_bookmark_tag(&rust, "   ").is_err());
+        assert_eq!(manager.get_bookmarks_by_tag("Programming").len(), 2);
+        assert_eq!(manager.get_all_tags(), vec![("programming".to_string(), 2), ("reference".to_string(), 1)]);
+        
+        manager.remove_bookmark_tag(&docs, "REFERENCE").unwrap();
+        assert!(manager.remove_bookmark_tag(&docs, "reference").is_err());
+        assert!(manager.get_bookmarks_by_tag("reference").is_empty());
+    }
+
+    #[test]
+    fn batch_import_reports_each_source() {
+        let mut manager = BookmarkManager::new();
+        let sources = vec![
+            BookmarkImportSource {
+                name: "Firefox".to_string(),
+                format: BookmarkImportFormat::NetscapeHtml,
+                data: r#"<DL><p>
+    <DT><A HREF="https://example.com/">Example</A>
+    <DT><H3>Reading</H3>
+    <DL><p>
+        <DT><A HREF="https://rust-lang.org/">Rust</A>
+    </DL><p>
+</DL><p>"#.to_string(),
+            },
+            BookmarkImportSource {
+                name: "Broken".to_string(),
+                format: BookmarkImportFormat::CrateJson,
+                data: "{ not json".to_string(),
+            },
+        ];
+        
+        let results = manager.import_bookmarks_batch(&sources);
+        
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].bookmarks_imported, 2);
+        assert_eq!(results[0].folders_imported, 1);
+        assert!(results[0].error.is_none());
+        let folder_id = results[0].folder_id.as_ref().unwrap();
+        assert_eq!(manager.tree.folders[folder_id].name, "Firefox");
+        assert!(manager.tree.folders[&manager.tree.root_folder_id].children.contains(folder_id));
+        
+        assert!(results[1].folder_id.is_none());
+        assert_eq!(results[1].bookmarks_imported, 0);
+        assert!(results[1].error.is_some());
+        assert_eq!(manager.tree.bookmarks.len(), 2);
+        assert_eq!(manager.tree.folders.len(), 3);
+    }
+
+    #[test]
+    fn duplicate_bookmarks_return_the_existing_id() {
+        let mut manager = BookmarkManager::new();
+        let rust = manager.add_bookmark("Rust", "https://rust-lang.org/learn/", None, false).unwrap();
+        
+        let again = manager.add_bookmark("Rust again", "HTTPS://Rust-Lang.org/learn", None, false).unwrap();
+        
+        assert_eq!(again, rust);
+        assert_eq!(manager.tree.bookmarks.len(), 1);
+        assert!(manager.add_bookmark("Rust", "https://rust-lang.org/learn", None, true).is_err());
+        assert_eq!(manager.is_bookmarked("https://RUST-LANG.org/learn/"), Some(rust));
+        assert_eq!(manager.is_bookmarked("https://rust-lang.org/Learn"), None);
+        assert_eq!(manager.is_bookmarked("https://docs.rs/"), None);
+    }
+
+    #[test]
+    fn netscape_import_builds_the_tree_under_the_root() {
+        let mut manager = BookmarkManager::new();
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">Toolbar</H3>
+    <DL><p>
+        <DT><A HREF="https://example.com/" ADD_DATE="1700000001">Example</A>
+        <DT><A>No link</A>
+    </DL><p>
+    <DT><A HREF="https://rust-lang.org/">Rust</A>
+</DL><p>"#;
+        
+        assert_eq!(manager.import_bookmarks_html(html).unwrap(), (2, 1));
+        
+        let root = &manager.tree.folders[&manager.tree.root_folder_id];
+        assert_eq!(root.children.len(), 2);
+        let toolbar = &manager.tree.folders[&root.children[0]];
+        assert_eq!(toolbar.name, "Toolbar");
+        assert_eq!(toolbar.created_at.timestamp(), 1700000000);
+        assert_eq!(toolbar.children.len(), 1);
+        let example = &manager.tree.bookmarks[&toolbar.children[0]];
+        assert_eq!(example.url, "https://example.com/");
+        assert_eq!(example.folder_id.as_ref(), Some(&toolbar.id));
+        assert_eq!(example.created_at.timestamp(), 1700000001);
+        assert_eq!(manager.tree.bookmarks[&root.children[1]].title, "Rust");
+        assert!(manager.import_bookmarks_html("just some text").is_err());
+    }
+
+    #[test]
+    fn html_export_round_trips_through_import() {
+        let mut manager = BookmarkManager::new();
+        let reading = manager.create_folder("Reading & <Notes>", None).unwrap();
+        let nested = manager.create_folder("Nested", Some(&reading)).unwrap();
+        manager.add_bookmark("Rust \"book\"", "https://doc.rust-lang.org/book/?a=1&b=2", Some(&nested), false).unwrap();
+        manager.add_bookmark("Example", "https://example.com/", None, false).unwrap();
+        manager.create_folder("Empty", None).unwrap();
+        let created_at = chrono::DateTime::from_timestamp(1700000000, 0).unwrap();
+        manager.tree.folders.values_mut().for_each(|folder| folder.created_at = created_at);
+        manager.tree.bookmarks.values_mut().for_each(|bookmark| bookmark.created_at = created_at);
+        let expected = BookmarkManager::parse_crate_json_bookmarks(&manager.export_bookmarks().unwrap()).unwrap();
+        
+        let mut reimported = BookmarkManager::new();
+        reimported.import_bookmarks_html(&manager.export_bookmarks_html()).unwrap();
+        
+        let actual = BookmarkManager::parse_crate_json_bookmarks(&reimported.export_bookmarks().unwrap()).unwrap();
+        assert_eq!(actual, expected);
+        assert_eq!(expected.len(), 3);
+    }
+
+    #[test]
+    fn visits_are_counted_and_ordered() {
+        let mut manager = BookmarkManager::new();
+        let rust = manager.add_bookmark("Rust", "https://rust-lang.org/", None, false).unwrap();
+        let docs = manager.add_bookmark("Docs", "https://docs.rs/", None, false).unwrap();
+        let crates = manager.add_bookmark("Crates", "https://crates.io/", None, false).unwrap();
+        manager.add_bookmark("Unvisited", "https://example.com/", None, false).unwrap();
+        
+        for url in ["https://docs.rs", "https://rust-lang.org/", "HTTPS://DOCS.RS/", "https://crates.io/", "https://docs.rs/"] {
+            assert!(manager.record_visit(url));
+        }
+        assert!(!manager.record_visit("https://unknown.example/"));
+        
+        assert_eq!(manager.tree.bookmarks[&docs].visit_count, 3);
+        assert!(manager.tree.bookmarks[&docs].last_accessed.is_some());
+        assert!(manager.tree.bookmarks.values().any(|bookmark| bookmark.visit_count == 0 && bookmark.last_accessed.is_none()));
+        manager.tree.bookmarks.get_mut(&rust).unwrap().last_accessed = chrono::DateTime::from_timestamp(1700000000, 0);
+        
+        let most_visited: Vec<&str> = manager.get_most_visited(10).iter().map(|bookmark| bookmark.id.as_str()).collect();
+        assert_eq!(most_visited, vec![docs.as_str(), crates.as_str(), rust.as_str()]);
+        assert_eq!(manager.get_most_visited(1).len(), 1);
+    }
+
+    #[test]
+    fn cyclic_folders_give_an_error_instead_of_looping() {
+        let mut manager = BookmarkManager::new();
+        let outer = manager.create_folder("Outer", None).unwrap();
+        let inner = manager.create_folder("Inner", Some(&outer)).unwrap();
+        manager.tree.folders.get_mut(&inner).unwrap().children.push(outer.clone());
+        let cyclic = manager.export_bookmarks().unwrap();
+        
+        assert!(manager.delete_folder(&outer).unwrap_err().contains("appears more than once"));
+        assert!(manager.tree.folders.contains_key(&inner));
+        
+        let mut importer = BookmarkManager::new();
+        let root_folder_id = importer.tree.root_folder_id.clone();
+        assert!(importer.import_bookmarks(&cyclic).is_err());
+        assert_eq!(importer.tree.root_folder_id, root_folder_id);
+    }
+
+    #[test]
+    fn folders_move_but_not_into_their_own_subfolders() {
+        let mut manager = BookmarkManager::new();
+        let root_folder_id = manager.tree.root_folder_id.clone();
+        let work = manager.create_folder("Work", None).unwrap();
+        let projects = manager.create_folder("Projects", Some(&work)).unwrap();
+        let archive = manager.create_folder("Archive", Some(&projects)).unwrap();
+        let personal = manager.create_folder("Personal", None).unwrap();
+        
+        manager.move_folder(&archive, &root_folder_id, Some(0)).unwrap();
+        
+        assert_eq!(manager.tree.folders[&root_folder_id].children, vec![archive.clone(), work.clone(), personal.clone()]);
+        assert!(manager.tree.folders[&projects].children.is_empty());
+        assert_eq!(manager.tree.folders[&archive].parent_id.as_ref(), Some(&root_folder_id));
+        
+        manager.move_folder(&projects, &personal, None).unwrap();
+        
+        assert!(manager.tree.folders[&work].children.is_empty());
+        assert_eq!(manager.tree.folders[&personal].children, vec![projects.clone()]);
+        
+        assert!(manager.move_folder(&personal, &projects, None).is_err());
+        assert!(manager.move_folder(&personal, &personal, None).is_err());
+        assert!(manager.move_folder(&root_folder_id, &work, None).is_err());
+        assert_eq!(manager.tree.folders[&personal].children, vec![projects.clone()]);
+        assert!(manager.tree.folders[&root_folder_id].children.contains(&personal));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Resolve, Resolving};
+use tokio::sync::RwLock;
+use once_cell::sync::Lazy;
+
+const RECORD_TYPE_A: u16 = 1;
+const RECORD_TYPE_AAAA: u16 = 28;
+const MIN_CACHE_TTL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum DohProvider {
+    #[default]
+    Cloudflare,
+    Quad9,
+}
+
+impl DohProvider {
+    /// JSON API endpoints are addressed by IP so that resolving them never
+    /// has to go through the resolver they are meant to replace.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            DohProvider::Cloudflare => "https://1.1.1.1/dns-query",
+            DohProvider::Quad9 => "https://9.9.9.9:5053/dns-query",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    #[serde(rename = "TTL")]
+    ttl: u64,
+    data: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DohResolution {
+    pub addresses: Vec<IpAddr>,
+    pub ttl: Duration,
+}
+
+struct CachedResolution {
+    addresses: Vec<IpAddr>,
+    expires_at: Instant,
+}
+
+/// Shared so lookups reuse pooled connections to the provider.
+static DOH_CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+static DOH_CACHE: Lazy<RwLock<DohCache>> = Lazy::new(|| {
+    RwLock::new(DohCache::new())
+});
+
+pub struct DohCache {
+    entries: HashMap<String, CachedResolution>,
+}
+
+impl DohCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        self.entries.get(host)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.addresses.clone())
+    }
+
+    pub fn insert(&mut self, host: &str, resolution: DohResolution, now: Instant) {
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        self.entries.insert(host.to_string(), CachedResolution {
+            addresses: resolution.addresses,
+            expires_at: now + resolution.ttl,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Parses a DoH JSON (`application/dns-json`) response into its A/AAAA addresses.
+/// The smallest answer TTL is used for caching, clamped to a sane minimum.
+pub fn parse_doh_response(body: &str) -> Result<DohResolution, String> {
+    let response: DohResponse = serde_json::from_str(body)
+        .map_err(|e| format!("Invalid DoH response: {}", e))?;
+    
+    if response.status != 0 {
+        return Err(format!("DoH query failed with status {}", response.status));
+    }
+    
+    let mut addresses = Vec::new();
+    let mut ttl = u64::MAX;
+    
+    for answer in &response.answer {
+        if answer.record_type != RECORD_TYPE_A && answer.record_type != RECORD_TYPE_AAAA {
+            continue;
+        }
+        
+        if let Ok(address) = answer.data.parse::<IpAddr>() {
+            addresses.push(address);
+            ttl = ttl.min(answer.ttl);
+        }
+    }
+    
+    if addresses.is_empty() {
+        return Err("DoH response contained no addresses".to_string());
+    }
+    
+    Ok(DohResolution {
+        addresses,
+        ttl: Duration::from_secs(ttl.max(MIN_CACHE_TTL_SECS)),
+    })
+}
+
+async fn query(client: &reqwest::Client, provider: DohProvider, host: &str, record_type: &str) -> Result<DohResolution, String> {
+    let body = client.get(provider.endpoint())
+        .query(&[("name", host), ("type", record_type)])
+        .header("accept", "application/dns-json")
+        .send()
+        .await
+        .map_err(|e| format!("DoH request failed: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read DoH response: {}", e))?;
+    
+    parse_doh_response(&body)
+}
+
+pub async fn resolve(provider: DohProvider, host: &str) -> Result<Vec<IpAddr>, String> {
+    if let Ok(address) = host.parse::<IpAddr>() {
+        return Ok(vec![address]);
+    }
+    
+    let host = host.trim_end_matches('.').to_lowercase();
+    
+    if let Some(addresses) = DOH_CACHE.read().await.get(&host, Instant::now()) {
+        return Ok(addresses);
+    }
+    
+    let (ipv4, ipv6) = tokio::join!(
+        query(&DOH_CLIENT, provider, &host, "A"),
+        query(&DOH_CLIENT, provider, &host, "AAAA"),
+    );
+    
+    let resolution = match (ipv4, ipv6) {
+        (Ok(mut ipv4), Ok(ipv6)) => {
+            ipv4.addresses.extend(ipv6.addresses);
+            ipv4.ttl = ipv4.ttl.min(ipv6.ttl);
+            ipv4
+        }
+        (Ok(resolution), Err(_)) | (Err(_), Ok(resolution)) => resolution,
+        (Err(e), Err(_)) => return Err(e),
+    };
+    
+    let addresses = resolution.addresses.clone();
+    DOH_CACHE.write().await.insert(&host, resolution, Instant::now());
+    
+    Ok(addresses)
+}
+
+/// Resolver plugged into reqwest clients so every lookup goes through DoH.
+pub struct DohResolver {
+    provider: DohProvider,
+}
+
+impl DohResolver {
+    pub fn new(provider: DohProvider) -> Self {
+        Self { provider }
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let provider = self.provider;
+        let host = name.as_str().to_string();
+        
+        Box::pin(async move {
+            let addresses = resolve(provider, &host).await?;
+            let addrs: reqwest::dns::Addrs = Box::new(
+                addresses.into_iter().map(|ip| SocketAddr::new(ip, 0))
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+#[tauri::command]
+pub async fn resolve_host(host: String) -> Result<Vec<IpAddr>, String> {
+    let provider = super::settings::current_settings().await.privacy.dns_over_https_provider;
+    resolve(provider, &host).await
+}
+
+#[tauri::command]
+pub async fn clear_dns_cache() -> Result<(), String> {
+    DOH_CACHE.write().await.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doh_response_keeps_addresses_and_the_smallest_ttl() {
+        let body = r#"{
+            "Status": 0,
+            "TC": false,
+            "Question": [{ "name": "www.example.com", "type": 1 }],
+            "Answer": [
+                { "name": "www.example.com", "type": 5, "TTL": 3600, "data": "example.com." },
+                { "name": "example.com", "type": 1, "TTL": 300, "data": "93.184.216.34" },
+                { "name": "example.com", "type": 28, "TTL": 120, "data": "2606:2800:220:1:248:1893:25c8:1946" }
+            ]
+        }"#;
+        
+        let resolution = parse_doh_response(body).unwrap();
+        
+        assert_eq!(resolution.addresses, vec![
+            "93.184.216.34".parse::<IpAddr>().unwrap(),
+            "2606:2800:220:1:248:1893:25c8:1946".parse::<IpAddr>().unwrap(),
+        ]);
+        assert_eq!(resolution.ttl, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn doh_response_ttl_is_clamped_and_failures_are_errors() {
+        let short = r#"{ "Status": 0, "Answer": [{ "name": "a.com", "type": 1, "TTL": 5, "data": "1.2.3.4" }] }"#;
+        assert_eq!(parse_doh_response(short).unwrap().ttl, Duration::from_secs(MIN_CACHE_TTL_SECS));
+        
+        assert!(parse_doh_response(r#"{ "Status": 3 }"#).is_err());
+        assert!(parse_doh_response(r#"{ "Status": 0, "Answer": [{ "name": "a.com", "type": 5, "TTL": 60, "data": "b.com." }] }"#).is_err());
+        assert!(parse_doh_response("not json").is_err());
+    }
+
+    #[test]
+    fn cached_resolutions_expire_at_their_ttl() {
+        let mut cache = DohCache::new();
+        let now = Instant::now();
+        let addresses = vec!["1.2.3.4".parse::<IpAddr>().unwrap()];
+        cache.insert("a.com", DohResolution { addresses: addresses.clone(), ttl: Duration::from_secs(60) }, now);
+        
+        assert_eq!(cache.get("a.com", now), Some(addresses.clone()));
+        assert_eq!(cache.get("a.com", now + Duration::from_secs(59)), Some(addresses));
+        assert_eq!(cache.get("a.com", now + Duration::from_secs(60)), None);
+        assert_eq!(cache.get("b.com", now), None);
+        
+        cache.insert("b.com", DohResolution { addresses: vec![], ttl: Duration::from_secs(60) }, now + Duration::from_secs(61));
+        assert!(!cache.entries.contains_key("a.com"));
+    }
+}
\ No newline at end of file
@@ -1,9 +1,41 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
+use super::tags::normalize_tag;
+
+/// Minimum gap between `download://progress` events for one download.
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How far back byte samples are kept when measuring download speed.
+const SPEED_WINDOW: Duration = Duration::from_secs(5);
+/// Minimum time between progress samples, so fast downloads reporting many
+/// small chunks don't grow the sample queue without bound.
+const PROGRESS_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// Headers a caller may not set: hop-by-hop headers plus those the HTTP
+/// client manages itself.
+const FORBIDDEN_DOWNLOAD_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "content-length",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DownloadStatus {
@@ -30,6 +62,72 @@ pub struct Download {
     pub error_message: Option<String>,
     pub referrer: Option<String>,
     pub user_agent: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Extra request headers such as cookies or auth tokens. Never serialized
+    /// so they don't leak into exports.
+    #[serde(default, skip_serializing)]
+    pub headers: HashMap<String, String>,
+}
+
+impl Download {
+    /// Headers to send when fetching this download: user agent and referrer,
+    /// then any caller-supplied extras.
+    pub fn request_headers(&self) -> Result<reqwest::header::HeaderMap, String> {
+        let mut entries: Vec<(&str, &str)> = Vec::new();
+        if let Some(user_agent) = &self.user_agent {
+            entries.push(("user-agent", user_agent));
+        }
+        if let Some(referrer) = &self.referrer {
+            entries.push(("referer", referrer));
+        }
+        entries.extend(self.headers.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+        
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in entries {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            let value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header: {}", name))?;
+            header_map.insert(name, value);
+        }
+        
+        Ok(header_map)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgressEvent {
+    pub download_id: String,
+    pub status: DownloadStatus,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub error_message: Option<String>,
+}
+
+impl From<&Download> for DownloadProgressEvent {
+    fn from(download: &Download) -> Self {
+        Self {
+            download_id: download.id.clone(),
+            status: download.status.clone(),
+            downloaded_bytes: download.downloaded_bytes,
+            total_bytes: download.total_bytes,
+            error_message: download.error_message.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadProgress {
+    pub download_id: String,
+    pub status: DownloadStatus,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub percent: Option<f64>,
+    /// Average over the last few seconds; zero unless the download is running.
+    pub speed_bytes_per_sec: f64,
+    /// `None` when the total size or the current speed is unknown.
+    pub eta_seconds: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +146,13 @@ static DOWNLOAD_MANAGER: Lazy<RwLock<DownloadManager>> = Lazy::new(|| {
 pub struct DownloadManager {
     pub downloads: HashMap<String, Download>,
     pub download_directory: PathBuf,
+    /// Recent (time, downloaded bytes) readings per download, oldest first.
+    progress_samples: HashMap<String, VecDeque<(Instant, u64)>>,
+    pub max_concurrent_downloads: usize,
+    /// Pending downloads waiting for a free slot, in start order.
+    queue: VecDeque<String>,
+    /// Downloads whose driver holds a slot.
+    running: HashSet<String>,
 }
 
 impl DownloadManager {
@@ -58,6 +163,10 @@ impl DownloadManager {
         Self {
             downloads: HashMap::new(),
             download_directory,
+            progress_samples: HashMap::new(),
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            queue: VecDeque::new(),
+            running: HashSet::new(),
         }
     }
 
@@ -67,13 +176,25 @@ impl DownloadManager {
         filename: Option<&str>,
         referrer: Option<&str>,
     ) -> Result<String, String> {
+        self.start_download_with_headers(url, filename, referrer, HashMap::new())
+    }
+
+    pub fn start_download_with_headers(
+        &mut self,
+        url: &str,
+        filename: Option<&str>,
+        referrer: Option<&str>,
+        headers: HashMap<String, String>,
+    ) -> Result<String, String> {
+        let headers = validate_download_headers(headers)?;
         let download_id = Uuid::new_v4().to_string();
         
         let filename = filename
-            .map(|f| f.to_string())
+            .and_then(sanitize_filename)
             .or_else(|| self.extract_filename_from_url(url))
             .unwrap_or_else(|| format!("download_{}", download_id));
         
+        let filename = self.unique_filename(&filename);
         let file_path = self.download_directory.join(&filename);
         
         let download = Download {
@@ -90,9 +211,12 @@ impl DownloadManager {
             error_message: None,
             referrer: referrer.map(|r| r.to_string()),
             user_agent: Some("Sw3do Browser/1.0".to_string()),
+            tags: Vec::new(),
+            headers,
         };
         
         self.downloads.insert(download_id.clone(), download);
+        self.queue.push_back(download_id.clone());
         Ok(download_id)
     }
 
@@ -110,16 +234,122 @@ impl DownloadManager {
             download.total_bytes = Some(total);
         }
         download.status = DownloadStatus::InProgress;
+        self.record_progress_sample(download_id, downloaded_bytes, Instant::now());
         
         Ok(())
     }
 
+    /// Records bytes written by the download driver without touching the
+    /// status, which the user may have changed meanwhile. Returns that status.
+    fn record_received_bytes(&mut self, download_id: &str, downloaded_bytes: u64) -> Option<DownloadStatus> {
+        let download = self.downloads.get_mut(download_id)?;
+        download.downloaded_bytes = downloaded_bytes;
+        let status = download.status.clone();
+        self.record_progress_sample(download_id, downloaded_bytes, Instant::now());
+        Some(status)
+    }
+
+    pub fn record_progress_sample(&mut self, download_id: &str, downloaded_bytes: u64, at: Instant) {
+        let samples = self.progress_samples.entry(download_id.to_string()).or_default();
+        if samples.back().is_some_and(|(time, _)| at.duration_since(*time) < PROGRESS_SAMPLE_INTERVAL) {
+            return;
+        }
+        samples.push_back((at, downloaded_bytes));
+        while samples.len() > 2 && samples.front().is_some_and(|(time, _)| at.duration_since(*time) > SPEED_WINDOW) {
+            samples.pop_front();
+        }
+    }
+
+    /// Bytes per second across the samples within `SPEED_WINDOW` of `now`.
+    fn current_speed(&self, download_id: &str, now: Instant) -> f64 {
+        let Some(samples) = self.progress_samples.get(download_id) else {
+            return 0.0;
+        };
+        let mut recent = samples.iter().filter(|(time, _)| now.duration_since(*time) <= SPEED_WINDOW);
+        let (Some((first_time, first_bytes)), Some((last_time, last_bytes))) = (recent.next(), recent.next_back()) else {
+            return 0.0;
+        };
+        
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        last_bytes.saturating_sub(*first_bytes) as f64 / elapsed
+    }
+
+    pub fn get_download_progress_detailed(&self, download_id: &str, now: Instant) -> Option<DownloadProgress> {
+        let download = self.downloads.get(download_id)?;
+        let speed_bytes_per_sec = if matches!(download.status, DownloadStatus::InProgress) {
+            self.current_speed(download_id, now)
+        } else {
+            0.0
+        };
+        let eta_seconds = download.total_bytes
+            .filter(|_| speed_bytes_per_sec > 0.0)
+            .map(|total| (total.saturating_sub(download.downloaded_bytes) as f64 / speed_bytes_per_sec).ceil() as u64);
+        
+        Some(DownloadProgress {
+            download_id: download.id.clone(),
+            status: download.status.clone(),
+            downloaded_bytes: download.downloaded_bytes,
+            total_bytes: download.total_bytes,
+            percent: self.get_download_progress(download_id),
+            speed_bytes_per_sec,
+            eta_seconds,
+        })
+    }
+
+    /// Moves queued downloads into free slots and returns the ids whose
+    /// drivers should now be started.
+    pub fn take_ready_downloads(&mut self) -> Vec<String> {
+        let mut ready = Vec::new();
+        while self.running.len() < self.max_concurrent_downloads {
+            let Some(download_id) = self.queue.pop_front() else {
+                break;
+            };
+            if self.downloads.get(&download_id).is_some_and(|download| matches!(download.status, DownloadStatus::Pending)) {
+                self.running.insert(download_id.clone());
+                ready.push(download_id);
+            }
+        }
+        ready
+    }
+
+    /// Frees the slot or queue position held by a download that has finished
+    /// or failed.
+    fn release_slot(&mut self, download_id: &str) {
+        self.running.remove(download_id);
+        self.dequeue(download_id);
+    }
+
+    /// Drops a cancelled or removed download from the queue. A running one
+    /// keeps its slot until its driver notices and stops, so the next queued
+    /// download can't start while it may still be transferring.
+    fn dequeue(&mut self, download_id: &str) {
+        self.queue.retain(|id| id != download_id);
+    }
+
+    pub fn get_queued_downloads(&self) -> Vec<&Download> {
+        self.queue.iter()
+            .filter_map(|download_id| self.downloads.get(download_id))
+            .collect()
+    }
+
+    pub fn set_max_concurrent_downloads(&mut self, max_concurrent_downloads: usize) -> Result<(), String> {
+        if max_concurrent_downloads == 0 {
+            return Err("At least one download must be allowed at a time".to_string());
+        }
+        self.max_concurrent_downloads = max_concurrent_downloads;
+        Ok(())
+    }
+
     pub fn complete_download(&mut self, download_id: &str) -> Result<(), String> {
         let download = self.downloads.get_mut(download_id)
             .ok_or("Download not found")?;
         
         download.status = DownloadStatus::Completed;
         download.end_time = Some(chrono::Utc::now());
+        self.release_slot(download_id);
         
         Ok(())
     }
@@ -131,6 +361,7 @@ impl DownloadManager {
         download.status = DownloadStatus::Failed;
         download.error_message = Some(error.to_string());
         download.end_time = Some(chrono::Utc::now());
+        self.release_slot(download_id);
         
         Ok(())
     }
@@ -141,6 +372,7 @@ impl DownloadManager {
         
         download.status = DownloadStatus::Cancelled;
         download.end_time = Some(chrono::Utc::now());
+        self.dequeue(download_id);
         
         Ok(())
     }
@@ -170,6 +402,8 @@ impl DownloadManager {
     pub fn remove_download(&mut self, download_id: &str) -> Result<(), String> {
         self.downloads.remove(download_id)
             .ok_or("Download not found")?;
+        self.progress_samples.remove(download_id);
+        self.dequeue(download_id);
         Ok(())
     }
 
@@ -177,6 +411,8 @@ impl DownloadManager {
         self.downloads.retain(|_, download| {
             !matches!(download.status, DownloadStatus::Completed | DownloadStatus::Failed | DownloadStatus::Cancelled)
         });
+        let downloads = &self.downloads;
+        self.progress_samples.retain(|download_id, _| downloads.contains_key(download_id));
     }
 
     pub fn get_downloads(&self) -> Vec<&Download> {
@@ -233,15 +469,65 @@ impl DownloadManager {
         if let Ok(parsed_url) = url::Url::parse(url) {
             if let Some(segments) = parsed_url.path_segments() {
                 if let Some(last_segment) = segments.last() {
-                    if !last_segment.is_empty() {
-                        return Some(last_segment.to_string());
-                    }
+                    let decoded = urlencoding::decode(last_segment).ok()?;
+                    return sanitize_filename(&decoded);
                 }
             }
         }
         None
     }
 
+    /// Stores the response's MIME type and, when the filename has no extension,
+    /// gives it one for that type. Returns the path the download will be written to.
+    pub fn apply_content_type(&mut self, download_id: &str, content_type: Option<&str>) -> Option<PathBuf> {
+        let mime_type = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty());
+        
+        let download = self.downloads.get(download_id)?;
+        let renamed = match (mime_type.as_deref().and_then(extension_for_mime), download.filename.rfind('.')) {
+            (Some(extension), None | Some(0)) => Some(format!("{}.{}", download.filename, extension)),
+            _ => None,
+        };
+        let renamed = renamed.map(|filename| self.unique_filename(&filename));
+        
+        let download_directory = self.download_directory.clone();
+        let download = self.downloads.get_mut(download_id)?;
+        download.mime_type = mime_type;
+        if let Some(filename) = renamed {
+            download.file_path = download.file_path.parent()
+                .map(|parent| parent.join(&filename))
+                .unwrap_or_else(|| download_directory.join(&filename));
+            download.filename = filename;
+        }
+        
+        Some(download.file_path.clone())
+    }
+
+    /// `filename`, or `name (1).ext`, `name (2).ext`, ... when a file of that
+    /// name exists in the download directory or an unfinished download targets it.
+    fn unique_filename(&self, filename: &str) -> String {
+        let (stem, extension) = match filename.rfind('.') {
+            Some(index) if index > 0 => filename.split_at(index),
+            _ => (filename, ""),
+        };
+        
+        (0..)
+            .map(|attempt| match attempt {
+                0 => filename.to_string(),
+                n => format!("{} ({}){}", stem, n, extension),
+            })
+            .find(|candidate| {
+                let path = self.download_directory.join(candidate);
+                !path.exists() && !self.downloads.values().any(|download| {
+                    download.file_path == path
+                        && matches!(download.status, DownloadStatus::Pending | DownloadStatus::InProgress | DownloadStatus::Paused)
+                })
+            })
+            .unwrap_or_else(|| filename.to_string())
+    }
+
     pub fn get_download_progress(&self, download_id: &str) -> Option<f64> {
         let download = self.downloads.get(download_id)?;
         
@@ -254,6 +540,38 @@ impl DownloadManager {
         None
     }
 
+    pub fn tag_download(&mut self, download_id: &str, tag: &str) -> Result<(), String> {
+        let tag = normalize_tag(tag)?;
+        let download = self.downloads.get_mut(download_id)
+            .ok_or("Download not found")?;
+        
+        if !download.tags.contains(&tag) {
+            download.tags.push(tag);
+        }
+        Ok(())
+    }
+
+    pub fn untag_download(&mut self, download_id: &str, tag: &str) -> Result<(), String> {
+        let tag = normalize_tag(tag)?;
+        let download = self.downloads.get_mut(download_id)
+            .ok_or("Download not found")?;
+        
+        download.tags.retain(|existing| existing != &tag);
+        Ok(())
+    }
+
+    pub fn get_downloads_by_tag(&self, tag: &str) -> Vec<&Download> {
+        let Ok(tag) = normalize_tag(tag) else {
+            return Vec::new();
+        };
+        
+        let mut downloads: Vec<&Download> = self.downloads.values()
+            .filter(|download| download.tags.contains(&tag))
+            .collect();
+        downloads.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+        downloads
+    }
+
     pub fn export_downloads(&self) -> Result<String, String> {
         let downloads: Vec<&Download> = self.downloads.values().collect();
         serde_json::to_string_pretty(&downloads)
@@ -261,16 +579,297 @@ impl DownloadManager {
     }
 }
 
-#[tauri::command]
-pub async fn start_download(url: String, filename: Option<String>, referrer: Option<String>) -> Result<String, String> {
+/// Lowercases header names and rejects forbidden or malformed headers.
+/// A `Referer` entry is allowed and takes precedence over the referrer argument.
+pub fn validate_download_headers(headers: HashMap<String, String>) -> Result<HashMap<String, String>, String> {
+    let mut validated = HashMap::new();
+    
+    for (name, value) in headers {
+        let name = name.trim().to_lowercase();
+        if FORBIDDEN_DOWNLOAD_HEADERS.contains(&name.as_str()) {
+            return Err(format!("Header cannot be overridden: {}", name));
+        }
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("Invalid header name: {}", name));
+        }
+        if reqwest::header::HeaderValue::from_str(&value).is_err() {
+            return Err(format!("Invalid value for header: {}", name));
+        }
+        validated.insert(name, value);
+    }
+    
+    Ok(validated)
+}
+
+/// Characters that are path separators or invalid in filenames on some platform.
+const ILLEGAL_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Extensions for common download types, used when the URL gives none.
+const MIME_EXTENSIONS: &[(&str, &str)] = &[
+    ("application/pdf", "pdf"),
+    ("application/zip", "zip"),
+    ("application/gzip", "gz"),
+    ("application/x-gzip", "gz"),
+    ("application/x-tar", "tar"),
+    ("application/x-7z-compressed", "7z"),
+    ("application/vnd.rar", "rar"),
+    ("application/x-rar-compressed", "rar"),
+    ("application/json", "json"),
+    ("application/xml", "xml"),
+    ("application/msword", "doc"),
+    ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", "docx"),
+    ("application/vnd.ms-excel", "xls"),
+    ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", "xlsx"),
+    ("application/vnd.ms-powerpoint", "ppt"),
+    ("application/vnd.openxmlformats-officedocument.presentationml.presentation", "pptx"),
+    ("application/epub+zip", "epub"),
+    ("application/x-msdownload", "exe"),
+    ("application/vnd.debian.binary-package", "deb"),
+    ("application/x-apple-diskimage", "dmg"),
+    ("text/plain", "txt"),
+    ("text/html", "html"),
+    ("text/css", "css"),
+    ("text/csv", "csv"),
+    ("text/javascript", "js"),
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/svg+xml", "svg"),
+    ("image/avif", "avif"),
+    ("image/bmp", "bmp"),
+    ("image/x-icon", "ico"),
+    ("audio/mpeg", "mp3"),
+    ("audio/ogg", "ogg"),
+    ("audio/wav", "wav"),
+    ("audio/flac", "flac"),
+    ("video/mp4", "mp4"),
+    ("video/webm", "webm"),
+    ("video/quicktime", "mov"),
+    ("font/woff2", "woff2"),
+];
+
+fn extension_for_mime(mime_type: &str) -> Option<&'static str> {
+    MIME_EXTENSIONS.iter()
+        .find(|(mime, _)| *mime == mime_type)
+        .map(|(_, extension)| *extension)
+}
+
+/// Longest filename kept, in bytes, leaving headroom under the common 255 limit.
+const MAX_FILENAME_BYTES: usize = 200;
+
+/// Turns `name` into a single safe path component: `.` and `..` parts and
+/// control characters are dropped, separators and characters rejected by the
+/// filesystem become `_`, and long names are cut down keeping the extension.
+/// `None` when nothing usable is left.
+fn sanitize_filename(name: &str) -> Option<String> {
+    let sanitized = name.split(['/', '\\'])
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .collect::<Vec<_>>()
+        .join("_");
+    let sanitized: String = sanitized.chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if ILLEGAL_FILENAME_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    let sanitized = sanitized.trim().trim_matches('.');
+    
+    if sanitized.is_empty() || sanitized.chars().all(|c| c == '_') {
+        return None;
+    }
+    Some(truncate_filename(sanitized, MAX_FILENAME_BYTES))
+}
+
+fn truncate_filename(name: &str, max_bytes: usize) -> String {
+    if name.len() <= max_bytes {
+        return name.to_string();
+    }
+    
+    let extension = name.rfind('.')
+        .map(|index| &name[index..])
+        .filter(|extension| extension.len() <= 16)
+        .unwrap_or("");
+    let mut stem_end = max_bytes - extension.len();
+    while !name.is_char_boundary(stem_end) {
+        stem_end -= 1;
+    }
+    format!("{}{}", name[..stem_end].trim_end(), extension)
+}
+
+async fn emit_download_progress(app: &AppHandle, download_id: &str) {
+    let manager = DOWNLOAD_MANAGER.read().await;
+    if let Some(download) = manager.downloads.get(download_id) {
+        let _ = app.emit("download://progress", DownloadProgressEvent::from(download));
+    }
+}
+
+/// Fetches a pending download into its `file_path`, emitting
+/// `download://progress` as bytes arrive and on every status change. Pausing
+/// holds the connection open; cancelling or removing the download stops it.
+/// The partial file is deleted unless the download completes. When it ends,
+/// the next queued download starts.
+///
+/// Boxed because the driver starts further drivers, which makes its future
+/// type recursive.
+fn run_download(app: AppHandle, download_id: String) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let result = drive_download(&app, &download_id).await;
+        finish_download(&download_id, result).await;
+        
+        emit_download_progress(&app, &download_id).await;
+        start_ready_downloads(&app).await;
+    })
+}
+
+/// Records how a driver ended and frees its slot.
+async fn finish_download(download_id: &str, result: Result<bool, String>) {
     let mut manager = DOWNLOAD_MANAGER.write().await;
-    manager.start_download(&url, filename.as_deref(), referrer.as_deref())
+    match result {
+        Ok(true) => {
+            let _ = manager.complete_download(download_id);
+        }
+        Ok(false) => {}
+        Err(e) => {
+            let _ = manager.fail_download(download_id, &e);
+        }
+    }
+    manager.release_slot(download_id);
+}
+
+/// Starts drivers for queued downloads while slots are free.
+async fn start_ready_downloads(app: &AppHandle) {
+    let ready = DOWNLOAD_MANAGER.write().await.take_ready_downloads();
+    for download_id in ready {
+        tauri::async_runtime::spawn(run_download(app.clone(), download_id));
+    }
+}
+
+/// Returns `Ok(false)` when the download was cancelled or removed midway.
+async fn drive_download(app: &AppHandle, download_id: &str) -> Result<bool, String> {
+    let (url, headers) = {
+        let mut manager = DOWNLOAD_MANAGER.write().await;
+        let Some(download) = manager.downloads.get_mut(download_id) else {
+            return Ok(false);
+        };
+        // Cancelled after being given a slot but before its driver ran.
+        if !matches!(download.status, DownloadStatus::Pending) {
+            return Ok(false);
+        }
+        download.status = DownloadStatus::InProgress;
+        (download.url.clone(), download.request_headers()?)
+    };
+    emit_download_progress(app, download_id).await;
+    
+    let client = super::network::download_client().await?;
+    let response = super::network::send_request(client.get(&url).headers(headers), "start download").await?;
+    if !response.status().is_success() {
+        return Err(format!("Server responded with {}", response.status()));
+    }
+    
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let file_path = {
+        let mut manager = DOWNLOAD_MANAGER.write().await;
+        manager.downloads.get_mut(download_id).ok_or("Download not found")?.total_bytes = response.content_length();
+        manager.apply_content_type(download_id, content_type.as_deref()).ok_or("Download not found")?
+    };
+    
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create download directory: {}", e))?;
+    }
+    
+    let stall_timeout = Duration::from_secs(super::settings::current_settings().await.advanced.network.request_timeout_secs.max(1));
+    save_response(download_id, response, &file_path, stall_timeout, || emit_download_progress(app, download_id)).await
+}
+
+/// Writes `response` to `file_path`, calling `on_progress` at most every
+/// `PROGRESS_EVENT_INTERVAL`. The partial file is deleted unless the download
+/// completes, whether it was cancelled, removed or failed.
+async fn save_response<F, Fut>(download_id: &str, response: reqwest::Response, file_path: &Path, stall_timeout: Duration, on_progress: F) -> Result<bool, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let result = write_response(download_id, response, file_path, stall_timeout, on_progress).await;
+    if !matches!(result, Ok(true)) {
+        let _ = tokio::fs::remove_file(file_path).await;
+    }
+    result
+}
+
+async fn write_response<F, Fut>(download_id: &str, mut response: reqwest::Response, file_path: &Path, stall_timeout: Duration, mut on_progress: F) -> Result<bool, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut file = tokio::fs::File::create(file_path)
+        .await
+        .map_err(|e| format!("Failed to create download file: {}", e))?;
+    
+    let mut downloaded_bytes = 0u64;
+    let mut last_event = Instant::now();
+    
+    loop {
+        let status = DOWNLOAD_MANAGER.write().await.record_received_bytes(download_id, downloaded_bytes);
+        match status {
+            Some(DownloadStatus::Paused) => {
+                tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+                continue;
+            }
+            Some(DownloadStatus::InProgress) => {}
+            _ => return Ok(false),
+        }
+        
+        if last_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+            on_progress().await;
+            last_event = Instant::now();
+        }
+        
+        let chunk = tokio::time::timeout(stall_timeout, response.chunk())
+            .await
+            .map_err(|_| format!("Download stalled for {}s", stall_timeout.as_secs()))?
+            .map_err(|e| format!("Failed to read download: {}", e))?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write download: {}", e))?;
+        downloaded_bytes += chunk.len() as u64;
+    }
+    
+    file.flush()
+        .await
+        .map_err(|e| format!("Failed to write download: {}", e))?;
+    
+    let status = DOWNLOAD_MANAGER.write().await.record_received_bytes(download_id, downloaded_bytes);
+    Ok(matches!(status, Some(DownloadStatus::InProgress | DownloadStatus::Paused)))
 }
 
 #[tauri::command]
-pub async fn cancel_download(download_id: String) -> Result<(), String> {
-    let mut manager = DOWNLOAD_MANAGER.write().await;
-    manager.cancel_download(&download_id)
+pub async fn start_download(app: AppHandle, url: String, filename: Option<String>, referrer: Option<String>) -> Result<String, String> {
+    let download_id = DOWNLOAD_MANAGER.write().await.start_download(&url, filename.as_deref(), referrer.as_deref())?;
+    start_ready_downloads(&app).await;
+    Ok(download_id)
+}
+
+#[tauri::command]
+pub async fn start_download_with_headers(app: AppHandle, url: String, filename: Option<String>, referrer: Option<String>, headers: HashMap<String, String>) -> Result<String, String> {
+    let download_id = DOWNLOAD_MANAGER.write().await.start_download_with_headers(&url, filename.as_deref(), referrer.as_deref(), headers)?;
+    start_ready_downloads(&app).await;
+    Ok(download_id)
+}
+
+#[tauri::command]
+pub async fn cancel_download(app: AppHandle, download_id: String) -> Result<(), String> {
+    DOWNLOAD_MANAGER.write().await.cancel_download(&download_id)?;
+    start_ready_downloads(&app).await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -286,9 +885,10 @@ pub async fn resume_download(download_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn remove_download(download_id: String) -> Result<(), String> {
-    let mut manager = DOWNLOAD_MANAGER.write().await;
-    manager.remove_download(&download_id)
+pub async fn remove_download(app: AppHandle, download_id: String) -> Result<(), String> {
+    DOWNLOAD_MANAGER.write().await.remove_download(&download_id)?;
+    start_ready_downloads(&app).await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -316,6 +916,19 @@ pub async fn get_download_stats() -> Result<DownloadStats, String> {
     Ok(manager.get_download_stats())
 }
 
+#[tauri::command]
+pub async fn get_queued_downloads() -> Result<Vec<Download>, String> {
+    let manager = DOWNLOAD_MANAGER.read().await;
+    Ok(manager.get_queued_downloads().into_iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn set_max_concurrent_downloads(app: AppHandle, max_concurrent_downloads: usize) -> Result<(), String> {
+    DOWNLOAD_MANAGER.write().await.set_max_concurrent_downloads(max_concurrent_downloads)?;
+    start_ready_downloads(&app).await;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn set_download_directory(path: String) -> Result<(), String> {
     let mut manager = DOWNLOAD_MANAGER.write().await;
@@ -328,8 +941,329 @@ pub async fn get_download_progress(download_id: String) -> Result<Option<f64>, S
     Ok(manager.get_download_progress(&download_id))
 }
 
+#[tauri::command]
+pub async fn get_download_progress_detailed(download_id: String) -> Result<DownloadProgress, String> {
+    let manager = DOWNLOAD_MANAGER.read().await;
+    manager.get_download_progress_detailed(&download_id, Instant::now())
+        .ok_or_else(|| "Download not found".to_string())
+}
+
 #[tauri::command]
 pub async fn export_downloads() -> Result<String, String> {
     let manager = DOWNLOAD_MANAGER.read().await;
     manager.export_downloads()
+}
+
+#[tauri::command]
+pub async fn tag_download(download_id: String, tag: String) -> Result<(), String> {
+    let mut manager = DOWNLOAD_MANAGER.write().await;
+    manager.tag_download(&download_id, &tag)
+}
+
+#[tauri::command]
+pub async fn untag_download(download_id: String, tag: String) -> Result<(), String> {
+    let mut manager = DOWNLOAD_MANAGER.write().await;
+    manager.untag_download(&download_id, &tag)
+}
+
+#[tauri::command]
+pub async fn get_downloads_by_tag(tag: String) -> Result<Vec<Download>, String> {
+    let manager = DOWNLOAD_MANAGER.read().await;
+    Ok(manager.get_downloads_by_tag(&tag).into_iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_running_download_keeps_its_slot_until_released() {
+        let mut manager = DownloadManager::new();
+        manager.set_max_concurrent_downloads(1).unwrap();
+        let first = manager.start_download("https://example.com/a.zip", None, None).unwrap();
+        let second = manager.start_download("https://example.com/b.zip", None, None).unwrap();
+        assert_eq!(manager.take_ready_downloads(), vec![first.clone()]);
+        
+        manager.cancel_download(&first).unwrap();
+        assert!(manager.take_ready_downloads().is_empty());
+        
+        manager.release_slot(&first);
+        assert_eq!(manager.take_ready_downloads(), vec![second]);
+    }
+
+    #[test]
+    fn progress_is_sampled_at_most_every_interval() {
+        let mut manager = DownloadManager::new();
+        let start = Instant::now();
+        for chunk in 0..1000u64 {
+            manager.record_progress_sample("d", chunk * 1024, start + Duration::from_millis(chunk));
+        }
+        
+        assert_eq!(manager.progress_samples["d"].len(), 10);
+    }
+
+    /// Serves `response` to the first connection and returns a URL for it.
+    async fn serve_once(response: Vec<u8>) -> String {
+        use tokio::io::AsyncReadExt;
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/file.bin", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let _ = socket.write_all(&response).await;
+        });
+        url
+    }
+
+    async fn fetch(url: &str) -> reqwest::Response {
+        reqwest::Client::builder().no_proxy().build().unwrap()
+            .get(url)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    /// Adds a download to the shared manager as its driver would find it.
+    async fn running_download(url: &str) -> String {
+        let mut manager = DOWNLOAD_MANAGER.write().await;
+        let download_id = manager.start_download(url, None, None).unwrap();
+        manager.dequeue(&download_id);
+        manager.downloads.get_mut(&download_id).unwrap().status = DownloadStatus::InProgress;
+        download_id
+    }
+
+    fn temp_file() -> PathBuf {
+        std::env::temp_dir().join(format!("sw3do-download-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn completed_download_is_written_to_its_file() {
+        let body = b"0123456789".repeat(1000);
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let url = serve_once(response).await;
+        let download_id = running_download(&url).await;
+        let file_path = temp_file();
+        
+        let result = save_response(&download_id, fetch(&url).await, &file_path, Duration::from_secs(5), || async {}).await;
+        finish_download(&download_id, result).await;
+        
+        assert_eq!(std::fs::read(&file_path).unwrap(), body);
+        let manager = DOWNLOAD_MANAGER.read().await;
+        let download = &manager.downloads[&download_id];
+        assert!(matches!(download.status, DownloadStatus::Completed));
+        assert_eq!(download.downloaded_bytes, body.len() as u64);
+        drop(manager);
+        let _ = std::fs::remove_file(&file_path);
+    }
+
+    #[tokio::test]
+    async fn failed_download_removes_the_partial_file() {
+        let url = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\npartial".to_vec()).await;
+        let download_id = running_download(&url).await;
+        let file_path = temp_file();
+        
+        let result = save_response(&download_id, fetch(&url).await, &file_path, Duration::from_secs(5), || async {}).await;
+        assert!(result.is_err());
+        finish_download(&download_id, result).await;
+        
+        assert!(!file_path.exists());
+        assert!(matches!(DOWNLOAD_MANAGER.read().await.downloads[&download_id].status, DownloadStatus::Failed));
+    }
+
+    #[tokio::test]
+    async fn unwritable_download_file_is_a_failure() {
+        let url = serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok".to_vec()).await;
+        let download_id = running_download(&url).await;
+        let file_path = temp_file().join("missing").join("file.bin");
+        
+        let result = save_response(&download_id, fetch(&url).await, &file_path, Duration::from_secs(5), || async {}).await;
+        
+        assert!(result.unwrap_err().starts_with("Failed to create download file"));
+    }
+
+    fn manager_in_temp_dir() -> DownloadManager {
+        let mut manager = DownloadManager::new();
+        let dir = std::env::temp_dir().join(format!("sw3do-downloads-{}", Uuid::new_v4()));
+        manager.set_download_directory(dir).unwrap();
+        manager
+    }
+
+    #[test]
+    fn colliding_filenames_are_numbered() {
+        let mut manager = manager_in_temp_dir();
+        std::fs::write(manager.download_directory.join("report.pdf"), b"").unwrap();
+        
+        let pending = manager.start_download("https://example.com/report.pdf", None, None).unwrap();
+        let next = manager.start_download("https://example.com/report.pdf", None, None).unwrap();
+        let unnamed = manager.start_download("https://example.com/files/README", None, None).unwrap();
+        let unnamed_again = manager.start_download("https://example.com/README", None, None).unwrap();
+        
+        assert_eq!(manager.downloads[&pending].filename, "report (1).pdf");
+        assert_eq!(manager.downloads[&next].filename, "report (2).pdf");
+        assert_eq!(manager.downloads[&unnamed].filename, "README");
+        assert_eq!(manager.downloads[&unnamed_again].filename, "README (1)");
+        
+        manager.complete_download(&pending).unwrap();
+        let after_completion = manager.start_download("https://example.com/report.pdf", None, None).unwrap();
+        assert_eq!(manager.downloads[&after_completion].filename, "report (1).pdf");
+        let _ = std::fs::remove_dir_all(&manager.download_directory);
+    }
+
+    #[test]
+    fn requested_filenames_stay_inside_the_download_directory() {
+        let mut manager = manager_in_temp_dir();
+        
+        let download_id = manager.start_download("https://example.com/", Some("../../etc/passwd"), None).unwrap();
+        
+        let download = &manager.downloads[&download_id];
+        assert_eq!(download.filename, "etc_passwd");
+        assert_eq!(download.file_path.parent(), Some(manager.download_directory.as_path()));
+        let _ = std::fs::remove_dir_all(&manager.download_directory);
+    }
+
+    #[test]
+    fn url_filenames_are_sanitized() {
+        let mut manager = manager_in_temp_dir();
+        
+        let encoded = manager.start_download("https://example.com/..%2f..%2fetc%2fpasswd", None, None).unwrap();
+        let directory = manager.start_download("https://example.com/files/", None, None).unwrap();
+        
+        assert_eq!(manager.downloads[&encoded].filename, "etc_passwd");
+        assert_eq!(manager.downloads[&directory].filename, format!("download_{}", directory));
+        assert_eq!(sanitize_filename("a:b*c?.txt").as_deref(), Some("a_b_c_.txt"));
+        assert_eq!(sanitize_filename("../.."), None);
+        assert_eq!(sanitize_filename("///"), None);
+        assert_eq!(sanitize_filename(&format!("{}.zip", "x".repeat(300))).unwrap().len(), MAX_FILENAME_BYTES);
+        let _ = std::fs::remove_dir_all(&manager.download_directory);
+    }
+
+    #[test]
+    fn content_type_names_extensionless_downloads() {
+        let mut manager = manager_in_temp_dir();
+        let image = manager.start_download("https://example.com/avatar", None, None).unwrap();
+        let archive = manager.start_download("https://example.com/backup.tar", None, None).unwrap();
+        
+        let path = manager.apply_content_type(&image, Some("Image/PNG; charset=binary")).unwrap();
+        manager.apply_content_type(&archive, Some("application/zip"));
+        
+        assert_eq!(manager.downloads[&image].filename, "avatar.png");
+        assert_eq!(path, manager.download_directory.join("avatar.png"));
+        assert_eq!(manager.downloads[&image].mime_type.as_deref(), Some("image/png"));
+        assert_eq!(manager.downloads[&archive].filename, "backup.tar");
+        let _ = std::fs::remove_dir_all(&manager.download_directory);
+    }
+
+    #[test]
+    fn speed_and_eta_come_from_recent_samples() {
+        let mut manager = DownloadManager::new();
+        let sized = manager.start_download("https://example.com/a.zip", None, None).unwrap();
+        let unsized_download = manager.start_download("https://example.com/b.zip", None, None).unwrap();
+        let start = Instant::now();
+        for download_id in [&sized, &unsized_download] {
+            for second in 0..=2u64 {
+                manager.record_progress_sample(download_id, second * 1000, start + Duration::from_secs(second));
+            }
+            let download = manager.downloads.get_mut(download_id).unwrap();
+            download.status = DownloadStatus::InProgress;
+            download.downloaded_bytes = 2000;
+        }
+        manager.downloads.get_mut(&sized).unwrap().total_bytes = Some(10_000);
+        
+        let now = start + Duration::from_secs(2);
+        let progress = manager.get_download_progress_detailed(&sized, now).unwrap();
+        assert_eq!(progress.speed_bytes_per_sec, 1000.0);
+        assert_eq!(progress.eta_seconds, Some(8));
+        assert_eq!(progress.percent, Some(20.0));
+        
+        let progress = manager.get_download_progress_detailed(&unsized_download, now).unwrap();
+        assert_eq!(progress.speed_bytes_per_sec, 1000.0);
+        assert_eq!(progress.eta_seconds, None);
+        
+        manager.pause_download(&sized).unwrap();
+        let progress = manager.get_download_progress_detailed(&sized, now).unwrap();
+        assert_eq!(progress.speed_bytes_per_sec, 0.0);
+        assert_eq!(progress.eta_seconds, None);
+    }
+
+    #[test]
+    fn only_the_concurrency_limit_runs_at_once() {
+        let mut manager = DownloadManager::new();
+        manager.set_max_concurrent_downloads(2).unwrap();
+        let ids: Vec<String> = (0..5)
+            .map(|n| manager.start_download(&format!("https://example.com/{}.zip", n), None, None).unwrap())
+            .collect();
+        let start_drivers = |manager: &mut DownloadManager| {
+            for download_id in manager.take_ready_downloads() {
+                manager.update_download_progress(&download_id, 0, None).unwrap();
+            }
+        };
+        let in_progress = |manager: &DownloadManager| manager.downloads.values()
+            .filter(|download| matches!(download.status, DownloadStatus::InProgress))
+            .count();
+        
+        start_drivers(&mut manager);
+        assert_eq!(in_progress(&manager), 2);
+        assert_eq!(manager.get_queued_downloads().len(), 3);
+        assert_eq!(manager.get_queued_downloads()[0].id, ids[2]);
+        
+        manager.complete_download(&ids[0]).unwrap();
+        start_drivers(&mut manager);
+        assert_eq!(in_progress(&manager), 2);
+        assert!(matches!(manager.downloads[&ids[2]].status, DownloadStatus::InProgress));
+        assert!(matches!(manager.downloads[&ids[3]].status, DownloadStatus::Pending));
+    }
+
+    #[test]
+    fn downloads_are_tagged_untagged_and_filtered_by_tag() {
+        let mut manager = DownloadManager::new();
+        let report = manager.start_download("https://example.com/report.pdf", None, None).unwrap();
+        let photo = manager.start_download("https://example.com/photo.jpg", None, None).unwrap();
+        manager.tag_download(&report, "Work").unwrap();
+        manager.tag_download(&report, " work ").unwrap();
+        manager.tag_download(&photo, "work").unwrap();
+        manager.tag_download(&photo, "personal").unwrap();
+        
+        assert_eq!(manager.downloads[&report].tags, vec!["work"]);
+        let mut tagged: Vec<&str> = manager.get_downloads_by_tag("WORK").iter().map(|d| d.id.as_str()).collect();
+        tagged.sort();
+        let mut expected = vec![report.as_str(), photo.as_str()];
+        expected.sort();
+        assert_eq!(tagged, expected);
+        
+        manager.untag_download(&report, "work").unwrap();
+        
+        let tagged: Vec<&str> = manager.get_downloads_by_tag("work").iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(tagged, vec![photo.as_str()]);
+        assert!(manager.downloads[&report].tags.is_empty());
+        assert!(manager.get_downloads_by_tag(" ").is_empty());
+        assert!(manager.tag_download(&report, "").is_err());
+        assert!(manager.tag_download("missing", "work").is_err());
+    }
+
+    #[test]
+    fn supplied_headers_are_sent_and_hop_by_hop_headers_rejected() {
+        let mut manager = DownloadManager::new();
+        let headers = HashMap::from([
+            ("Cookie".to_string(), "session=1".to_string()),
+            ("Referer".to_string(), "https://override.example/".to_string()),
+        ]);
+        
+        let download_id = manager.start_download_with_headers("https://example.com/a.zip", None, Some("https://page.example/"), headers).unwrap();
+        
+        let request_headers = manager.downloads[&download_id].request_headers().unwrap();
+        assert_eq!(request_headers["cookie"], "session=1");
+        assert_eq!(request_headers["referer"], "https://override.example/");
+        assert_eq!(request_headers["user-agent"], "Sw3do Browser/1.0");
+        assert!(!manager.export_downloads().unwrap().contains("session=1"));
+        
+        for name in ["Connection", " Transfer-Encoding ", "host"] {
+            let headers = HashMap::from([(name.to_string(), "x".to_string())]);
+            let error = manager.start_download_with_headers("https://example.com/b.zip", None, None, headers).unwrap_err();
+            assert!(error.starts_with("Header cannot be overridden"), "{}", error);
+        }
+        assert_eq!(manager.downloads.len(), 1);
+    }
 }
\ No newline at end of file
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tauri::webview::PageLoadEvent;
 use uuid::Uuid;
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
+use super::tabs::Tab;
 
+/// A browser window. `tabs` and `active_tab` are filled from the tab manager
+/// when the window is read; the engine itself only tracks the window.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserWindow {
     pub id: String,
@@ -14,19 +18,6 @@ pub struct BrowserWindow {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BrowserTab {
-    pub id: String,
-    pub window_id: String,
-    pub url: String,
-    pub title: String,
-    pub favicon: Option<String>,
-    pub is_loading: bool,
-    pub is_private: bool,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub last_accessed: chrono::DateTime<chrono::Utc>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NavigationEntry {
     pub url: String,
@@ -34,25 +25,37 @@ pub struct NavigationEntry {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// Injects an `img-src` CSP as soon as `<head>` exists so images are never fetched.
+const BLOCK_IMAGES_SCRIPT: &str = r#"(function () {
+  var inject = function (head) {
+    var meta = document.createElement('meta');
+    meta.httpEquiv = 'Content-Security-Policy';
+    meta.content = "img-src 'none'";
+    head.insertBefore(meta, head.firstChild);
+  };
+  if (document.head) { inject(document.head); return; }
+  new MutationObserver(function (_, observer) {
+    if (document.head) { observer.disconnect(); inject(document.head); }
+  }).observe(document, { childList: true, subtree: true });
+})();"#;
+
 static BROWSER_ENGINE: Lazy<RwLock<BrowserEngine>> = Lazy::new(|| {
     RwLock::new(BrowserEngine::new())
 });
 
+/// Tracks browser windows. Tabs live in the tab manager.
 pub struct BrowserEngine {
     pub windows: HashMap<String, BrowserWindow>,
-    pub tabs: HashMap<String, BrowserTab>,
 }
 
 impl BrowserEngine {
     pub fn new() -> Self {
         Self {
             windows: HashMap::new(),
-            tabs: HashMap::new(),
         }
     }
 
-    pub fn create_window(&mut self, is_private: bool) -> String {
-        let window_id = Uuid::new_v4().to_string();
+    pub fn create_window_with_id(&mut self, window_id: String, is_private: bool) -> String {
         let window = BrowserWindow {
             id: window_id.clone(),
             is_private,
@@ -63,97 +66,25 @@ impl BrowserEngine {
         self.windows.insert(window_id.clone(), window);
         window_id
     }
+}
 
-    pub fn create_tab(&mut self, window_id: &str, url: &str, is_private: bool) -> Result<String, String> {
-        if !self.windows.contains_key(window_id) {
-            return Err("Window not found".to_string());
-        }
-
-        let tab_id = Uuid::new_v4().to_string();
-        let tab = BrowserTab {
-            id: tab_id.clone(),
-            window_id: window_id.to_string(),
-            url: url.to_string(),
-            title: "Loading...".to_string(),
-            favicon: None,
-            is_loading: true,
-            is_private,
-            created_at: chrono::Utc::now(),
-            last_accessed: chrono::Utc::now(),
-        };
-
-        self.tabs.insert(tab_id.clone(), tab);
-        
-        if let Some(window) = self.windows.get_mut(window_id) {
-            window.tabs.push(tab_id.clone());
-            if window.active_tab.is_none() {
-                window.active_tab = Some(tab_id.clone());
-            }
-        }
-
-        Ok(tab_id)
-    }
-
-    pub fn close_tab(&mut self, tab_id: &str) -> Result<(), String> {
-        let tab = self.tabs.get(tab_id).ok_or("Tab not found")?;
-        let window_id = tab.window_id.clone();
-        
-        self.tabs.remove(tab_id);
-        
-        if let Some(window) = self.windows.get_mut(&window_id) {
-            window.tabs.retain(|id| id != tab_id);
-            
-            if window.active_tab.as_ref() == Some(&tab_id.to_string()) {
-                window.active_tab = window.tabs.first().cloned();
-            }
-        }
-
-        Ok(())
-    }
-
-    pub fn set_active_tab(&mut self, window_id: &str, tab_id: &str) -> Result<(), String> {
-        let window = self.windows.get_mut(window_id).ok_or("Window not found")?;
-        
-        if !window.tabs.contains(&tab_id.to_string()) {
-            return Err("Tab not in window".to_string());
-        }
-
-        window.active_tab = Some(tab_id.to_string());
-        
-        if let Some(tab) = self.tabs.get_mut(tab_id) {
-            tab.last_accessed = chrono::Utc::now();
-        }
-
-        Ok(())
-    }
+/// Fills in the window's tabs from the tab manager.
+async fn with_tabs(mut window: BrowserWindow) -> BrowserWindow {
+    let (tabs, active_tab) = super::tabs::window_tab_ids(&window.id).await;
+    window.tabs = tabs;
+    window.active_tab = active_tab;
+    window
+}
 
-    pub fn update_tab_url(&mut self, tab_id: &str, url: &str, title: Option<&str>) -> Result<(), String> {
-        let tab = self.tabs.get_mut(tab_id).ok_or("Tab not found")?;
-        tab.url = url.to_string();
-        tab.is_loading = false;
-        tab.last_accessed = chrono::Utc::now();
-        
-        if let Some(title) = title {
-            tab.title = title.to_string();
-        }
+pub async fn window_ids() -> HashSet<String> {
+    BROWSER_ENGINE.read().await.windows.keys().cloned().collect()
+}
 
+async fn ensure_window_exists(window_id: &str) -> Result<(), String> {
+    if BROWSER_ENGINE.read().await.windows.contains_key(window_id) {
         Ok(())
-    }
-
-    pub fn get_window_tabs(&self, window_id: &str) -> Vec<&BrowserTab> {
-        if let Some(window) = self.windows.get(window_id) {
-            window.tabs.iter()
-                .filter_map(|tab_id| self.tabs.get(tab_id))
-                .collect()
-        } else {
-            Vec::new()
-        }
-    }
-
-    pub fn get_active_tab(&self, window_id: &str) -> Option<&BrowserTab> {
-        let window = self.windows.get(window_id)?;
-        let active_tab_id = window.active_tab.as_ref()?;
-        self.tabs.get(active_tab_id)
+    } else {
+        Err("Window not found".to_string())
     }
 }
 
@@ -176,13 +107,11 @@ pub async fn create_browser_window(app: AppHandle, is_private: bool) -> Result<S
     .closable(true)
     .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Sw3doBrowser/1.0")
     .accept_first_mouse(true);
-
+    
     match builder.build() {
         Ok(_) => {
             let mut engine = BROWSER_ENGINE.write().await;
-            let window_id_clone = window_id.clone();
-            engine.create_window(is_private);
-            Ok(window_id_clone)
+            Ok(engine.create_window_with_id(window_id, is_private))
         },
         Err(e) => Err(format!("Failed to create window: {}", e)),
     }
@@ -192,17 +121,11 @@ pub async fn create_browser_window(app: AppHandle, is_private: bool) -> Result<S
 pub async fn close_browser_window(app: AppHandle, window_id: String) -> Result<(), String> {
     let window_label = format!("browser-{}", window_id);
     
-    {
-        let mut engine = BROWSER_ENGINE.write().await;
-        let tab_ids = engine.windows.get(&window_id)
-            .map(|window| window.tabs.clone())
-            .unwrap_or_default();
-        
-        for tab_id in &tab_ids {
-            engine.tabs.remove(tab_id);
-        }
-        engine.windows.remove(&window_id);
-    }
+    let is_private = BROWSER_ENGINE.write().await
+        .windows
+        .remove(&window_id)
+        .is_some_and(|window| window.is_private);
+    super::tabs::close_window(&window_id, is_private).await;
     
     if let Some(window) = app.get_webview_window(&window_label) {
         window.close().map_err(|e| format!("Failed to close window: {}", e))?;
@@ -211,58 +134,157 @@ pub async fn close_browser_window(app: AppHandle, window_id: String) -> Result<(
     Ok(())
 }
 
+/// Reopens the most recently closed window with its tabs. Returns the new
+/// window id, or `None` when nothing is left to restore.
+#[tauri::command]
+pub async fn restore_last_closed_window(app: AppHandle) -> Result<Option<String>, String> {
+    let Some(closed) = super::tabs::take_last_closed_window().await else {
+        return Ok(None);
+    };
+    
+    let window_id = match create_browser_window(app, false).await {
+        Ok(window_id) => window_id,
+        Err(e) => {
+            super::tabs::return_closed_window(closed).await;
+            return Err(e);
+        }
+    };
+    
+    super::tabs::restore_window_tabs(&window_id, &closed.layout).await?;
+    Ok(Some(window_id))
+}
+
 #[tauri::command]
 pub async fn get_browser_window(window_id: String) -> Result<Option<BrowserWindow>, String> {
-    let engine = BROWSER_ENGINE.read().await;
-    Ok(engine.windows.get(&window_id).cloned())
+    let window = BROWSER_ENGINE.read().await.windows.get(&window_id).cloned();
+    match window {
+        Some(window) => Ok(Some(with_tabs(window).await)),
+        None => Ok(None),
+    }
 }
 
 #[tauri::command]
 pub async fn get_all_windows() -> Result<Vec<BrowserWindow>, String> {
-    let engine = BROWSER_ENGINE.read().await;
-    Ok(engine.windows.values().cloned().collect())
+    let windows: Vec<BrowserWindow> = BROWSER_ENGINE.read().await.windows.values().cloned().collect();
+    let mut filled = Vec::with_capacity(windows.len());
+    for window in windows {
+        filled.push(with_tabs(window).await);
+    }
+    Ok(filled)
 }
 
+/// Deprecated: use `create_tab`. Kept as a shim over the shared tab manager.
 #[tauri::command]
 pub async fn create_engine_tab(window_id: String, url: String, is_private: bool) -> Result<String, String> {
-    let mut engine = BROWSER_ENGINE.write().await;
-    engine.create_tab(&window_id, &url, is_private)
+    ensure_window_exists(&window_id).await?;
+    super::tabs::create_tab(window_id, url, is_private).await
 }
 
+/// Deprecated: use `close_tab`.
 #[tauri::command]
 pub async fn close_engine_tab(tab_id: String) -> Result<(), String> {
-    let mut engine = BROWSER_ENGINE.write().await;
-    engine.close_tab(&tab_id)
+    super::tabs::close_tab(tab_id).await
 }
 
+/// Deprecated: use `update_tab_url`.
 #[tauri::command]
 pub async fn update_engine_tab_url(tab_id: String, url: String, title: Option<String>) -> Result<(), String> {
-    let mut engine = BROWSER_ENGINE.write().await;
-    engine.update_tab_url(&tab_id, &url, title.as_deref())
+    super::tabs::update_tab_url(tab_id, url, title).await
 }
 
+/// Deprecated: use `set_active_tab`.
 #[tauri::command]
 pub async fn set_engine_active_tab(window_id: String, tab_id: String) -> Result<(), String> {
-    let mut engine = BROWSER_ENGINE.write().await;
-    engine.set_active_tab(&window_id, &tab_id)
+    super::tabs::set_active_tab(window_id, tab_id).await
 }
 
+/// Deprecated: use `get_window_tabs`.
 #[tauri::command]
-pub async fn get_engine_window_tabs(window_id: String) -> Result<Vec<BrowserTab>, String> {
-    let engine = BROWSER_ENGINE.read().await;
-    Ok(engine.get_window_tabs(&window_id).into_iter().cloned().collect())
+pub async fn get_engine_window_tabs(window_id: String) -> Result<Vec<Tab>, String> {
+    super::tabs::get_window_tabs(window_id).await
 }
 
+/// Deprecated: use `get_active_tab`.
 #[tauri::command]
-pub async fn get_engine_active_tab(window_id: String) -> Result<Option<BrowserTab>, String> {
-    let engine = BROWSER_ENGINE.read().await;
-    Ok(engine.get_active_tab(&window_id).cloned())
+pub async fn get_engine_active_tab(window_id: String) -> Result<Option<Tab>, String> {
+    super::tabs::get_active_tab(window_id).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KioskBlockedEvent {
+    pub tab_id: String,
+    pub url: String,
+}
+
+/// Refuses navigation outside the kiosk allowlist and tells the UI to show
+/// its `sw3do://kiosk-blocked` page in place of the requested URL.
+fn ensure_kiosk_allows(app: &AppHandle, tab_id: &str, url: &str) -> Result<(), String> {
+    if super::settings::kiosk_allows(url) {
+        return Ok(());
+    }
+    
+    emit_kiosk_blocked(app, tab_id, url);
+    Err(format!("Navigation to {} is blocked in kiosk mode", url))
+}
+
+/// Navigation hook for tab webviews, so links, redirects and script-driven
+/// navigations inside the page are held to the kiosk allowlist too.
+fn kiosk_allows_navigation(app: &AppHandle, tab_id: &str, url: &str) -> bool {
+    let allowed = super::settings::kiosk_allows(url);
+    if !allowed {
+        emit_kiosk_blocked(app, tab_id, url);
+    }
+    allowed
+}
+
+fn emit_kiosk_blocked(app: &AppHandle, tab_id: &str, url: &str) {
+    let _ = app.emit("sw3do://kiosk-blocked", KioskBlockedEvent {
+        tab_id: tab_id.to_string(),
+        url: url.to_string(),
+    });
+}
+
+async fn inject_custom_css(webview: WebviewWindow, host: String) {
+    let Some(css) = super::settings::current_settings().await.appearance.resolve_custom_css(&host) else {
+        return;
+    };
+    
+    if let Ok(css) = serde_json::to_string(&css) {
+        let js_code = format!(
+            "(function () {{ var style = document.getElementById('sw3do-custom-css') || document.createElement('style'); style.id = 'sw3do-custom-css'; style.textContent = {}; document.head.appendChild(style); }})();",
+            css
+        );
+        let _ = webview.eval(&js_code);
+    }
+}
+
+/// Emits `sw3do://lookalike-warning` instead of loading `url` when it imitates
+/// a well-known domain, unless the user chose to continue. Returns whether
+/// the load was held back.
+async fn hold_lookalike(app: &AppHandle, tab_id: &str, url: &str, allow_lookalike: Option<bool>) -> Result<bool, String> {
+    if allow_lookalike.unwrap_or(false) {
+        return Ok(false);
+    }
+    let Some(warning) = super::lookalike::detect_lookalike(url).await else {
+        return Ok(false);
+    };
+    app.emit("sw3do://lookalike-warning", super::lookalike::LookalikeWarningEvent { tab_id: tab_id.to_string(), warning })
+        .map_err(|e| format!("Failed to emit lookalike warning: {}", e))?;
+    Ok(true)
 }
 
 #[tauri::command]
-pub async fn create_webview_tab(app: AppHandle, tab_id: String, url: String) -> Result<(), String> {
+pub async fn create_webview_tab(app: AppHandle, tab_id: String, url: String, allow_lookalike: Option<bool>) -> Result<(), String> {
     let webview_label = format!("webview-{}", tab_id);
+    let url = super::redirects::clean_navigation_url(&url).await;
     
+    ensure_kiosk_allows(&app, &tab_id, &url)?;
+    if hold_lookalike(&app, &tab_id, &url, allow_lookalike).await? {
+        return Ok(());
+    }
+    
+    let navigation_app = app.clone();
+    let navigation_tab_id = tab_id.clone();
     let builder = WebviewWindowBuilder::new(
         &app,
         &webview_label,
@@ -276,7 +298,21 @@ pub async fn create_webview_tab(app: AppHandle, tab_id: String, url: String) ->
     .closable(true)
     .visible(false)
     .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Sw3doBrowser/1.0")
-    .accept_first_mouse(true);
+    .accept_first_mouse(true)
+    .on_navigation(move |url| kiosk_allows_navigation(&navigation_app, &navigation_tab_id, url.as_str()))
+    .on_page_load(|webview, payload| {
+        if let PageLoadEvent::Finished = payload.event() {
+            if let Some(host) = payload.url().host_str().map(|host| host.to_string()) {
+                tauri::async_runtime::spawn(inject_custom_css(webview, host));
+            }
+        }
+    });
+    
+    let builder = if super::settings::current_settings().await.data_usage_policy().load_images {
+        builder
+    } else {
+        builder.initialization_script(BLOCK_IMAGES_SCRIPT)
+    };
 
     match builder.build() {
         Ok(_) => Ok(()),
@@ -318,14 +354,28 @@ pub async fn close_webview_tab(app: AppHandle, tab_id: String) -> Result<(), Str
     Ok(())
 }
 
-#[tauri::command]
-pub async fn navigate_webview_tab(app: AppHandle, tab_id: String, url: String) -> Result<(), String> {
+/// Points the tab's webview at `url`. Does nothing when the tab has no webview,
+/// e.g. after it was discarded.
+pub fn set_webview_location(app: &AppHandle, tab_id: &str, url: &str) -> Result<(), String> {
     let webview_label = format!("webview-{}", tab_id);
     
     if let Some(webview) = app.get_webview_window(&webview_label) {
-        let js_code = format!("window.location.href = '{}';", url.replace("'", "\\'")); 
+        let url = serde_json::to_string(url).map_err(|e| format!("Failed to encode URL: {}", e))?;
+        let js_code = format!("window.location.href = {};", url);
         webview.eval(&js_code).map_err(|e| format!("Failed to navigate webview: {}", e))?;
     }
     
     Ok(())
+}
+
+#[tauri::command]
+pub async fn navigate_webview_tab(app: AppHandle, tab_id: String, url: String, allow_lookalike: Option<bool>) -> Result<(), String> {
+    let url = super::redirects::clean_navigation_url(&url).await;
+    
+    ensure_kiosk_allows(&app, &tab_id, &url)?;
+    if hold_lookalike(&app, &tab_id, &url, allow_lookalike).await? {
+        return Ok(());
+    }
+    
+    set_webview_location(&app, &tab_id, &url)
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use base64::Engine;
+use tokio::sync::RwLock;
+use once_cell::sync::Lazy;
+
+const FAVICON_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const NEGATIVE_FAVICON_TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_FAVICON_BYTES: usize = 512 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favicon {
+    pub url: String,
+    pub data_url: String,
+    pub is_placeholder: bool,
+}
+
+struct CachedFavicon {
+    data_url: Option<String>,
+    expires_at: Instant,
+}
+
+static FAVICON_CACHE: Lazy<RwLock<FaviconCache>> = Lazy::new(|| {
+    RwLock::new(FaviconCache::new())
+});
+
+/// Caches validated favicons as data URLs. Failed fetches are cached as
+/// `None` for a shorter time so broken icons are not refetched on every paint.
+pub struct FaviconCache {
+    entries: HashMap<String, CachedFavicon>,
+}
+
+impl FaviconCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// `Some(None)` means a recent fetch failed and should not be retried yet.
+    pub fn get(&self, url: &str, now: Instant) -> Option<Option<String>> {
+        self.entries.get(url)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.data_url.clone())
+    }
+
+    pub fn insert(&mut self, url: &str, data_url: String, now: Instant) {
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        self.entries.insert(url.to_string(), CachedFavicon {
+            data_url: Some(data_url),
+            expires_at: now + FAVICON_TTL,
+        });
+    }
+
+    pub fn insert_failure(&mut self, url: &str, now: Instant) {
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        self.entries.insert(url.to_string(), CachedFavicon {
+            data_url: None,
+            expires_at: now + NEGATIVE_FAVICON_TTL,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Identifies the image type from its leading bytes. The declared content
+/// type is not trusted since error pages are often served as `image/*`.
+pub fn detect_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("image/jpeg");
+    }
+    if bytes.starts_with(&[0x00, 0x00, 0x01, 0x00]) {
+        return Some("image/x-icon");
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    if bytes.starts_with(b"BM") {
+        return Some("image/bmp");
+    }
+    
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+        return Some("image/svg+xml");
+    }
+    
+    None
+}
+
+pub fn validate_favicon(bytes: &[u8]) -> Result<String, String> {
+    if bytes.is_empty() {
+        return Err("Favicon response was empty".to_string());
+    }
+    if bytes.len() > MAX_FAVICON_BYTES {
+        return Err("Favicon response was too large".to_string());
+    }
+    
+    let mime = detect_image_mime(bytes)
+        .ok_or("Favicon response is not an image")?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    Ok(format!("data:{};base64,{}", mime, encoded))
+}
+
+/// A neutral rounded square with the site's initial, used when no real icon is available.
+pub fn placeholder_favicon(favicon_url: &str) -> String {
+    let initial = super::network::url_host(favicon_url)
+        .map(|host| host.trim_start_matches("www.").to_string())
+        .and_then(|host| host.chars().next())
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "?".to_string());
+    
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' width='32' height='32'><rect width='32' height='32' rx='6' fill='#9aa0a6'/><text x='16' y='22' font-family='sans-serif' font-size='18' text-anchor='middle' fill='#fff'>{}</text></svg>",
+        initial
+    );
+    let encoded = base64::engine::general_purpose::STANDARD.encode(svg);
+    format!("data:image/svg+xml;base64,{}", encoded)
+}
+
+async fn download_favicon(url: &str) -> Result<String, String> {
+    let client = super::network::http_client().await?;
+    let mut response = super::network::send_request(client.get(url), "fetch favicon").await?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Favicon request failed with status {}", response.status()));
+    }
+    
+    if response.content_length().is_some_and(|length| length > MAX_FAVICON_BYTES as u64) {
+        return Err("Favicon response was too large".to_string());
+    }
+    
+    // Read chunk by chunk so a body without a length can't be buffered past the limit.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read favicon: {}", e))? {
+        if bytes.len() + chunk.len() > MAX_FAVICON_BYTES {
+            return Err("Favicon response was too large".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    
+    validate_favicon(&bytes)
+}
+
+pub async fn fetch_favicon(url: &str) -> Favicon {
+    let cached = FAVICON_CACHE.read().await.get(url, Instant::now());
+    
+    let data_url = match cached {
+        Some(data_url) => data_url,
+        None => match download_favicon(url).await {
+            Ok(data_url) => {
+                FAVICON_CACHE.write().await.insert(url, data_url.clone(), Instant::now());
+                Some(data_url)
+            }
+            Err(_) => {
+                FAVICON_CACHE.write().await.insert_failure(url, Instant::now());
+                None
+            }
+        },
+    };
+    
+    match data_url {
+        Some(data_url) => Favicon {
+            url: url.to_string(),
+            data_url,
+            is_placeholder: false,
+        },
+        None => Favicon {
+            url: url.to_string(),
+            data_url: placeholder_favicon(url),
+            is_placeholder: true,
+        },
+    }
+}
+
+#[tauri::command]
+pub async fn get_favicon(url: String) -> Result<Favicon, String> {
+    Ok(fetch_favicon(&url).await)
+}
+
+#[tauri::command]
+pub async fn clear_favicon_cache() -> Result<(), String> {
+    FAVICON_CACHE.write().await.clear();
+    Ok(())
+}
\ No newline at end of file
@@ -1,9 +1,29 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use regex::Regex;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use tauri::{AppHandle, Emitter};
 use url::Url;
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock;
+use super::storage;
+
+const SHIELD_STATS_FILE: &str = "shield_stats.json";
+const CUSTOM_RULES_FILE: &str = "custom_filter_rules.json";
+const FILTER_LISTS_FILE: &str = "filter_lists.json";
+const SHIELD_STATS_SAVE_DELAY: Duration = Duration::from_secs(5);
+/// How often the background updater checks whether a refresh is due.
+const FILTER_UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Lists imported from text have no remote source; their URL uses this prefix.
+const LOCAL_FILTER_LIST_PREFIX: &str = "local://filter-lists/";
+/// The list holding rules the user added one at a time.
+const CUSTOM_RULES_LIST_ID: &str = "user-rules";
+/// Id prefix of lists added by URL.
+const REMOTE_FILTER_LIST_PREFIX: &str = "remote-";
+/// Id prefix of lists imported from text.
+const IMPORTED_FILTER_LIST_PREFIX: &str = "custom-";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FilterList {
@@ -12,9 +32,57 @@ pub struct FilterList {
     pub enabled: bool,
     pub last_updated: chrono::DateTime<chrono::Utc>,
     pub rules: Vec<FilterRule>,
+    /// Validators from the last download, sent back so an unchanged list
+    /// is answered with `304 Not Modified` instead of its full body.
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Why the last refresh failed; cleared by the next successful one.
+    #[serde(default)]
+    pub last_update_error: Option<String>,
+    #[serde(default)]
+    pub category: FilterListCategory,
+}
+
+/// What a list blocks, which decides the stats category of its matches.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterListCategory {
+    #[default]
+    Ads,
+    Privacy,
+}
+
+/// Where a remote list is refreshed from, with its cache validators.
+#[derive(Debug, Clone)]
+pub struct FilterListSource {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// A list body that was actually downloaded and parsed.
+#[derive(Debug, Clone)]
+pub struct FilterListDownload {
+    pub rules: Vec<FilterRule>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
+/// A filter list without its rules, as shown in list settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterListSummary {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub enabled: bool,
+    pub last_updated: chrono::DateTime<chrono::Utc>,
+    pub rules_count: usize,
+    pub last_update_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct FilterRule {
     pub pattern: String,
     pub rule_type: FilterRuleType,
@@ -23,15 +91,20 @@ pub struct FilterRule {
     pub options: FilterOptions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum FilterRuleType {
     Block,
     Allow,
     Hide,
+    /// A `#@#` exception that stops a `##` selector from being applied.
+    Unhide,
     Redirect,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which requests a rule applies to. Each flag enables one request type, or
+/// for `third_party`/`first_party` one side of the origin boundary.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(default)]
 pub struct FilterOptions {
     pub script: bool,
     pub image: bool,
@@ -40,6 +113,9 @@ pub struct FilterOptions {
     pub subdocument: bool,
     pub third_party: bool,
     pub popup: bool,
+    pub first_party: bool,
+    /// Request types without a flag of their own: documents, media, fonts and so on.
+    pub other: bool,
 }
 
 impl Default for FilterOptions {
@@ -52,10 +128,60 @@ impl Default for FilterOptions {
             subdocument: true,
             third_party: true,
             popup: true,
+            first_party: true,
+            other: true,
+        }
+    }
+}
+
+impl FilterOptions {
+    fn type_flag(&mut self, request_type: &str) -> Option<&mut bool> {
+        match request_type {
+            "script" => Some(&mut self.script),
+            "image" => Some(&mut self.image),
+            "stylesheet" => Some(&mut self.stylesheet),
+            "xmlhttprequest" | "xhr" => Some(&mut self.xmlhttprequest),
+            "subdocument" | "frame" => Some(&mut self.subdocument),
+            "popup" => Some(&mut self.popup),
+            "other" | "document" | "media" | "font" | "object" | "ping" | "websocket" => Some(&mut self.other),
+            _ => None,
+        }
+    }
+
+    fn applies_to_type(&self, request_type: &str) -> bool {
+        match request_type {
+            "script" => self.script,
+            "image" => self.image,
+            "stylesheet" => self.stylesheet,
+            "xmlhttprequest" => self.xmlhttprequest,
+            "subdocument" => self.subdocument,
+            "popup" => self.popup,
+            _ => self.other,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateRule {
+    pub pattern: String,
+    pub kept_in: String,
+    pub removed_from: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeduplicationReport {
+    pub removed: usize,
+    pub remaining: usize,
+    pub duplicates: Vec<DuplicateRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterListImport {
+    pub list_id: String,
+    pub rules_imported: usize,
+    pub lines_skipped: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SiteShields {
     pub domain: String,
@@ -67,9 +193,51 @@ pub struct SiteShields {
     pub scripts_blocked: u32,
     pub trackers_blocked: u32,
     pub ads_blocked: u32,
+    #[serde(default)]
+    pub total_requests: u32,
+    #[serde(default)]
+    pub third_party_requests: u32,
+    #[serde(default)]
+    pub fingerprinting_attempts: u32,
     pub last_updated: chrono::DateTime<chrono::Utc>,
 }
 
+/// Filter list state kept across launches. Lists imported from text are
+/// stored whole. Lists added by URL are stored without their rules or cache
+/// validators, so the next refresh downloads them in full like the default lists.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedFilterLists {
+    #[serde(default)]
+    pub lists: HashMap<String, FilterList>,
+    /// Whether each list, default ones included, is enabled.
+    #[serde(default)]
+    pub enabled: HashMap<String, bool>,
+}
+
+/// Per-site shields and the user's own rules, for backup and restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyData {
+    pub site_shields: HashMap<String, SiteShields>,
+    pub custom_rules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVerdict {
+    pub blocked: bool,
+    pub block_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyScore {
+    pub domain: String,
+    pub grade: String,
+    pub score: u32,
+    pub https: bool,
+    pub trackers_blocked: u32,
+    pub third_party_requests: u32,
+    pub fingerprinting_attempts: u32,
+}
+
 impl Default for SiteShields {
     fn default() -> Self {
         Self {
@@ -82,16 +250,58 @@ impl Default for SiteShields {
             scripts_blocked: 0,
             trackers_blocked: 0,
             ads_blocked: 0,
+            total_requests: 0,
+            third_party_requests: 0,
+            fingerprinting_attempts: 0,
             last_updated: chrono::Utc::now(),
         }
     }
 }
 
+/// Typical transfer sizes in bytes, used as the saving for a blocked request
+/// whose real size is unknown. Unlisted types use `other`.
+const AVERAGE_RESOURCE_BYTES: &[(&str, u64)] = &[
+    ("script", 50 * 1024),
+    ("image", 100 * 1024),
+    ("stylesheet", 30 * 1024),
+    ("subdocument", 80 * 1024),
+    ("xmlhttprequest", 10 * 1024),
+    ("media", 500 * 1024),
+    ("font", 40 * 1024),
+    ("other", 20 * 1024),
+];
+
+/// A rule's list id and position within that list.
+type RuleRef = (String, usize);
+
+/// A URL rule's pattern compiled for matching.
+pub struct CompiledPattern {
+    pub regex: Regex,
+    /// The longest literal every match contains, ASCII-lowercased. Checked
+    /// before the regex, which is much slower to reject a URL.
+    pub literal: Option<String>,
+}
+
+/// Tokens too common in URLs to narrow down the rules worth testing.
+const COMMON_URL_TOKENS: &[&str] = &["http", "https", "www", "com", "net", "org", "js", "html"];
+
 pub struct FilterEngine {
     pub filter_lists: HashMap<String, FilterList>,
     pub site_shields: HashMap<String, SiteShields>,
-    pub compiled_rules: HashMap<String, Regex>,
+    pub compiled_rules: HashMap<String, CompiledPattern>,
     pub global_stats: GlobalStats,
+    pub blocked_tracker_hosts: HashMap<String, u64>,
+    pub session_stats: SessionStats,
+    /// Rule lines as the user entered them; parsed into the `user-rules` list.
+    pub custom_rules: Vec<String>,
+    /// URL rules keyed by a token every matching URL contains, so a request
+    /// is only tested against rules sharing one of its tokens.
+    rule_index: HashMap<String, Vec<RuleRef>>,
+    /// URL rules with no usable token, tested against every request.
+    generic_rules: Vec<RuleRef>,
+    /// Element hiding rules keyed by each domain they are scoped to. Rules
+    /// for every site are under the empty key.
+    cosmetic_rules: HashMap<String, Vec<RuleRef>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -99,24 +309,76 @@ pub struct GlobalStats {
     pub total_ads_blocked: u64,
     pub total_trackers_blocked: u64,
     pub total_scripts_blocked: u64,
+    /// Estimated bytes not downloaded: the `Content-Length` of a blocked
+    /// response when known, otherwise the average for its resource type.
     pub bandwidth_saved: u64,
     pub last_reset: chrono::DateTime<chrono::Utc>,
 }
 
-static FILTER_ENGINE: Lazy<RwLock<FilterEngine>> = Lazy::new(|| {
+/// Blocks counted since the app started or since the last `reset_session_stats`.
+/// Unlike `GlobalStats` these are never persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub ads_blocked: u64,
+    pub trackers_blocked: u64,
+    pub scripts_blocked: u64,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SessionStats {
+    fn new() -> Self {
+        Self {
+            ads_blocked: 0,
+            trackers_blocked: 0,
+            scripts_blocked: 0,
+            started_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Per-site counters and lifetime totals, persisted across launches.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ShieldStats {
+    pub site_shields: HashMap<String, SiteShields>,
+    pub global_stats: GlobalStats,
+    #[serde(default)]
+    pub blocked_tracker_hosts: HashMap<String, u64>,
+}
+
+static SHIELD_STATS_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+pub(super) static FILTER_ENGINE: Lazy<RwLock<FilterEngine>> = Lazy::new(|| {
     RwLock::new(FilterEngine::new())
 });
 
 impl FilterEngine {
     pub fn new() -> Self {
+        let stats: ShieldStats = storage::load_json(SHIELD_STATS_FILE).unwrap_or_else(|| ShieldStats {
+            site_shields: HashMap::new(),
+            global_stats: GlobalStats {
+                last_reset: chrono::Utc::now(),
+                ..GlobalStats::default()
+            },
+            blocked_tracker_hosts: HashMap::new(),
+        });
+        
         let mut engine = Self {
             filter_lists: HashMap::new(),
-            site_shields: HashMap::new(),
+            site_shields: stats.site_shields,
             compiled_rules: HashMap::new(),
-            global_stats: GlobalStats::default(),
+            global_stats: stats.global_stats,
+            blocked_tracker_hosts: stats.blocked_tracker_hosts,
+            session_stats: SessionStats::new(),
+            custom_rules: storage::load_json(CUSTOM_RULES_FILE).unwrap_or_default(),
+            rule_index: HashMap::new(),
+            generic_rules: Vec::new(),
+            cosmetic_rules: HashMap::new(),
         };
         
         engine.load_default_filter_lists();
+        engine.rebuild_custom_rules_list();
+        engine.restore_filter_lists(storage::load_json(FILTER_LISTS_FILE).unwrap_or_default());
+        engine.index_rules();
         engine
     }
 
@@ -127,6 +389,10 @@ impl FilterEngine {
             enabled: true,
             last_updated: chrono::Utc::now(),
             rules: Vec::new(),
+            etag: None,
+            last_modified: None,
+            last_update_error: None,
+            category: FilterListCategory::Ads,
         };
         
         let easyprivacy = FilterList {
@@ -135,6 +401,10 @@ impl FilterEngine {
             enabled: true,
             last_updated: chrono::Utc::now(),
             rules: Vec::new(),
+            etag: None,
+            last_modified: None,
+            last_update_error: None,
+            category: FilterListCategory::Privacy,
         };
         
         self.filter_lists.insert("easylist".to_string(), easylist);
@@ -142,42 +412,162 @@ impl FilterEngine {
     }
 
     pub fn should_block_request(&self, url: &str, request_type: &str, origin_domain: &str) -> bool {
-        if let Ok(parsed_url) = Url::parse(url) {
-            let domain = parsed_url.domain().unwrap_or("");
+        self.block_reason(url, request_type, origin_domain).is_some()
+    }
+
+    /// The stats category (`ad`, `tracker` or `script`) a request is blocked
+    /// under, or `None` when it is allowed. A matching exception rule always
+    /// wins over block rules. Matches from privacy lists count as trackers,
+    /// other blocked scripts as scripts, the rest as ads.
+    pub fn block_reason(&self, url: &str, request_type: &str, origin_domain: &str) -> Option<&'static str> {
+        let parsed_url = Url::parse(url).ok()?;
+        let domain = parsed_url.domain().unwrap_or("");
+        
+        if let Some(shields) = self.site_shields.get(origin_domain) {
+            if !shields.ad_blocking && !shields.tracker_blocking {
+                return None;
+            }
             
-            if let Some(shields) = self.site_shields.get(origin_domain) {
-                if !shields.ad_blocking && !shields.tracker_blocking {
-                    return false;
-                }
-                
-                if shields.third_party_cookies && domain != origin_domain {
-                    return true;
-                }
+            if shields.third_party_cookies && domain != origin_domain {
+                return Some("tracker");
+            }
+        }
+        
+        let third_party = !is_same_site(&parsed_url.host_str().unwrap_or("").to_lowercase(), origin_domain);
+        let lowercase_url = url.to_ascii_lowercase();
+        let url_tokens: HashSet<String> = url_tokens(url).collect();
+        let candidates = url_tokens.iter()
+            .filter_map(|token| self.rule_index.get(token))
+            .flatten()
+            .chain(&self.generic_rules);
+        
+        let mut reason = None;
+        for (list_id, rule_index) in candidates {
+            let Some((category, rule)) = self.filter_lists.get(list_id)
+                .filter(|list| list.enabled)
+                .and_then(|list| Some((list.category, list.rules.get(*rule_index)?))) else {
+                continue;
+            };
+            if !self.matches_rule(url, &lowercase_url, rule, request_type, origin_domain, third_party) {
+                continue;
             }
             
-            for filter_list in self.filter_lists.values() {
-                if !filter_list.enabled {
+            match rule.rule_type {
+                FilterRuleType::Allow => return None,
+                FilterRuleType::Block if category == FilterListCategory::Privacy => reason = Some("tracker"),
+                FilterRuleType::Block if request_type == "script" => reason = reason.or(Some("script")),
+                FilterRuleType::Block => reason = reason.or(Some("ad")),
+                _ => {}
+            }
+        }
+        
+        reason
+    }
+
+    /// Compiles every URL rule's pattern into `compiled_rules` and rebuilds the
+    /// token and cosmetic indexes. Must run whenever list contents change,
+    /// since the indexes refer to rules by position.
+    fn index_rules(&mut self) {
+        let mut previous = std::mem::take(&mut self.compiled_rules);
+        self.rule_index.clear();
+        self.generic_rules.clear();
+        self.cosmetic_rules.clear();
+        
+        for (list_id, list) in &self.filter_lists {
+            for (index, rule) in list.rules.iter().enumerate() {
+                if matches!(rule.rule_type, FilterRuleType::Hide | FilterRuleType::Unhide) {
+                    let generic = [String::new()];
+                    for domain in rule.domains.as_deref().unwrap_or(&generic) {
+                        self.cosmetic_rules.entry(domain.clone()).or_default().push((list_id.clone(), index));
+                    }
+                    continue;
+                }
+                if !matches!(rule.rule_type, FilterRuleType::Block | FilterRuleType::Allow) {
                     continue;
                 }
                 
-                for rule in &filter_list.rules {
-                    if self.matches_rule(url, rule, request_type, origin_domain) {
-                        match rule.rule_type {
-                            FilterRuleType::Block => return true,
-                            FilterRuleType::Allow => return false,
-                            _ => continue,
-                        }
-                    }
+                if !self.compiled_rules.contains_key(&rule.pattern) {
+                    let compiled = previous.remove(&rule.pattern)
+                        .map(Ok)
+                        .unwrap_or_else(|| Self::pattern_to_regex(&rule.pattern).map(|regex| CompiledPattern {
+                            regex,
+                            literal: pattern_literal(&rule.pattern),
+                        }));
+                    let Ok(compiled) = compiled else {
+                        continue;
+                    };
+                    self.compiled_rules.insert(rule.pattern.clone(), compiled);
+                }
+                
+                let rule_ref = (list_id.clone(), index);
+                match pattern_token(&rule.pattern) {
+                    Some(token) => self.rule_index.entry(token).or_default().push(rule_ref),
+                    None => self.generic_rules.push(rule_ref),
+                }
+            }
+        }
+    }
+
+    /// CSS selectors to hide on pages of `domain`: generic rules plus those
+    /// scoped to the domain or a parent domain, minus any `#@#` exceptions.
+    pub fn get_cosmetic_filters(&self, domain: &str) -> Vec<String> {
+        let host = domain.trim().trim_end_matches('.').to_lowercase();
+        let parent_domains = host.match_indices('.').map(|(dot, _)| &host[dot + 1..]);
+        let keys = std::iter::once("").chain(std::iter::once(host.as_str())).chain(parent_domains);
+        
+        let mut hidden = Vec::new();
+        let mut unhidden = HashSet::new();
+        for (list_id, index) in keys.filter_map(|key| self.cosmetic_rules.get(key)).flatten() {
+            let Some(rule) = self.filter_lists.get(list_id)
+                .filter(|list| list.enabled)
+                .and_then(|list| list.rules.get(*index)) else {
+                continue;
+            };
+            if rule.exceptions.iter().flatten().any(|excluded| super::network::host_matches_domain(&host, excluded)) {
+                continue;
+            }
+            
+            match rule.rule_type {
+                FilterRuleType::Hide => hidden.push(rule.pattern.clone()),
+                FilterRuleType::Unhide => {
+                    unhidden.insert(rule.pattern.clone());
                 }
+                _ => {}
             }
         }
         
-        false
+        let mut seen = HashSet::new();
+        hidden.into_iter()
+            .filter(|selector| !unhidden.contains(selector) && seen.insert(selector.clone()))
+            .collect()
+    }
+
+    /// Checks a request a page is about to make and updates the shield
+    /// counters for `origin_domain`: every request is recorded, and blocked
+    /// ones are tallied under their block reason.
+    pub fn intercept_request(&mut self, url: &str, request_type: &str, origin_domain: &str) -> RequestVerdict {
+        self.record_request(url, origin_domain);
+        
+        let block_type = self.block_reason(url, request_type, origin_domain);
+        if let Some(block_type) = block_type {
+            let blocked_host = super::network::url_host(url);
+            self.increment_blocked_count(origin_domain, block_type, blocked_host.as_deref());
+            self.record_bandwidth_saved(estimated_resource_bytes(request_type));
+        }
+        
+        RequestVerdict {
+            blocked: block_type.is_some(),
+            block_type: block_type.map(|block_type| block_type.to_string()),
+        }
     }
 
-    fn matches_rule(&self, url: &str, rule: &FilterRule, request_type: &str, origin_domain: &str) -> bool {
-        if let Some(regex) = self.compiled_rules.get(&rule.pattern) {
-            if !regex.is_match(url) {
+    /// `lowercase_url` is `url` ASCII-lowercased, shared across the rules tested.
+    fn matches_rule(&self, url: &str, lowercase_url: &str, rule: &FilterRule, request_type: &str, origin_domain: &str, third_party: bool) -> bool {
+        if let Some(compiled) = self.compiled_rules.get(&rule.pattern) {
+            if compiled.literal.as_ref().is_some_and(|literal| !lowercase_url.contains(literal.as_str())) {
+                return false;
+            }
+            if !compiled.regex.is_match(url) {
                 return false;
             }
         } else {
@@ -186,136 +576,1442 @@ impl FilterEngine {
             }
         }
         
+        Self::rule_applies(rule, request_type, origin_domain, third_party)
+    }
+
+    /// `domain=` entries cover their subdomains too, as in EasyList.
+    fn rule_applies(rule: &FilterRule, request_type: &str, origin_domain: &str, third_party: bool) -> bool {
         if let Some(domains) = &rule.domains {
-            if !domains.contains(&origin_domain.to_string()) {
+            if !domains.iter().any(|domain| super::network::host_matches_domain(origin_domain, domain)) {
                 return false;
             }
         }
         
         if let Some(exceptions) = &rule.exceptions {
-            if exceptions.contains(&origin_domain.to_string()) {
+            if exceptions.iter().any(|domain| super::network::host_matches_domain(origin_domain, domain)) {
                 return false;
             }
         }
         
-        match request_type {
-            "script" => rule.options.script,
-            "image" => rule.options.image,
-            "stylesheet" => rule.options.stylesheet,
-            "xmlhttprequest" => rule.options.xmlhttprequest,
-            "subdocument" => rule.options.subdocument,
-            _ => true,
+        if third_party && !rule.options.third_party || !third_party && !rule.options.first_party {
+            return false;
+        }
+        
+        rule.options.applies_to_type(request_type)
+    }
+
+    /// Evaluates a single rule against sample URLs without adding it to any list.
+    /// Each URL is treated as a first-party request from its own domain.
+    pub fn test_rule(rule: &str, sample_urls: &[String]) -> Result<Vec<(String, bool)>, String> {
+        let rule = Self::parse_filter_rule(rule)?;
+        
+        if matches!(rule.rule_type, FilterRuleType::Hide | FilterRuleType::Unhide) {
+            return Err("Element hiding rules do not match URLs".to_string());
+        }
+        
+        let regex = Self::pattern_to_regex(&rule.pattern)?;
+        
+        Ok(sample_urls.iter()
+            .map(|url| {
+                let origin_domain = Url::parse(url)
+                    .ok()
+                    .and_then(|parsed| parsed.domain().map(|d| d.to_string()))
+                    .unwrap_or_default();
+                let matched = regex.is_match(url) && Self::rule_applies(&rule, "other", &origin_domain, false);
+                (url.clone(), matched)
+            })
+            .collect())
+    }
+
+    /// Translates Adblock pattern syntax into a case-insensitive regex: `||` anchors
+    /// to a domain, `|` to the start or end of the URL, `*` is a wildcard and `^`
+    /// matches a separator. Patterns wrapped in slashes are used as raw regexes.
+    pub fn pattern_to_regex(pattern: &str) -> Result<Regex, String> {
+        if pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/') {
+            return Regex::new(&format!("(?i){}", &pattern[1..pattern.len() - 1]))
+                .map_err(|e| format!("Invalid regular expression rule: {}", e));
+        }
+        
+        let mut body = pattern;
+        let mut regex = String::from("(?i)");
+        
+        if let Some(rest) = body.strip_prefix("||") {
+            regex.push_str(r"^[a-z][a-z0-9+.-]*://([^/?#]*\.)?");
+            body = rest;
+        } else if let Some(rest) = body.strip_prefix('|') {
+            regex.push('^');
+            body = rest;
+        }
+        
+        let anchored_end = body.ends_with('|');
+        let body = body.trim_end_matches('|');
+        
+        for c in body.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '^' => regex.push_str(r"(?:[^0-9A-Za-z_.%-]|$)"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        
+        if anchored_end {
+            regex.push('$');
+        }
+        
+        Regex::new(&regex).map_err(|e| format!("Invalid filter pattern: {}", e))
+    }
+
+    /// Removes rules that are exact duplicates of a rule in another enabled list.
+    /// Lists are visited in id order and the first copy is kept. Block and allow
+    /// rules with the same pattern are distinct, so exceptions are never dropped
+    /// in favour of a block rule.
+    pub fn deduplicate_rules(&mut self) -> DeduplicationReport {
+        let mut list_ids: Vec<String> = self.filter_lists
+            .iter()
+            .filter(|(_, list)| list.enabled)
+            .map(|(id, _)| id.clone())
+            .collect();
+        list_ids.sort();
+        
+        let mut seen: HashMap<FilterRule, String> = HashMap::new();
+        let mut duplicates = Vec::new();
+        
+        for list_id in &list_ids {
+            if let Some(list) = self.filter_lists.get_mut(list_id) {
+                list.rules.retain(|rule| match seen.get(rule) {
+                    Some(kept_in) => {
+                        duplicates.push(DuplicateRule {
+                            pattern: rule.pattern.clone(),
+                            kept_in: kept_in.clone(),
+                            removed_from: list_id.clone(),
+                        });
+                        false
+                    }
+                    None => {
+                        seen.insert(rule.clone(), list_id.clone());
+                        true
+                    }
+                });
+            }
+        }
+        
+        self.index_rules();
+        
+        DeduplicationReport {
+            removed: duplicates.len(),
+            remaining: self.count_total_rules(),
+            duplicates,
         }
     }
 
+    pub fn count_total_rules(&self) -> usize {
+        self.filter_lists.values()
+            .filter(|list| list.enabled)
+            .map(|list| list.rules.len())
+            .sum()
+    }
+
     pub fn update_site_shields(&mut self, domain: &str, shields: SiteShields) {
         self.site_shields.insert(domain.to_string(), shields);
     }
 
+    /// Puts a site's toggles back to the defaults. Its counters are kept.
+    pub fn reset_site_shields(&mut self, domain: &str) -> SiteShields {
+        let defaults = SiteShields::default();
+        let shields = self.shields_mut(domain);
+        shields.ad_blocking = defaults.ad_blocking;
+        shields.tracker_blocking = defaults.tracker_blocking;
+        shields.third_party_cookies = defaults.third_party_cookies;
+        shields.fingerprinting_protection = defaults.fingerprinting_protection;
+        shields.https_only = defaults.https_only;
+        shields.last_updated = defaults.last_updated;
+        shields.clone()
+    }
+
     pub fn get_site_shields(&self, domain: &str) -> SiteShields {
         self.site_shields.get(domain)
             .cloned()
-            .unwrap_or_else(|| {
-                let mut shields = SiteShields::default();
-                shields.domain = domain.to_string();
-                shields
+            .unwrap_or_else(|| SiteShields {
+                domain: domain.to_string(),
+                ..Default::default()
             })
     }
 
-    pub fn increment_blocked_count(&mut self, domain: &str, block_type: &str) {
-        if let Some(shields) = self.site_shields.get_mut(domain) {
-            match block_type {
-                "ad" => {
-                    shields.ads_blocked += 1;
-                    self.global_stats.total_ads_blocked += 1;
-                }
-                "tracker" => {
-                    shields.trackers_blocked += 1;
-                    self.global_stats.total_trackers_blocked += 1;
-                }
-                "script" => {
-                    shields.scripts_blocked += 1;
-                    self.global_stats.total_scripts_blocked += 1;
-                }
-                _ => {}
+    fn shields_mut(&mut self, domain: &str) -> &mut SiteShields {
+        self.site_shields.entry(domain.to_string())
+            .or_insert_with(|| SiteShields {
+                domain: domain.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// `blocked_host` is the host the blocked request was going to; tracker
+    /// blocks are tallied per host for the top-trackers view.
+    pub fn increment_blocked_count(&mut self, domain: &str, block_type: &str, blocked_host: Option<&str>) {
+        let shields = self.shields_mut(domain);
+        match block_type {
+            "ad" => shields.ads_blocked += 1,
+            "tracker" => shields.trackers_blocked += 1,
+            "script" => shields.scripts_blocked += 1,
+            _ => {}
+        }
+        shields.last_updated = chrono::Utc::now();
+        
+        match block_type {
+            "ad" => {
+                self.global_stats.total_ads_blocked += 1;
+                self.session_stats.ads_blocked += 1;
+            }
+            "tracker" => {
+                self.global_stats.total_trackers_blocked += 1;
+                self.session_stats.trackers_blocked += 1;
+            }
+            "script" => {
+                self.global_stats.total_scripts_blocked += 1;
+                self.session_stats.scripts_blocked += 1;
+            }
+            _ => {}
+        }
+        
+        if block_type == "tracker" {
+            if let Some(host) = blocked_host.map(|host| host.trim().trim_end_matches('.').to_lowercase()).filter(|host| !host.is_empty()) {
+                *self.blocked_tracker_hosts.entry(host).or_insert(0) += 1;
             }
-            shields.last_updated = chrono::Utc::now();
         }
     }
 
-    pub async fn update_filter_lists(&mut self) -> Result<(), String> {
-        for (_, filter_list) in self.filter_lists.iter_mut() {
-            if let Ok(response) = reqwest::get(&filter_list.url).await {
-                if let Ok(content) = response.text().await {
-                    let parsed_rules = Self::parse_filter_rules(&content);
-                    filter_list.rules = parsed_rules;
-                    filter_list.last_updated = chrono::Utc::now();
-                }
-            }
+    /// Most-blocked tracker hosts, ties broken alphabetically.
+    pub fn top_blocked_trackers(&self, limit: usize) -> Vec<(String, u64)> {
+        let mut trackers: Vec<(String, u64)> = self.blocked_tracker_hosts
+            .iter()
+            .map(|(host, count)| (host.clone(), *count))
+            .collect();
+        trackers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        trackers.truncate(limit);
+        trackers
+    }
+
+    pub fn save_shield_stats(&self) -> Result<(), String> {
+        storage::save_json(SHIELD_STATS_FILE, &self.shield_stats())
+    }
+
+    pub fn shield_stats(&self) -> ShieldStats {
+        ShieldStats {
+            site_shields: self.site_shields.clone(),
+            global_stats: self.global_stats.clone(),
+            blocked_tracker_hosts: self.blocked_tracker_hosts.clone(),
         }
-        Ok(())
     }
 
-    fn parse_filter_rules(content: &str) -> Vec<FilterRule> {
-        let mut rules = Vec::new();
+    /// Zeroes every counter but keeps each site's shield toggles.
+    pub fn reset_stats(&mut self) {
+        let now = chrono::Utc::now();
         
-        for line in content.lines() {
-            let line = line.trim();
-            
-            if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
-                continue;
-            }
-            
-            let rule_type = if line.starts_with("@@") {
-                FilterRuleType::Allow
-            } else if line.contains("##") {
-                FilterRuleType::Hide
-            } else {
-                FilterRuleType::Block
-            };
-            
-            let pattern = line.replace("@@", "").split("$").next().unwrap_or(line).to_string();
-            
-            rules.push(FilterRule {
-                pattern,
-                rule_type,
-                domains: None,
-                exceptions: None,
-                options: FilterOptions::default(),
-            });
+        for shields in self.site_shields.values_mut() {
+            shields.ads_blocked = 0;
+            shields.trackers_blocked = 0;
+            shields.scripts_blocked = 0;
+            shields.total_requests = 0;
+            shields.third_party_requests = 0;
+            shields.fingerprinting_attempts = 0;
+            shields.last_updated = now;
         }
         
-        rules
+        self.global_stats = GlobalStats {
+            last_reset: now,
+            ..GlobalStats::default()
+        };
+        self.blocked_tracker_hosts.clear();
     }
-}
 
-#[tauri::command]
-pub async fn get_site_shields(domain: String) -> Result<SiteShields, String> {
-    let engine = FILTER_ENGINE.read().await;
-    Ok(engine.get_site_shields(&domain))
-}
+    /// Zeroes the lifetime totals and restarts `last_reset`. Per-site counters are kept.
+    pub fn reset_global_stats(&mut self) {
+        self.global_stats = GlobalStats {
+            last_reset: chrono::Utc::now(),
+            ..GlobalStats::default()
+        };
+    }
 
-#[tauri::command]
-pub async fn update_site_shields(domain: String, shields: SiteShields) -> Result<(), String> {
-    let mut engine = FILTER_ENGINE.write().await;
-    engine.update_site_shields(&domain, shields);
-    Ok(())
-}
+    pub fn record_bandwidth_saved(&mut self, bytes: u64) {
+        self.global_stats.bandwidth_saved = self.global_stats.bandwidth_saved.saturating_add(bytes);
+    }
 
-#[tauri::command]
-pub async fn get_global_stats() -> Result<GlobalStats, String> {
-    let engine = FILTER_ENGINE.read().await;
-    Ok(engine.global_stats.clone())
-}
+    /// Starts a new session count; lifetime totals are untouched.
+    pub fn reset_session_stats(&mut self) {
+        self.session_stats = SessionStats::new();
+    }
 
-#[tauri::command]
-pub async fn should_block_request(url: String, request_type: String, origin_domain: String) -> Result<bool, String> {
-    let engine = FILTER_ENGINE.read().await;
-    Ok(engine.should_block_request(&url, &request_type, &origin_domain))
-}
+    /// Counts a request made by a page on `origin_domain`, noting whether it went to a third party.
+    pub fn record_request(&mut self, url: &str, origin_domain: &str) {
+        let request_domain = Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.domain().map(|d| d.to_string()))
+            .unwrap_or_default();
+        let is_third_party = !is_same_site(&request_domain, origin_domain);
+        
+        let shields = self.shields_mut(origin_domain);
+        shields.total_requests += 1;
+        if is_third_party {
+            shields.third_party_requests += 1;
+        }
+    }
 
-#[tauri::command]
-pub async fn update_filter_lists() -> Result<(), String> {
-    let mut engine = FILTER_ENGINE.write().await;
-    engine.update_filter_lists().await
+    pub fn record_fingerprinting_attempt(&mut self, domain: &str) {
+        let shields = self.shields_mut(domain);
+        shields.fingerprinting_attempts += 1;
+        shields.last_updated = chrono::Utc::now();
+    }
+
+    pub fn compute_privacy_score(&self, url: &str) -> Result<PrivacyScore, String> {
+        let parsed_url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+        let domain = parsed_url.domain().unwrap_or("").to_string();
+        let https = parsed_url.scheme() == "https";
+        let shields = self.get_site_shields(&domain);
+        
+        let score = privacy_score(
+            https,
+            shields.trackers_blocked,
+            shields.third_party_requests,
+            shields.total_requests,
+            shields.fingerprinting_attempts,
+        );
+        
+        Ok(PrivacyScore {
+            domain,
+            grade: privacy_grade(score).to_string(),
+            score,
+            https,
+            trackers_blocked: shields.trackers_blocked,
+            third_party_requests: shields.third_party_requests,
+            fingerprinting_attempts: shields.fingerprinting_attempts,
+        })
+    }
+
+    /// Adds a personal filter list from pasted text. Its rules are compiled
+    /// straight away so they take part in matching like any downloaded list.
+    pub fn import_filter_list_from_text(&mut self, name: &str, content: &str) -> Result<FilterListImport, String> {
+        let name = name.trim();
+        let slug = filter_list_slug(name);
+        if slug.is_empty() {
+            return Err("Filter list name is required".to_string());
+        }
+        
+        let list_id = format!("{}{}", IMPORTED_FILTER_LIST_PREFIX, slug);
+        if self.filter_lists.contains_key(&list_id) {
+            return Err(format!("Filter list already exists: {}", name));
+        }
+        
+        let rules = Self::parse_filter_rules(content);
+        let rule_lines = content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('!') && !line.starts_with('['))
+            .count();
+        if rules.is_empty() {
+            return Err("No valid filter rules found".to_string());
+        }
+        
+        let import = FilterListImport {
+            list_id: list_id.clone(),
+            rules_imported: rules.len(),
+            lines_skipped: rule_lines - rules.len(),
+        };
+        
+        self.filter_lists.insert(list_id.clone(), FilterList {
+            name: name.to_string(),
+            url: format!("{}{}", LOCAL_FILTER_LIST_PREFIX, list_id),
+            enabled: true,
+            last_updated: chrono::Utc::now(),
+            rules,
+            etag: None,
+            last_modified: None,
+            last_update_error: None,
+            category: FilterListCategory::Ads,
+        });
+        self.index_rules();
+        
+        Ok(import)
+    }
+
+    pub fn get_filter_list_rules(&self, list_id: &str) -> Result<Vec<FilterRule>, String> {
+        self.filter_lists.get(list_id)
+            .map(|list| list.rules.clone())
+            .ok_or_else(|| format!("Filter list not found: {}", list_id))
+    }
+
+    pub fn filter_list_summaries(&self) -> Vec<FilterListSummary> {
+        let mut summaries: Vec<FilterListSummary> = self.filter_lists.keys()
+            .filter_map(|id| self.filter_list_summary(id).ok())
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
+
+    pub fn filter_list_summary(&self, list_id: &str) -> Result<FilterListSummary, String> {
+        let list = self.filter_lists.get(list_id)
+            .ok_or_else(|| format!("Filter list not found: {}", list_id))?;
+        Ok(FilterListSummary {
+            id: list_id.to_string(),
+            name: list.name.clone(),
+            url: list.url.clone(),
+            enabled: list.enabled,
+            last_updated: list.last_updated,
+            rules_count: list.rules.len(),
+            last_update_error: list.last_update_error.clone(),
+        })
+    }
+
+    /// Adds a list downloaded from `url`. It is named after the file it was
+    /// fetched from and kept up to date by `update_filter_lists` like the defaults.
+    pub fn add_filter_list(&mut self, url: &str, download: FilterListDownload) -> Result<FilterListSummary, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+        if self.filter_lists.values().any(|list| list.url == parsed.as_str()) {
+            return Err(format!("Filter list already added: {}", url));
+        }
+        if download.rules.is_empty() {
+            return Err("No valid filter rules found".to_string());
+        }
+        
+        let host = parsed.host_str().unwrap_or_default();
+        let list_id = format!("{}{}", REMOTE_FILTER_LIST_PREFIX, filter_list_slug(&format!("{}{}", host, parsed.path())));
+        let name = parsed.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .map(|file| file.split('.').next().unwrap_or(file))
+            .filter(|file| !file.is_empty())
+            .unwrap_or(host)
+            .to_string();
+        
+        self.filter_lists.insert(list_id.clone(), FilterList {
+            name,
+            url: parsed.to_string(),
+            enabled: true,
+            last_updated: chrono::Utc::now(),
+            rules: download.rules,
+            etag: download.etag,
+            last_modified: download.last_modified,
+            last_update_error: None,
+            category: FilterListCategory::Ads,
+        });
+        self.index_rules();
+        
+        self.filter_list_summary(&list_id)
+    }
+
+    pub fn remove_filter_list(&mut self, list_id: &str) -> Result<(), String> {
+        if list_id == CUSTOM_RULES_LIST_ID {
+            return Err("Custom rules are removed one rule at a time".to_string());
+        }
+        if self.filter_lists.remove(list_id).is_none() {
+            return Err(format!("Filter list not found: {}", list_id));
+        }
+        
+        self.index_rules();
+        Ok(())
+    }
+
+    pub fn set_filter_list_enabled(&mut self, list_id: &str, enabled: bool) -> Result<(), String> {
+        let list = self.filter_lists.get_mut(list_id)
+            .ok_or_else(|| format!("Filter list not found: {}", list_id))?;
+        list.enabled = enabled;
+        Ok(())
+    }
+
+    pub fn saved_filter_lists(&self) -> SavedFilterLists {
+        SavedFilterLists {
+            lists: self.filter_lists.iter()
+                .filter_map(|(id, list)| {
+                    if id.starts_with(IMPORTED_FILTER_LIST_PREFIX) {
+                        Some((id.clone(), list.clone()))
+                    } else if id.starts_with(REMOTE_FILTER_LIST_PREFIX) {
+                        Some((id.clone(), FilterList {
+                            rules: Vec::new(),
+                            etag: None,
+                            last_modified: None,
+                            ..list.clone()
+                        }))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            enabled: self.filter_lists.iter()
+                .map(|(id, list)| (id.clone(), list.enabled))
+                .collect(),
+        }
+    }
+
+    /// Adds saved lists that aren't loaded yet and applies the saved enabled
+    /// flags. Callers reindex afterwards.
+    fn restore_filter_lists(&mut self, saved: SavedFilterLists) {
+        for (list_id, list) in saved.lists {
+            self.filter_lists.entry(list_id).or_insert(list);
+        }
+        for (list_id, enabled) in saved.enabled {
+            if let Some(list) = self.filter_lists.get_mut(&list_id) {
+                list.enabled = enabled;
+            }
+        }
+    }
+
+    pub fn save_filter_lists(&self) -> Result<(), String> {
+        storage::save_json(FILTER_LISTS_FILE, &self.saved_filter_lists())
+    }
+
+    /// Where a list is refreshed from. Lists entered locally have no source.
+    pub fn filter_list_source(&self, list_id: &str) -> Result<FilterListSource, String> {
+        let list = self.filter_lists.get(list_id)
+            .ok_or_else(|| format!("Filter list not found: {}", list_id))?;
+        if list.url.starts_with(LOCAL_FILTER_LIST_PREFIX) {
+            return Err(format!("Filter list has no remote source: {}", list.name));
+        }
+        Ok(FilterListSource {
+            url: list.url.clone(),
+            etag: list.etag.clone(),
+            last_modified: list.last_modified.clone(),
+        })
+    }
+
+    pub fn remote_filter_list_sources(&self, enabled_only: bool) -> Vec<(String, FilterListSource)> {
+        self.filter_lists.iter()
+            .filter(|(_, list)| list.enabled || !enabled_only)
+            .filter_map(|(id, _)| Some((id.clone(), self.filter_list_source(id).ok()?)))
+            .collect()
+    }
+
+    /// Records the outcome of refreshing each list. Failed or empty downloads
+    /// keep the old rules and set `last_update_error`; a `None` download means
+    /// the list was not modified. Rules are reindexed once if any changed.
+    pub fn apply_filter_list_fetches(&mut self, results: Vec<(String, Result<Option<FilterListDownload>, String>)>) {
+        let mut changed = false;
+        
+        for (list_id, result) in results {
+            let Some(list) = self.filter_lists.get_mut(&list_id) else {
+                continue;
+            };
+            
+            match result {
+                Ok(None) => list.last_update_error = None,
+                Ok(Some(download)) if download.rules.is_empty() => {
+                    list.last_update_error = Some("No valid filter rules found".to_string());
+                }
+                Ok(Some(download)) => {
+                    list.rules = download.rules;
+                    list.etag = download.etag;
+                    list.last_modified = download.last_modified;
+                    list.last_updated = chrono::Utc::now();
+                    list.last_update_error = None;
+                    changed = true;
+                }
+                Err(e) => list.last_update_error = Some(e),
+            }
+        }
+        
+        if changed {
+            self.index_rules();
+        }
+    }
+
+    /// Rebuilds the `user-rules` list from `custom_rules`, keeping whether it is enabled.
+    fn rebuild_custom_rules_list(&mut self) {
+        let rules = self.custom_rules.iter()
+            .filter_map(|line| Self::parse_filter_rule(line).ok())
+            .collect();
+        let enabled = self.filter_lists.get(CUSTOM_RULES_LIST_ID).is_none_or(|list| list.enabled);
+        
+        self.filter_lists.insert(CUSTOM_RULES_LIST_ID.to_string(), FilterList {
+            name: "Custom rules".to_string(),
+            url: format!("{}{}", LOCAL_FILTER_LIST_PREFIX, CUSTOM_RULES_LIST_ID),
+            enabled,
+            last_updated: chrono::Utc::now(),
+            rules,
+            etag: None,
+            last_modified: None,
+            last_update_error: None,
+            category: FilterListCategory::Ads,
+        });
+    }
+
+    pub fn add_custom_rule(&mut self, rule: &str) -> Result<(), String> {
+        let rule = rule.trim();
+        Self::parse_filter_rule(rule)?;
+        if self.custom_rules.iter().any(|existing| existing == rule) {
+            return Err(format!("Rule already added: {}", rule));
+        }
+        
+        self.custom_rules.push(rule.to_string());
+        self.rebuild_custom_rules_list();
+        self.index_rules();
+        Ok(())
+    }
+
+    pub fn remove_custom_rule(&mut self, rule: &str) -> Result<(), String> {
+        let rule = rule.trim();
+        let position = self.custom_rules.iter()
+            .position(|existing| existing == rule)
+            .ok_or_else(|| format!("Rule not found: {}", rule))?;
+        
+        self.custom_rules.remove(position);
+        self.rebuild_custom_rules_list();
+        self.index_rules();
+        Ok(())
+    }
+
+    pub fn save_custom_rules(&self) -> Result<(), String> {
+        storage::save_json(CUSTOM_RULES_FILE, &self.custom_rules)
+    }
+
+    pub fn export_privacy_data(&self) -> PrivacyData {
+        PrivacyData {
+            site_shields: self.site_shields.clone(),
+            custom_rules: self.custom_rules.clone(),
+        }
+    }
+
+    /// Replaces per-site shields and custom rules with a backup. Nothing is
+    /// changed if any of the backup's rules fails to parse.
+    pub fn import_privacy_data(&mut self, data: PrivacyData) -> Result<(), String> {
+        for rule in &data.custom_rules {
+            Self::parse_filter_rule(rule).map_err(|e| format!("Invalid rule {}: {}", rule, e))?;
+        }
+        
+        self.site_shields = data.site_shields;
+        let mut seen = HashSet::new();
+        self.custom_rules = data.custom_rules.into_iter()
+            .filter(|rule| seen.insert(rule.clone()))
+            .collect();
+        self.rebuild_custom_rules_list();
+        self.index_rules();
+        Ok(())
+    }
+
+    fn parse_filter_rules(content: &str) -> Vec<FilterRule> {
+        content.lines()
+            .filter_map(|line| Self::parse_filter_rule(line).ok())
+            .collect()
+    }
+
+    pub fn parse_filter_rule(line: &str) -> Result<FilterRule, String> {
+        let line = line.trim();
+        
+        if line.is_empty() {
+            return Err("Rule is empty".to_string());
+        }
+        
+        if line.starts_with('!') || line.starts_with('[') {
+            return Err("Rule is a comment".to_string());
+        }
+        
+        if !line.starts_with("@@") {
+            let separator = ["##", "#@#"].into_iter()
+                .filter_map(|separator| line.find(separator).map(|at| (at, separator)))
+                .min();
+            if let Some((at, separator)) = separator {
+                return Self::parse_cosmetic_rule(&line[..at], &line[at + separator.len()..], separator == "#@#");
+            }
+        }
+        
+        let rule_type = if line.starts_with("@@") {
+            FilterRuleType::Allow
+        } else {
+            FilterRuleType::Block
+        };
+        
+        let line = line.strip_prefix("@@").unwrap_or(line);
+        let (pattern, options) = match line.rsplit_once('$') {
+            Some((pattern, options)) if !options.contains('/') || !pattern.starts_with('/') => (pattern, Some(options)),
+            _ => (line, None),
+        };
+        
+        if pattern.is_empty() {
+            return Err("Rule has no pattern".to_string());
+        }
+        
+        Self::pattern_to_regex(pattern)?;
+        
+        let mut rule = FilterRule {
+            pattern: pattern.to_string(),
+            rule_type,
+            domains: None,
+            exceptions: None,
+            options: FilterOptions::default(),
+        };
+        if let Some(options) = options {
+            Self::apply_rule_options(&mut rule, options)?;
+        }
+        
+        Ok(rule)
+    }
+
+    /// Parses `domains##selector` or, for exceptions, `domains#@#selector`.
+    /// The selector is kept as the pattern; `~domain` entries become exceptions.
+    fn parse_cosmetic_rule(domains: &str, selector: &str, unhide: bool) -> Result<FilterRule, String> {
+        let selector = selector.trim();
+        if selector.is_empty() {
+            return Err("Element hiding rule has no selector".to_string());
+        }
+        
+        let (excluded, included): (Vec<String>, Vec<String>) = domains.split(',')
+            .map(|domain| domain.trim().to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .partition(|domain| domain.starts_with('~'));
+        
+        Ok(FilterRule {
+            pattern: selector.to_string(),
+            rule_type: if unhide { FilterRuleType::Unhide } else { FilterRuleType::Hide },
+            domains: (!included.is_empty()).then_some(included),
+            exceptions: (!excluded.is_empty()).then(|| excluded.iter().map(|domain| domain.trim_start_matches('~').to_string()).collect()),
+            options: FilterOptions::default(),
+        })
+    }
+
+    /// Applies a rule's `$` options. Listing request types restricts the rule
+    /// to them, while `~type` only excludes that type. `third-party` and
+    /// `~third-party` pick one side of the origin boundary, and `domain=a|~b`
+    /// scopes the rule to origins. Options that would change what the rule
+    /// does rather than what it matches, such as `redirect` or `csp`, are rejected.
+    fn apply_rule_options(rule: &mut FilterRule, options: &str) -> Result<(), String> {
+        let mut included_types = FilterOptions {
+            script: false,
+            image: false,
+            stylesheet: false,
+            xmlhttprequest: false,
+            subdocument: false,
+            popup: false,
+            other: false,
+            ..FilterOptions::default()
+        };
+        let mut has_included_type = false;
+        
+        for option in options.split(',').map(|option| option.trim().to_lowercase()) {
+            let (negated, name) = match option.strip_prefix('~') {
+                Some(name) => (true, name.to_string()),
+                None => (false, option.clone()),
+            };
+            
+            if let Some(domains) = name.strip_prefix("domain=") {
+                let (excluded, included): (Vec<&str>, Vec<&str>) = domains.split('|')
+                    .filter(|domain| !domain.is_empty())
+                    .partition(|domain| domain.starts_with('~'));
+                if !included.is_empty() {
+                    rule.domains = Some(included.iter().map(|domain| domain.to_string()).collect());
+                }
+                if !excluded.is_empty() {
+                    rule.exceptions = Some(excluded.iter().map(|domain| domain.trim_start_matches('~').to_string()).collect());
+                }
+                continue;
+            }
+            
+            match name.as_str() {
+                "third-party" | "3p" => {
+                    rule.options.first_party = negated;
+                    rule.options.third_party = !negated;
+                }
+                "first-party" | "1p" => {
+                    rule.options.third_party = negated;
+                    rule.options.first_party = !negated;
+                }
+                "important" | "match-case" | "collapse" => {}
+                _ => {
+                    if negated {
+                        *rule.options.type_flag(&name).ok_or_else(|| format!("Unsupported filter option: {}", option))? = false;
+                    } else {
+                        *included_types.type_flag(&name).ok_or_else(|| format!("Unsupported filter option: {}", option))? = true;
+                        has_included_type = true;
+                    }
+                }
+            }
+        }
+        
+        if has_included_type {
+            rule.options = FilterOptions {
+                third_party: rule.options.third_party,
+                first_party: rule.options.first_party,
+                ..included_types
+            };
+        }
+        
+        Ok(())
+    }
+}
+
+/// Lowercase alphanumeric runs of a URL.
+fn url_tokens(url: &str) -> impl Iterator<Item = String> + '_ {
+    url.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_ascii_lowercase())
+}
+
+/// The longest alphanumeric run of a pattern that any matching URL must
+/// contain as a whole token: it has to be bounded on both sides by a literal
+/// separator, `^` or an anchor, never by `*` or an open pattern end. Regex
+/// rules and patterns without such a run have no token.
+fn pattern_token(pattern: &str) -> Option<String> {
+    if pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return None;
+    }
+    
+    let bytes = pattern.as_bytes();
+    let mut best: Option<&str> = None;
+    let mut start = 0;
+    
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < bytes.len() && bytes[end].is_ascii_alphanumeric() {
+            end += 1;
+        }
+        
+        let bounded_before = start > 0 && bytes[start - 1] != b'*';
+        let bounded_after = end < bytes.len() && bytes[end] != b'*';
+        let token = &pattern[start..end];
+        let is_common = COMMON_URL_TOKENS.contains(&token.to_ascii_lowercase().as_str());
+        if bounded_before && bounded_after && !is_common && best.is_none_or(|best| token.len() > best.len()) {
+            best = Some(token);
+        }
+        start = end;
+    }
+    
+    best.map(|token| token.to_ascii_lowercase())
+}
+
+/// The longest run of a non-regex pattern that matches itself literally, for
+/// prefiltering. Runs with non-ASCII characters are skipped, since the regex
+/// folds their case in ways ASCII lowercasing does not.
+fn pattern_literal(pattern: &str) -> Option<String> {
+    if pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return None;
+    }
+    
+    pattern.trim_start_matches('|')
+        .trim_end_matches('|')
+        .split(['*', '^'])
+        .filter(|run| run.is_ascii())
+        .max_by_key(|run| run.len())
+        .filter(|run| run.len() >= 3)
+        .map(|run| run.to_ascii_lowercase())
+}
+
+pub fn estimated_resource_bytes(request_type: &str) -> u64 {
+    let average = |name: &str| AVERAGE_RESOURCE_BYTES.iter().find(|(resource, _)| *resource == name).map(|(_, bytes)| *bytes);
+    average(request_type).or_else(|| average("other")).unwrap_or(0)
+}
+
+/// Lowercase alphanumeric runs of `name` joined with dashes, for list ids.
+fn filter_list_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Downloads and parses a remote filter list. The validators from a previous
+/// download make the request conditional; `None` means the server answered
+/// `304 Not Modified` and there is nothing to parse.
+pub async fn fetch_filter_list(url: &str, etag: Option<&str>, last_modified: Option<&str>) -> Result<Option<FilterListDownload>, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Filter lists must be fetched over http or https".to_string());
+    }
+    
+    let client = super::network::http_client().await?;
+    let mut request = client.get(parsed.as_str());
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    
+    let response = super::network::send_request(request, "fetch filter list").await?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Filter list request failed with status {}", response.status()));
+    }
+    
+    let header = |name| response.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    let content = response.text()
+        .await
+        .map_err(|e| format!("Failed to read filter list: {}", e))?;
+    
+    Ok(Some(FilterListDownload {
+        rules: FilterEngine::parse_filter_rules(&content),
+        etag,
+        last_modified,
+    }))
+}
+
+/// Refreshes every remote list, or only the enabled ones. Downloads happen
+/// without holding the engine lock so requests keep being filtered meanwhile.
+pub async fn refresh_filter_lists(enabled_only: bool) -> Vec<FilterListSummary> {
+    let sources = FILTER_ENGINE.read().await.remote_filter_list_sources(enabled_only);
+    
+    let mut results = Vec::new();
+    for (list_id, source) in sources {
+        let result = fetch_filter_list(&source.url, source.etag.as_deref(), source.last_modified.as_deref()).await;
+        results.push((list_id, result));
+    }
+    
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.apply_filter_list_fetches(results);
+    engine.filter_list_summaries()
+}
+
+/// Refreshes enabled lists every `filter_update_interval_hours` while the
+/// privacy setting `auto_update_filter_lists` is on, emitting
+/// `filters://updated` with the resulting list summaries.
+pub fn start_filter_list_updater(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_refresh: Option<Instant> = None;
+        
+        loop {
+            let privacy = super::settings::current_settings().await.privacy;
+            let interval = Duration::from_secs(privacy.filter_update_interval_hours.max(1) as u64 * 60 * 60);
+            
+            if privacy.auto_update_filter_lists && last_refresh.is_none_or(|at| at.elapsed() >= interval) {
+                let summaries = refresh_filter_lists(true).await;
+                last_refresh = Some(Instant::now());
+                let _ = app.emit("filters://updated", summaries);
+            }
+            
+            tokio::time::sleep(FILTER_UPDATE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Hosts are the same site when they share a registrable domain, so
+/// `cdn.example.com` is first party on `www.example.com` but `b.co.uk` is
+/// third party on `a.co.uk`. IP addresses only match themselves.
+fn is_same_site(request_domain: &str, origin_domain: &str) -> bool {
+    if request_domain.parse::<std::net::IpAddr>().is_ok() || origin_domain.parse::<std::net::IpAddr>().is_ok() {
+        return request_domain == origin_domain;
+    }
+    super::lookalike::registrable_domain(request_domain) == super::lookalike::registrable_domain(origin_domain)
+}
+
+/// Scores a page out of 100: plain HTTP costs the most, then fingerprinting
+/// attempts, the number of trackers seen and the share of third-party requests.
+pub fn privacy_score(https: bool, trackers_blocked: u32, third_party_requests: u32, total_requests: u32, fingerprinting_attempts: u32) -> u32 {
+    let mut penalty = 0.0;
+    
+    if !https {
+        penalty += 40.0;
+    }
+    
+    penalty += (trackers_blocked as f64 * 2.0).min(20.0);
+    penalty += (fingerprinting_attempts as f64 * 10.0).min(25.0);
+    
+    if total_requests > 0 {
+        penalty += (third_party_requests as f64 / total_requests as f64) * 15.0;
+    }
+    
+    (100.0 - penalty).max(0.0).round() as u32
+}
+
+pub fn privacy_grade(score: u32) -> &'static str {
+    match score {
+        90..=100 => "A",
+        80..=89 => "B",
+        70..=79 => "C",
+        60..=69 => "D",
+        _ => "F",
+    }
+}
+
+pub async fn blocking_snapshot() -> (GlobalStats, Vec<SiteShields>) {
+    let engine = FILTER_ENGINE.read().await;
+    (engine.global_stats.clone(), engine.site_shields.values().cloned().collect())
+}
+
+/// Counters change on nearly every request, so writes are coalesced: the first
+/// change schedules a save and later ones within the delay ride along with it.
+fn schedule_shield_stats_save() {
+    if SHIELD_STATS_SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(SHIELD_STATS_SAVE_DELAY).await;
+        SHIELD_STATS_SAVE_PENDING.store(false, Ordering::SeqCst);
+        
+        let stats = FILTER_ENGINE.read().await.shield_stats();
+        let _ = storage::save_json(SHIELD_STATS_FILE, &stats);
+    });
+}
+
+#[tauri::command]
+pub async fn get_site_shields(domain: String) -> Result<SiteShields, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.get_site_shields(&domain))
+}
+
+#[tauri::command]
+pub async fn update_site_shields(domain: String, shields: SiteShields) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.update_site_shields(&domain, shields);
+    schedule_shield_stats_save();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_global_stats() -> Result<GlobalStats, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.global_stats.clone())
+}
+
+#[tauri::command]
+pub async fn get_session_stats() -> Result<SessionStats, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.session_stats.clone())
+}
+
+#[tauri::command]
+pub async fn reset_session_stats() -> Result<SessionStats, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.reset_session_stats();
+    Ok(engine.session_stats.clone())
+}
+
+/// Selectors the webview injects as a hiding stylesheet for pages on `domain`.
+#[tauri::command]
+pub async fn get_cosmetic_filters(domain: String) -> Result<Vec<String>, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.get_cosmetic_filters(&domain))
+}
+
+#[tauri::command]
+pub async fn should_block_request(url: String, request_type: String, origin_domain: String) -> Result<bool, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.should_block_request(&url, &request_type, &origin_domain))
+}
+
+/// Called by the webview's request handler before each request is sent.
+#[tauri::command]
+pub async fn intercept_request(url: String, request_type: String, origin_domain: String) -> Result<RequestVerdict, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    let verdict = engine.intercept_request(&url, &request_type, &origin_domain);
+    schedule_shield_stats_save();
+    Ok(verdict)
+}
+
+#[tauri::command]
+pub async fn compute_privacy_score(url: String) -> Result<PrivacyScore, String> {
+    let engine = FILTER_ENGINE.read().await;
+    engine.compute_privacy_score(&url)
+}
+
+#[tauri::command]
+pub async fn record_request(url: String, origin_domain: String) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.record_request(&url, &origin_domain);
+    schedule_shield_stats_save();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn record_blocked_request(domain: String, block_type: String, blocked_url: Option<String>, request_type: Option<String>, content_length: Option<u64>) -> Result<(), String> {
+    let blocked_host = blocked_url.as_deref().and_then(super::network::url_host);
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.increment_blocked_count(&domain, &block_type, blocked_host.as_deref());
+    engine.record_bandwidth_saved(content_length.unwrap_or_else(|| estimated_resource_bytes(request_type.as_deref().unwrap_or("other"))));
+    schedule_shield_stats_save();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_top_blocked_trackers(limit: usize) -> Result<Vec<(String, u64)>, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.top_blocked_trackers(limit))
+}
+
+#[tauri::command]
+pub async fn reset_global_stats() -> Result<GlobalStats, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.reset_global_stats();
+    engine.save_shield_stats()?;
+    Ok(engine.global_stats.clone())
+}
+
+#[tauri::command]
+pub async fn reset_shield_stats() -> Result<GlobalStats, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.reset_stats();
+    engine.save_shield_stats()?;
+    Ok(engine.global_stats.clone())
+}
+
+#[tauri::command]
+pub async fn report_fingerprinting_attempt(domain: String) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.record_fingerprinting_attempt(&domain);
+    schedule_shield_stats_save();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn test_filter_rule(rule: String, sample_urls: Vec<String>) -> Result<Vec<(String, bool)>, String> {
+    FilterEngine::test_rule(&rule, &sample_urls)
+}
+
+#[tauri::command]
+pub async fn deduplicate_filter_rules() -> Result<DeduplicationReport, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    Ok(engine.deduplicate_rules())
+}
+
+#[tauri::command]
+pub async fn count_total_rules() -> Result<usize, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.count_total_rules())
+}
+
+#[tauri::command]
+pub async fn update_filter_lists() -> Result<Vec<FilterListSummary>, String> {
+    Ok(refresh_filter_lists(false).await)
+}
+
+#[tauri::command]
+pub async fn import_filter_list_from_text(name: String, content: String) -> Result<FilterListImport, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    let import = engine.import_filter_list_from_text(&name, &content)?;
+    engine.save_filter_lists()?;
+    Ok(import)
+}
+
+#[tauri::command]
+pub async fn get_filter_list_rules(list_id: String) -> Result<Vec<FilterRule>, String> {
+    let engine = FILTER_ENGINE.read().await;
+    engine.get_filter_list_rules(&list_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download(content: &str) -> FilterListDownload {
+        FilterListDownload {
+            rules: FilterEngine::parse_filter_rules(content),
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        }
+    }
+
+    #[test]
+    fn added_lists_and_enabled_flags_survive_a_restart() {
+        let mut engine = FilterEngine::new();
+        let summary = engine.add_filter_list("https://lists.example.com/extra.txt", download("||ads.example.net^")).unwrap();
+        engine.set_filter_list_enabled("easylist", false).unwrap();
+        engine.set_filter_list_enabled(&summary.id, false).unwrap();
+        let saved = engine.saved_filter_lists();
+        
+        let mut restarted = FilterEngine::new();
+        restarted.restore_filter_lists(saved);
+        
+        let list = &restarted.filter_lists[&summary.id];
+        assert_eq!(list.url, "https://lists.example.com/extra.txt");
+        assert!(!list.enabled);
+        assert!(list.rules.is_empty() && list.etag.is_none());
+        assert!(!restarted.filter_lists["easylist"].enabled);
+        assert!(restarted.filter_lists["easyprivacy"].enabled);
+    }
+
+    #[test]
+    fn imported_lists_survive_a_restart_with_their_rules() {
+        let mut engine = FilterEngine::new();
+        let import = engine.import_filter_list_from_text("My list", "||tracker.example.org^\n##.banner").unwrap();
+        let saved = engine.saved_filter_lists();
+        
+        let mut restarted = FilterEngine::new();
+        restarted.restore_filter_lists(saved);
+        restarted.index_rules();
+        
+        assert_eq!(restarted.filter_lists[&import.list_id].rules.len(), 2);
+        assert!(restarted.should_block_request("https://tracker.example.org/pixel.gif", "image", "example.com"));
+    }
+
+    #[test]
+    fn tracker_matches_come_from_the_list_category_not_its_id() {
+        let mut engine = FilterEngine::new();
+        let summary = engine.add_filter_list("https://lists.example.com/privacy.txt", download("||ads.example.net^")).unwrap();
+        engine.filter_lists.get_mut("easyprivacy").unwrap().rules = FilterEngine::parse_filter_rules("||tracker.example.org^");
+        engine.index_rules();
+        
+        assert!(summary.id.contains("privacy"));
+        assert_eq!(engine.block_reason("https://ads.example.net/a.png", "image", "site.example"), Some("ad"));
+        assert_eq!(engine.block_reason("https://tracker.example.org/t.gif", "image", "site.example"), Some("tracker"));
+    }
+
+    #[test]
+    fn pattern_literals_are_required_plain_runs() {
+        assert_eq!(pattern_literal("||Ads.Example.com^$script").as_deref(), Some("ads.example.com"));
+        assert_eq!(pattern_literal("/banner/*/img^").as_deref(), Some("/banner/"));
+        assert_eq!(pattern_literal("*ad*"), None);
+        assert_eq!(pattern_literal("/ads?[0-9]/"), None);
+    }
+
+    /// 42k rules is roughly EasyList plus EasyPrivacy; about 1% of them have
+    /// no indexable token and are tested against every URL.
+    #[test]
+    #[ignore = "timing test; run with cargo test --release -- --ignored"]
+    fn matching_against_42k_rules_stays_well_under_a_millisecond() {
+        let rules: Vec<String> = (0..42_000u32)
+            .map(|i| match i % 100 {
+                0 => format!("*-ad{}x*", i),
+                1..=50 => format!("||adhost{}.example^", i),
+                51..=70 => format!("||track{}.example/pixel$third-party", i),
+                71..=85 => format!("/banner{}/*", i),
+                86..=90 => format!("@@||cdn{}.example.org^$script", i),
+                _ => format!("||metrics{}.example.io/collect?$image", i),
+            })
+            .collect();
+        let mut engine = FilterEngine::new();
+        engine.import_filter_list_from_text("Benchmark", &rules.join("\n")).unwrap();
+        let urls: Vec<String> = (0..2000)
+            .map(|i| format!("https://www{}.site{}.com/assets/app-{}.js?v={}", i % 13, i, i, i * 7))
+            .chain((0..400).map(|i| format!("https://adhost{}.example/x.png", i * 100 + 3)))
+            .collect();
+        
+        let start = Instant::now();
+        let blocked = urls.iter()
+            .filter(|url| engine.should_block_request(url, "script", "news.example"))
+            .count();
+        let per_url = start.elapsed() / urls.len() as u32;
+        
+        assert_eq!(blocked, 400);
+        assert!(per_url < Duration::from_micros(250), "{:?} per URL", per_url);
+    }
+
+    #[test]
+    fn custom_rules_apply_to_top_level_checks_until_removed() {
+        let mut engine = FilterEngine::new();
+        let url = "https://ads.custom-rule.example/landing";
+        let blocked = |engine: &FilterEngine| engine.should_block_request(url, "document", "ads.custom-rule.example");
+        
+        engine.add_custom_rule("||custom-rule.example^").unwrap();
+        assert!(blocked(&engine));
+        assert!(engine.add_custom_rule(" ||custom-rule.example^ ").is_err());
+        
+        engine.add_custom_rule("@@||ads.custom-rule.example^").unwrap();
+        assert!(!blocked(&engine));
+        
+        engine.remove_custom_rule("@@||ads.custom-rule.example^").unwrap();
+        engine.remove_custom_rule("||custom-rule.example^").unwrap();
+        assert!(!blocked(&engine));
+    }
+
+    #[test]
+    fn fetch_results_keep_rules_unless_a_new_list_arrives() {
+        let mut engine = FilterEngine::new();
+        engine.apply_filter_list_fetches(vec![("easylist".to_string(), Ok(Some(download("||ads.example.net^"))))]);
+        let list = &engine.filter_lists["easylist"];
+        assert_eq!(list.rules.len(), 1);
+        assert_eq!(list.etag.as_deref(), Some("\"v1\""));
+        assert!(engine.should_block_request("https://ads.example.net/a.js", "script", "site.example"));
+        
+        engine.apply_filter_list_fetches(vec![("easylist".to_string(), Err("Request timed out after 15s".to_string()))]);
+        assert_eq!(engine.filter_lists["easylist"].last_update_error.as_deref(), Some("Request timed out after 15s"));
+        
+        engine.apply_filter_list_fetches(vec![("easylist".to_string(), Ok(Some(download(""))))]);
+        assert_eq!(engine.filter_lists["easylist"].last_update_error.as_deref(), Some("No valid filter rules found"));
+        
+        engine.apply_filter_list_fetches(vec![("easylist".to_string(), Ok(None))]);
+        let list = &engine.filter_lists["easylist"];
+        assert!(list.last_update_error.is_none());
+        assert_eq!(list.rules.len(), 1);
+        assert!(engine.should_block_request("https://ads.example.net/a.js", "script", "site.example"));
+    }
+
+    #[test]
+    fn resetting_global_stats_keeps_site_counters() {
+        let mut engine = FilterEngine::new();
+        engine.increment_blocked_count("reset-stats.example", "ad", None);
+        engine.record_bandwidth_saved(1024);
+        engine.record_bandwidth_saved(u64::MAX);
+        assert_eq!(engine.global_stats.bandwidth_saved, u64::MAX);
+        
+        engine.reset_global_stats();
+        
+        assert_eq!(engine.global_stats.total_ads_blocked, 0);
+        assert_eq!(engine.global_stats.bandwidth_saved, 0);
+        assert_eq!(engine.get_site_shields("reset-stats.example").ads_blocked, 1);
+    }
+
+    #[test]
+    fn blocked_requests_save_their_estimated_size() {
+        let mut engine = FilterEngine::new();
+        engine.add_custom_rule("||bandwidth-estimate.example^").unwrap();
+        let before = engine.global_stats.bandwidth_saved;
+        
+        let verdict = engine.intercept_request("https://bandwidth-estimate.example/a.js", "script", "site.example");
+        engine.intercept_request("https://bandwidth-estimate.example/beacon", "ping", "site.example");
+        engine.intercept_request("https://allowed.example/a.js", "script", "site.example");
+        
+        assert!(verdict.blocked);
+        assert_eq!(
+            engine.global_stats.bandwidth_saved - before,
+            estimated_resource_bytes("script") + estimated_resource_bytes("other"),
+        );
+        assert_eq!(estimated_resource_bytes("ping"), estimated_resource_bytes("other"));
+    }
+
+    #[test]
+    fn same_site_compares_registrable_domains() {
+        assert!(is_same_site("cdn.example.com", "www.example.com"));
+        assert!(is_same_site("example.com", "shop.example.com"));
+        assert!(is_same_site("static.bbc.co.uk", "www.bbc.co.uk"));
+        assert!(!is_same_site("tracker.co.uk", "news.co.uk"));
+        assert!(!is_same_site("example.net", "example.com"));
+        assert!(!is_same_site("5.6.3.4", "1.2.3.4"));
+        assert!(is_same_site("1.2.3.4", "1.2.3.4"));
+    }
+
+    #[test]
+    fn rule_options_set_domains_exceptions_and_types() {
+        let rule = FilterEngine::parse_filter_rule("||ads.example.net^$script,third-party,domain=a.com|~b.a.com").unwrap();
+        assert_eq!(rule.pattern, "||ads.example.net^");
+        assert_eq!(rule.rule_type, FilterRuleType::Block);
+        assert_eq!(rule.domains, Some(vec!["a.com".to_string()]));
+        assert_eq!(rule.exceptions, Some(vec!["b.a.com".to_string()]));
+        assert!(rule.options.script && rule.options.third_party);
+        assert!(!rule.options.image && !rule.options.other && !rule.options.first_party);
+        
+        let rule = FilterEngine::parse_filter_rule("||ads.example.net^$~image").unwrap();
+        assert!(!rule.options.image);
+        assert!(rule.options.script && rule.options.other && rule.options.first_party && rule.options.third_party);
+        assert_eq!(rule.domains, None);
+        
+        let rule = FilterEngine::parse_filter_rule("@@||ads.example.net^$domain=a.com").unwrap();
+        assert_eq!(rule.rule_type, FilterRuleType::Allow);
+        assert_eq!(rule.pattern, "||ads.example.net^");
+        assert_eq!(rule.domains, Some(vec!["a.com".to_string()]));
+        assert_eq!(rule.options, FilterOptions::default());
+        
+        assert!(FilterEngine::parse_filter_rule("||ads.example.net^$redirect=noop.js").is_err());
+    }
+
+    #[test]
+    fn cosmetic_filters_apply_generic_domain_and_exception_rules() {
+        let mut engine = FilterEngine::new();
+        for list in engine.filter_lists.values_mut() {
+            list.enabled = false;
+        }
+        engine.import_filter_list_from_text("Cosmetic", "##.ad\nexample.com##.banner\nexample.com,~shop.example.com##.promo\nnews.example.com#@#.ad").unwrap();
+        
+        assert_eq!(engine.get_cosmetic_filters("other.org"), vec![".ad".to_string()]);
+        
+        let mut selectors = engine.get_cosmetic_filters("example.com");
+        selectors.sort();
+        assert_eq!(selectors, vec![".ad", ".banner", ".promo"]);
+        
+        let mut selectors = engine.get_cosmetic_filters("www.example.com");
+        selectors.sort();
+        assert_eq!(selectors, vec![".ad", ".banner", ".promo"]);
+        
+        let mut selectors = engine.get_cosmetic_filters("shop.example.com");
+        selectors.sort();
+        assert_eq!(selectors, vec![".ad", ".banner"]);
+        
+        let mut selectors = engine.get_cosmetic_filters("news.example.com");
+        selectors.sort();
+        assert_eq!(selectors, vec![".banner", ".promo"]);
+    }
+
+    #[test]
+    fn shield_stats_survive_a_save_round_trip() {
+        let mut engine = FilterEngine::new();
+        engine.reset_stats();
+        engine.increment_blocked_count("news.example", "ad", None);
+        engine.increment_blocked_count("news.example", "tracker", Some("Tracker.Example."));
+        engine.increment_blocked_count("shop.example", "script", None);
+        engine.record_bandwidth_saved(2048);
+        
+        let json = serde_json::to_string(&engine.shield_stats()).unwrap();
+        let reloaded: ShieldStats = serde_json::from_str(&json).unwrap();
+        
+        assert_eq!(reloaded.global_stats.total_ads_blocked, 1);
+        assert_eq!(reloaded.global_stats.total_trackers_blocked, 1);
+        assert_eq!(reloaded.global_stats.total_scripts_blocked, 1);
+        assert_eq!(reloaded.global_stats.bandwidth_saved, 2048);
+        assert_eq!(reloaded.site_shields["news.example"].ads_blocked, 1);
+        assert_eq!(reloaded.site_shields["news.example"].trackers_blocked, 1);
+        assert_eq!(reloaded.site_shields["shop.example"].scripts_blocked, 1);
+        assert_eq!(reloaded.blocked_tracker_hosts["tracker.example"], 1);
+        assert_eq!(reloaded.global_stats.last_reset, engine.global_stats.last_reset);
+    }
+
+    #[test]
+    fn privacy_score_penalties_and_grades() {
+        assert_eq!(privacy_score(true, 0, 0, 0, 0), 100);
+        assert_eq!(privacy_score(false, 0, 0, 0, 0), 60);
+        assert_eq!(privacy_score(true, 3, 0, 10, 0), 94);
+        assert_eq!(privacy_score(true, 50, 0, 10, 0), 80);
+        assert_eq!(privacy_score(true, 0, 5, 10, 1), 83);
+        assert_eq!(privacy_score(false, 50, 10, 10, 9), 0);
+        
+        assert_eq!(privacy_grade(100), "A");
+        assert_eq!(privacy_grade(90), "A");
+        assert_eq!(privacy_grade(89), "B");
+        assert_eq!(privacy_grade(70), "C");
+        assert_eq!(privacy_grade(60), "D");
+        assert_eq!(privacy_grade(59), "F");
+        
+        let mut engine = FilterEngine::new();
+        engine.site_shields.remove("score.example");
+        engine.record_fingerprinting_attempt("score.example");
+        let score = engine.compute_privacy_score("http://score.example/page").unwrap();
+        assert_eq!(score.score, 50);
+        assert_eq!(score.grade, "F");
+        assert!(!score.https);
+        assert_eq!(score.fingerprinting_attempts, 1);
+    }
+
+    #[test]
+    fn test_rule_reports_matches_and_parse_errors() {
+        let samples = vec![
+            "https://ads.example.com/banner.js".to_string(),
+            "https://cdn.ads.example.com/pixel.gif".to_string(),
+            "https://example.com/ads.example.com".to_string(),
+        ];
+        
+        let results = FilterEngine::test_rule("||ads.example.com^", &samples).unwrap();
+        
+        assert_eq!(results, vec![
+            (samples[0].clone(), true),
+            (samples[1].clone(), true),
+            (samples[2].clone(), false),
+        ]);
+        assert!(!FilterEngine::test_rule("||ads.example.com^$third-party", &samples).unwrap()[0].1);
+        assert!(FilterEngine::test_rule("/ads(/", &samples).is_err());
+        assert!(FilterEngine::test_rule("##.banner", &samples).is_err());
+    }
+
+    #[test]
+    fn deduplication_keeps_exceptions_and_counts_removals() {
+        let mut engine = FilterEngine::new();
+        for list in engine.filter_lists.values_mut() {
+            list.enabled = false;
+        }
+        let first = engine.import_filter_list_from_text("A list", "||ads.example^\n@@||ads.example^$domain=shop.example").unwrap();
+        let second = engine.import_filter_list_from_text("B list", "||ads.example^\n@@||ads.example^$domain=shop.example\n@@||ads.example^\n||other.example^").unwrap();
+        
+        let report = engine.deduplicate_rules();
+        
+        assert_eq!(report.removed, 2);
+        assert_eq!(report.remaining, 4);
+        assert!(report.duplicates.iter().all(|duplicate| duplicate.kept_in == first.list_id && duplicate.removed_from == second.list_id));
+        let remaining = &engine.filter_lists[&second.list_id].rules;
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].rule_type, FilterRuleType::Allow);
+        assert_eq!(remaining[0].pattern, "||ads.example^");
+        assert_eq!(engine.filter_lists[&first.list_id].rules.len(), 2);
+    }
 }
\ No newline at end of file
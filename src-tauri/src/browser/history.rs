@@ -1,8 +1,35 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use rusqlite::{params, params_from_iter, Connection};
 use uuid::Uuid;
-use tokio::sync::RwLock;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
 use once_cell::sync::Lazy;
+use super::storage;
+
+const CLEAR_PREVIEW_SAMPLES: usize = 5;
+const HISTORY_DB_FILE: &str = "history.sqlite3";
+/// A history export dropped into the profile; imported when the database is first created.
+const HISTORY_JSON_FILE: &str = "history.json";
+/// `user_version` of a database that has had `history.json` imported.
+const JSON_MIGRATED_VERSION: i64 = 1;
+const ENTRY_COLUMNS: &str = "id, url, title, visit_time, visit_count, last_visit, favicon, is_private";
+
+/// Timestamps are stored as milliseconds since the epoch so range queries and
+/// ordering can use the indexes.
+const HISTORY_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS history (
+        id TEXT PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        visit_time INTEGER NOT NULL,
+        visit_count INTEGER NOT NULL,
+        last_visit INTEGER NOT NULL,
+        favicon TEXT,
+        is_private INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS history_last_visit ON history (last_visit);
+    CREATE INDEX IF NOT EXISTS history_visit_count ON history (visit_count);
+";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
@@ -25,198 +52,481 @@ pub struct HistoryStats {
     pub this_month_visits: u64,
 }
 
-static HISTORY_MANAGER: Lazy<RwLock<HistoryManager>> = Lazy::new(|| {
-    RwLock::new(HistoryManager::new())
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowsingSession {
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    pub end_time: chrono::DateTime<chrono::Utc>,
+    pub urls: Vec<String>,
+}
+
+/// All history for one site, as shown when history is grouped by domain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainHistory {
+    pub domain: String,
+    pub visit_count: u64,
+    pub last_visit: chrono::DateTime<chrono::Utc>,
+    /// Favicon of the most recently visited page that has one.
+    pub favicon: Option<String>,
+}
+
+/// What `clear_history` would remove for the same time range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearPreview {
+    pub count: usize,
+    pub sample_urls: Vec<String>,
+    pub earliest: Option<chrono::DateTime<chrono::Utc>>,
+    pub latest: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Holds why the database could not be opened instead of a manager, so the
+/// history commands report it rather than silently losing visits.
+static HISTORY_MANAGER: Lazy<Mutex<Result<HistoryManager, String>>> = Lazy::new(|| {
+    Mutex::new(HistoryManager::open())
 });
 
+async fn history_manager() -> Result<MappedMutexGuard<'static, HistoryManager>, String> {
+    MutexGuard::try_map(HISTORY_MANAGER.lock().await, |manager| manager.as_mut().ok())
+        .map_err(|manager| manager.as_ref().err().cloned().unwrap_or_default())
+}
+
+/// History backed by a SQLite database in the profile directory. The
+/// connection is not `Sync`, so the manager sits behind a `Mutex` rather
+/// than the `RwLock` other managers use.
 pub struct HistoryManager {
-    pub entries: HashMap<String, HistoryEntry>,
-    pub url_to_id: HashMap<String, String>,
+    conn: Connection,
+    /// Off while the user has paused history; visits are then dropped. Not persisted.
+    recording_enabled: bool,
 }
 
-impl HistoryManager {
-    pub fn new() -> Self {
-        Self {
-            entries: HashMap::new(),
-            url_to_id: HashMap::new(),
-        }
+fn db_error(e: rusqlite::Error) -> String {
+    format!("History database error: {}", e)
+}
+
+fn from_millis(millis: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+/// Start of a clearing range ending now, in milliseconds.
+fn range_cutoff(duration: chrono::Duration) -> Result<i64, String> {
+    chrono::Utc::now()
+        .checked_sub_signed(duration)
+        .map(|cutoff| cutoff.timestamp_millis())
+        .ok_or_else(|| "Time range is too large".to_string())
+}
+
+/// Validates the `hours` argument of the clearing commands.
+fn clear_range_hours(hours: Option<i64>) -> Result<Option<chrono::Duration>, String> {
+    let Some(hours) = hours else {
+        return Ok(None);
+    };
+    if hours <= 0 {
+        return Err("Hours must be a positive number".to_string());
     }
+    chrono::TimeDelta::try_hours(hours)
+        .map(Some)
+        .ok_or_else(|| "Time range is too large".to_string())
+}
 
-    pub fn add_visit(&mut self, url: &str, title: &str, is_private: bool) -> String {
-        if is_private {
-            return String::new();
-        }
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        visit_time: from_millis(row.get(3)?),
+        visit_count: row.get(4)?,
+        last_visit: from_millis(row.get(5)?),
+        favicon: row.get(6)?,
+        is_private: row.get(7)?,
+    })
+}
 
-        let now = chrono::Utc::now();
+/// A `LIKE` pattern matching `text` anywhere, with wildcards in it escaped.
+fn like_contains(text: &str) -> String {
+    format!("%{}%", escape_like(text))
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Runs `<verb> INTO history` for each entry, e.g. with `INSERT OR IGNORE`.
+fn insert_rows(transaction: &rusqlite::Transaction, verb: &str, entries: &[HistoryEntry]) -> Result<(), String> {
+    let mut statement = transaction
+        .prepare(&format!("{} INTO history ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", verb, ENTRY_COLUMNS))
+        .map_err(db_error)?;
+    for entry in entries {
+        statement.execute(params![
+            entry.id,
+            entry.url,
+            entry.title,
+            entry.visit_time.timestamp_millis(),
+            entry.visit_count,
+            entry.last_visit.timestamp_millis(),
+            entry.favicon,
+            entry.is_private,
+        ]).map_err(db_error)?;
+    }
+    Ok(())
+}
+
+impl HistoryManager {
+    /// Opens the profile's history database, importing `history.json` into it
+    /// if that hasn't succeeded yet.
+    pub fn open() -> Result<Self, String> {
+        let dir = storage::profile_dir();
         
-        if let Some(entry_id) = self.url_to_id.get(url) {
-            if let Some(entry) = self.entries.get_mut(entry_id) {
-                entry.visit_count += 1;
-                entry.last_visit = now;
-                entry.title = title.to_string();
-                return entry_id.clone();
-            }
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create profile directory: {}", e))
+            .and_then(|_| Connection::open(dir.join(HISTORY_DB_FILE)).map_err(db_error))
+            .and_then(Self::with_connection)
+            .and_then(|mut manager| manager.migrate_json_history(&dir.join(HISTORY_JSON_FILE)).map(|_| manager))
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Connection::open_in_memory()
+            .map_err(db_error)
+            .and_then(Self::with_connection)
+            .expect("in-memory history database")
+    }
+
+    pub fn with_connection(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(HISTORY_SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            conn,
+            recording_enabled: true,
+        })
+    }
+
+    /// Runs `SELECT <entry columns> FROM history <clauses>`.
+    fn query_entries<P: rusqlite::Params>(&self, clauses: &str, params: P) -> Result<Vec<HistoryEntry>, String> {
+        let mut statement = self.conn
+            .prepare(&format!("SELECT {} FROM history {}", ENTRY_COLUMNS, clauses))
+            .map_err(db_error)?;
+        let entries = statement.query_map(params, entry_from_row)
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+        Ok(entries)
+    }
+
+    /// Inserts entries as they are, replacing any with the same id or URL.
+    fn insert_entries(&mut self, entries: &[HistoryEntry]) -> Result<(), String> {
+        let transaction = self.conn.transaction().map_err(db_error)?;
+        insert_rows(&transaction, "INSERT OR REPLACE", entries)?;
+        transaction.commit().map_err(db_error)
+    }
+
+    /// Imports `history.json` unless `user_version` records that it already
+    /// was. The rows and the version bump commit together, so a failed import
+    /// is retried on the next launch. Existing rows are kept, so a retry never
+    /// rolls back visits recorded since. A missing file has nothing to import;
+    /// one that can't be read or parsed is an error rather than skipped.
+    fn migrate_json_history(&mut self, json_path: &std::path::Path) -> Result<(), String> {
+        let version: i64 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(db_error)?;
+        if version >= JSON_MIGRATED_VERSION {
+            return Ok(());
         }
         
-        let entry_id = Uuid::new_v4().to_string();
-        let entry = HistoryEntry {
-            id: entry_id.clone(),
-            url: url.to_string(),
-            title: title.to_string(),
-            visit_time: now,
-            visit_count: 1,
-            last_visit: now,
-            favicon: None,
-            is_private,
+        let entries: Vec<HistoryEntry> = match std::fs::read_to_string(json_path) {
+            Ok(data) => serde_json::from_str(&data)
+                .map_err(|e| format!("Failed to parse {}: {}", HISTORY_JSON_FILE, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("Failed to read {}: {}", HISTORY_JSON_FILE, e)),
         };
+        let transaction = self.conn.transaction().map_err(db_error)?;
+        insert_rows(&transaction, "INSERT OR IGNORE", &entries)?;
+        transaction.pragma_update(None, "user_version", JSON_MIGRATED_VERSION).map_err(db_error)?;
+        transaction.commit().map_err(db_error)
+    }
+
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.recording_enabled = enabled;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording_enabled
+    }
+
+    pub fn add_visit(&mut self, url: &str, title: &str, is_private: bool) -> Result<String, String> {
+        if is_private || !self.recording_enabled {
+            return Ok(String::new());
+        }
         
-        self.entries.insert(entry_id.clone(), entry);
-        self.url_to_id.insert(url.to_string(), entry_id.clone());
-        
-        entry_id
+        let now = chrono::Utc::now().timestamp_millis();
+        self.conn.query_row(
+            "INSERT INTO history (id, url, title, visit_time, visit_count, last_visit, favicon, is_private)
+             VALUES (?1, ?2, ?3, ?4, 1, ?4, NULL, 0)
+             ON CONFLICT (url) DO UPDATE SET
+                 visit_count = visit_count + 1,
+                 last_visit = excluded.last_visit,
+                 title = excluded.title
+             RETURNING id",
+            params![Uuid::new_v4().to_string(), url, title, now],
+            |row| row.get(0),
+        ).map_err(db_error)
     }
 
     pub fn remove_entry(&mut self, entry_id: &str) -> Result<(), String> {
-        if let Some(entry) = self.entries.remove(entry_id) {
-            self.url_to_id.remove(&entry.url);
-            Ok(())
-        } else {
-            Err("Entry not found".to_string())
+        let removed = self.conn.execute("DELETE FROM history WHERE id = ?1", params![entry_id])
+            .map_err(db_error)?;
+        if removed == 0 {
+            return Err("Entry not found".to_string());
         }
+        Ok(())
     }
 
-    pub fn clear_history(&mut self, time_range: Option<chrono::Duration>) {
-        if let Some(duration) = time_range {
-            let cutoff = chrono::Utc::now() - duration;
-            let entries_to_remove: Vec<String> = self.entries
-                .iter()
-                .filter(|(_, entry)| entry.last_visit >= cutoff)
-                .map(|(id, _)| id.clone())
-                .collect();
-            
-            for entry_id in entries_to_remove {
-                if let Some(entry) = self.entries.remove(&entry_id) {
-                    self.url_to_id.remove(&entry.url);
-                }
+    /// Entries last visited within `time_range`, or every entry when it is
+    /// `None`, most recent first.
+    fn entries_in_range(&self, time_range: Option<chrono::Duration>) -> Result<Vec<HistoryEntry>, String> {
+        match time_range {
+            Some(duration) => {
+                let cutoff = range_cutoff(duration)?;
+                self.query_entries("WHERE last_visit >= ?1 ORDER BY last_visit DESC", params![cutoff])
             }
-        } else {
-            self.entries.clear();
-            self.url_to_id.clear();
+            None => self.query_entries("ORDER BY last_visit DESC", []),
         }
     }
 
-    pub fn search_history(&self, query: &str, limit: Option<usize>) -> Vec<&HistoryEntry> {
-        let query = query.to_lowercase();
-        let mut results: Vec<&HistoryEntry> = self.entries
-            .values()
-            .filter(|entry| {
-                entry.title.to_lowercase().contains(&query) ||
-                entry.url.to_lowercase().contains(&query)
+    pub fn preview_clear_history(&self, time_range: Option<chrono::Duration>) -> Result<ClearPreview, String> {
+        let entries = self.entries_in_range(time_range)?;
+        
+        Ok(ClearPreview {
+            count: entries.len(),
+            sample_urls: entries.iter()
+                .take(CLEAR_PREVIEW_SAMPLES)
+                .map(|entry| entry.url.clone())
+                .collect(),
+            earliest: entries.last().map(|entry| entry.last_visit),
+            latest: entries.first().map(|entry| entry.last_visit),
+        })
+    }
+
+    /// Deletes the entries last visited within the past `time_range`, so
+    /// "clear the last hour" removes recent browsing and keeps older history.
+    /// `None` deletes everything.
+    pub fn clear_history(&mut self, time_range: Option<chrono::Duration>) -> Result<(), String> {
+        match time_range {
+            Some(duration) => {
+                let cutoff = range_cutoff(duration)?;
+                self.conn.execute("DELETE FROM history WHERE last_visit >= ?1", params![cutoff])
+            }
+            None => self.conn.execute("DELETE FROM history", []),
+        }.map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Entries matching every whitespace-separated term in their title or URL,
+    /// ranked by how well the terms match weighted by `frecency`. SQL narrows
+    /// the candidates; ranking happens here.
+    pub fn search_history(&self, query: &str, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+        let terms: Vec<String> = query.split_whitespace().map(|term| term.to_lowercase()).collect();
+        let now = chrono::Utc::now();
+        
+        // SQLite's lower() and LIKE only fold ASCII, so other terms are left
+        // to search_match_score.
+        let sql_terms: Vec<&String> = terms.iter().filter(|term| term.is_ascii()).collect();
+        let conditions: Vec<String> = (1..=sql_terms.len())
+            .map(|n| format!("(lower(title) LIKE ?{n} ESCAPE '\\' OR lower(url) LIKE ?{n} ESCAPE '\\')"))
+            .collect();
+        let clauses = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let candidates = self.query_entries(&clauses, params_from_iter(sql_terms.iter().map(|term| like_contains(term))))?;
+        
+        let mut results: Vec<(HistoryEntry, f64)> = candidates
+            .into_iter()
+            .filter_map(|entry| {
+                let score = search_match_score(&entry, &terms)? * frecency(&entry, now);
+                Some((entry, score))
             })
             .collect();
         
-        results.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
+        results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.0.last_visit.cmp(&a.0.last_visit))
+        });
         
         if let Some(limit) = limit {
             results.truncate(limit);
         }
         
-        results
+        Ok(results.into_iter().map(|(entry, _)| entry).collect())
     }
 
-    pub fn get_recent_history(&self, limit: usize) -> Vec<&HistoryEntry> {
-        let mut entries: Vec<&HistoryEntry> = self.entries.values().collect();
-        entries.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
-        entries.truncate(limit);
-        entries
+    pub fn get_recent_history(&self, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        self.query_entries("ORDER BY last_visit DESC LIMIT ?1", params![limit as i64])
     }
 
-    pub fn get_most_visited(&self, limit: usize) -> Vec<&HistoryEntry> {
-        let mut entries: Vec<&HistoryEntry> = self.entries.values().collect();
-        entries.sort_by(|a, b| b.visit_count.cmp(&a.visit_count));
-        entries.truncate(limit);
-        entries
+    pub fn get_most_visited(&self, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        self.query_entries("ORDER BY visit_count DESC LIMIT ?1", params![limit as i64])
     }
 
-    pub fn get_history_by_date(&self, date: chrono::NaiveDate) -> Vec<&HistoryEntry> {
+    pub fn get_history_by_date(&self, date: chrono::NaiveDate) -> Result<Vec<HistoryEntry>, String> {
         let start_of_day = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let end_of_day = date.and_hms_opt(23, 59, 59).unwrap().and_utc();
+        let end_of_day = start_of_day + chrono::Duration::days(1);
+        
+        self.query_entries(
+            "WHERE last_visit >= ?1 AND last_visit < ?2 ORDER BY last_visit DESC",
+            params![start_of_day.timestamp_millis(), end_of_day.timestamp_millis()],
+        )
+    }
+
+    /// One page of the entries last visited between `start` and `end`
+    /// inclusive, most recent first, with the total number in the range.
+    pub fn get_history_range(&self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>, offset: usize, limit: usize) -> Result<(Vec<HistoryEntry>, usize), String> {
+        if start > end {
+            return Err("Range start must not be after its end".to_string());
+        }
         
-        let mut entries: Vec<&HistoryEntry> = self.entries
-            .values()
-            .filter(|entry| entry.last_visit >= start_of_day && entry.last_visit <= end_of_day)
+        let range = params![start.timestamp_millis(), end.timestamp_millis()];
+        let total: i64 = self.conn
+            .query_row("SELECT COUNT(*) FROM history WHERE last_visit BETWEEN ?1 AND ?2", range, |row| row.get(0))
+            .map_err(db_error)?;
+        let entries = self.query_entries(
+            "WHERE last_visit BETWEEN ?1 AND ?2 ORDER BY last_visit DESC, id LIMIT ?3 OFFSET ?4",
+            params![start.timestamp_millis(), end.timestamp_millis(), limit as i64, offset as i64],
+        )?;
+        
+        Ok((entries, total as usize))
+    }
+
+    /// Entries grouped by registrable domain, most visited first.
+    pub fn get_history_by_domain(&self) -> Result<Vec<DomainHistory>, String> {
+        let mut groups: Vec<DomainHistory> = Vec::new();
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        
+        for entry in self.all_entries()? {
+            let Some(domain) = history_domain(&entry.url) else {
+                continue;
+            };
+            
+            let index = *group_index.entry(domain.clone()).or_insert_with(|| {
+                groups.push(DomainHistory {
+                    domain,
+                    visit_count: 0,
+                    last_visit: entry.last_visit,
+                    favicon: None,
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[index];
+            group.visit_count += entry.visit_count as u64;
+            if group.favicon.is_none() {
+                group.favicon = entry.favicon;
+            }
+        }
+        
+        groups.sort_by(|a, b| b.visit_count.cmp(&a.visit_count).then_with(|| b.last_visit.cmp(&a.last_visit)));
+        Ok(groups)
+    }
+
+    /// Deletes every entry on `domain` or its subdomains, returning how many were removed.
+    pub fn clear_history_for_domain(&mut self, domain: &str) -> Result<usize, String> {
+        let ids: Vec<String> = self.all_entries()?
+            .into_iter()
+            .filter(|entry| super::network::url_host(&entry.url).is_some_and(|host| super::network::host_matches_domain(&host, domain)))
+            .map(|entry| entry.id)
             .collect();
         
-        entries.sort_by(|a, b| b.last_visit.cmp(&a.last_visit));
-        entries
+        let transaction = self.conn.transaction().map_err(db_error)?;
+        for id in &ids {
+            transaction.execute("DELETE FROM history WHERE id = ?1", params![id]).map_err(db_error)?;
+        }
+        transaction.commit().map_err(db_error)?;
+        
+        Ok(ids.len())
+    }
+
+    pub fn all_entries(&self) -> Result<Vec<HistoryEntry>, String> {
+        self.query_entries("ORDER BY last_visit DESC", [])
     }
 
-    pub fn get_stats(&self) -> HistoryStats {
+    pub fn get_stats(&self) -> Result<HistoryStats, String> {
         let now = chrono::Utc::now();
-        let today = now.date_naive();
+        let today = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
         let week_ago = now - chrono::Duration::days(7);
         let month_ago = now - chrono::Duration::days(30);
         
-        let total_visits = self.entries.values().map(|e| e.visit_count as u64).sum();
-        let unique_sites = self.entries.len() as u64;
-        
-        let today_visits = self.entries
-            .values()
-            .filter(|entry| entry.last_visit.date_naive() == today)
-            .map(|e| e.visit_count as u64)
-            .sum();
-        
-        let this_week_visits = self.entries
-            .values()
-            .filter(|entry| entry.last_visit >= week_ago)
-            .map(|e| e.visit_count as u64)
-            .sum();
-        
-        let this_month_visits = self.entries
-            .values()
-            .filter(|entry| entry.last_visit >= month_ago)
-            .map(|e| e.visit_count as u64)
-            .sum();
-        
-        HistoryStats {
-            total_visits,
-            unique_sites,
-            today_visits,
-            this_week_visits,
-            this_month_visits,
-        }
+        self.conn.query_row(
+            "SELECT
+                 COALESCE(SUM(visit_count), 0),
+                 COUNT(*),
+                 COALESCE(SUM(CASE WHEN last_visit >= ?1 THEN visit_count END), 0),
+                 COALESCE(SUM(CASE WHEN last_visit >= ?2 THEN visit_count END), 0),
+                 COALESCE(SUM(CASE WHEN last_visit >= ?3 THEN visit_count END), 0)
+             FROM history",
+            params![today.timestamp_millis(), week_ago.timestamp_millis(), month_ago.timestamp_millis()],
+            |row| Ok(HistoryStats {
+                total_visits: row.get::<_, i64>(0)? as u64,
+                unique_sites: row.get::<_, i64>(1)? as u64,
+                today_visits: row.get::<_, i64>(2)? as u64,
+                this_week_visits: row.get::<_, i64>(3)? as u64,
+                this_month_visits: row.get::<_, i64>(4)? as u64,
+            }),
+        ).map_err(db_error)
     }
 
-    pub fn get_suggestions(&self, partial_url: &str, limit: usize) -> Vec<&HistoryEntry> {
+    pub fn get_suggestions(&self, partial_url: &str, limit: usize) -> Result<Vec<HistoryEntry>, String> {
         let partial_url = partial_url.to_lowercase();
-        let mut suggestions: Vec<&HistoryEntry> = self.entries
-            .values()
-            .filter(|entry| {
-                entry.url.to_lowercase().starts_with(&partial_url) ||
-                entry.title.to_lowercase().contains(&partial_url)
-            })
-            .collect();
+        let mut suggestions = if partial_url.is_ascii() {
+            self.query_entries(
+                "WHERE lower(url) LIKE ?1 ESCAPE '\\' OR lower(title) LIKE ?2 ESCAPE '\\'",
+                params![format!("{}%", escape_like(&partial_url)), like_contains(&partial_url)],
+            )?
+        } else {
+            // SQLite only folds ASCII case, so match non-ASCII input here.
+            self.all_entries()?
+                .into_iter()
+                .filter(|entry| entry.url.to_lowercase().starts_with(&partial_url) || entry.title.to_lowercase().contains(&partial_url))
+                .collect()
+        };
         
+        let now = chrono::Utc::now();
         suggestions.sort_by(|a, b| {
-            let a_score = a.visit_count as f64 * (1.0 / (chrono::Utc::now() - a.last_visit).num_hours() as f64).max(0.01);
-            let b_score = b.visit_count as f64 * (1.0 / (chrono::Utc::now() - b.last_visit).num_hours() as f64).max(0.01);
-            b_score.partial_cmp(&a_score).unwrap_or(std::cmp::Ordering::Equal)
+            frecency(b, now).partial_cmp(&frecency(a, now)).unwrap_or(std::cmp::Ordering::Equal)
         });
         
         suggestions.truncate(limit);
-        suggestions
+        Ok(suggestions)
     }
 
-    pub fn update_favicon(&mut self, url: &str, favicon: &str) {
-        if let Some(entry_id) = self.url_to_id.get(url) {
-            if let Some(entry) = self.entries.get_mut(entry_id) {
-                entry.favicon = Some(favicon.to_string());
+    /// Clusters visits into sessions separated by idle gaps longer than `gap`.
+    /// Sessions are returned most recent first, with URLs in visit order.
+    pub fn get_browsing_sessions(&self, gap: chrono::Duration) -> Result<Vec<BrowsingSession>, String> {
+        let entries = self.query_entries("WHERE is_private = 0 ORDER BY last_visit ASC", [])?;
+        
+        let mut sessions: Vec<BrowsingSession> = Vec::new();
+        for entry in entries {
+            match sessions.last_mut() {
+                Some(session) if entry.last_visit - session.end_time <= gap => {
+                    session.end_time = entry.last_visit;
+                    session.urls.push(entry.url);
+                }
+                _ => sessions.push(BrowsingSession {
+                    start_time: entry.last_visit,
+                    end_time: entry.last_visit,
+                    urls: vec![entry.url],
+                }),
             }
         }
+        
+        sessions.reverse();
+        Ok(sessions)
+    }
+
+    pub fn update_favicon(&mut self, url: &str, favicon: &str) -> Result<(), String> {
+        self.conn.execute("UPDATE history SET favicon = ?1 WHERE url = ?2", params![favicon, url])
+            .map_err(db_error)?;
+        Ok(())
     }
 
     pub fn export_history(&self) -> Result<String, String> {
-        let entries: Vec<&HistoryEntry> = self.entries.values().collect();
+        let entries = self.all_entries()?;
         serde_json::to_string_pretty(&entries)
             .map_err(|e| format!("Failed to export history: {}", e))
     }
@@ -225,88 +535,535 @@ impl HistoryManager {
         let imported_entries: Vec<HistoryEntry> = serde_json::from_str(data)
             .map_err(|e| format!("Failed to parse history data: {}", e))?;
         
-        for entry in imported_entries {
-            self.url_to_id.insert(entry.url.clone(), entry.id.clone());
-            self.entries.insert(entry.id.clone(), entry);
-        }
-        
-        Ok(())
+        self.insert_entries(&imported_entries)
+    }
+}
+
+/// The registrable domain of `url`, or the bare address for IP hosts.
+fn history_domain(url: &str) -> Option<String> {
+    match url::Url::parse(url).ok()?.host()? {
+        url::Host::Domain(host) => Some(super::lookalike::registrable_domain(host)),
+        host => Some(host.to_string()),
     }
 }
 
+/// Visit count weighted by recency: full weight within the first hour, then
+/// inversely proportional to the hours since the last visit, with a floor
+/// so frequently used old pages still count.
+fn frecency(entry: &HistoryEntry, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    let hours = (now - entry.last_visit).num_hours().max(0) as f64;
+    entry.visit_count as f64 * (1.0 / (hours + 1.0)).max(0.01)
+}
+
+/// Sums each term's best match: in the title (more if a title word starts
+/// with it), in the host, or elsewhere in the URL. `None` if any term is
+/// missing; with no terms every entry matches equally.
+fn search_match_score(entry: &HistoryEntry, terms: &[String]) -> Option<f64> {
+    if terms.is_empty() {
+        return Some(1.0);
+    }
+    
+    let title = entry.title.to_lowercase();
+    let url = entry.url.to_lowercase();
+    let host = super::network::url_host(&entry.url).unwrap_or_default();
+    
+    terms.iter()
+        .map(|term| {
+            if title.split_whitespace().any(|word| word.starts_with(term.as_str())) {
+                Some(3.0)
+            } else if title.contains(term.as_str()) {
+                Some(2.0)
+            } else if host.contains(term.as_str()) {
+                Some(1.5)
+            } else if url.contains(term.as_str()) {
+                Some(1.0)
+            } else {
+                None
+            }
+        })
+        .sum()
+}
+
+/// Closes the history database while `task` runs, then reopens it from the
+/// current profile directory. Used around moving the profile, so no write
+/// lands in the old file and the open file doesn't block the move.
+pub async fn with_history_closed<T>(task: impl std::future::Future<Output = T>) -> T {
+    let mut manager = HISTORY_MANAGER.lock().await;
+    let recording_enabled = manager.as_ref().map_or(true, |manager| manager.recording_enabled);
+    *manager = Err("History database is being moved".to_string());
+    
+    let result = task.await;
+    
+    *manager = HistoryManager::open().map(|mut reopened| {
+        reopened.recording_enabled = recording_enabled;
+        reopened
+    });
+    result
+}
+
+pub async fn most_visited_urls(limit: usize) -> Vec<String> {
+    let Ok(manager) = history_manager().await else {
+        return Vec::new();
+    };
+    manager.get_most_visited(limit)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| !entry.is_private)
+        .map(|entry| entry.url.clone())
+        .collect()
+}
+
+pub async fn history_snapshot() -> Vec<HistoryEntry> {
+    let Ok(manager) = history_manager().await else {
+        return Vec::new();
+    };
+    manager.all_entries().unwrap_or_default()
+}
+
+/// Adds the visit unless it is on a sensitive domain. Returns an empty id
+/// when nothing was recorded.
+fn record_visit(manager: &mut HistoryManager, privacy: &super::settings::PrivacySettings, url: &str, title: &str, is_private: bool) -> Result<String, String> {
+    if privacy.is_sensitive_url(url) {
+        return Ok(String::new());
+    }
+    manager.add_visit(url, title, is_private)
+}
+
 #[tauri::command]
 pub async fn add_history_visit(url: String, title: String, is_private: bool) -> Result<String, String> {
-    let mut manager = HISTORY_MANAGER.write().await;
-    Ok(manager.add_visit(&url, &title, is_private))
+    let privacy = super::settings::current_settings().await.privacy;
+    let entry_id = record_visit(&mut *history_manager().await?, &privacy, &url, &title, is_private)?;
+    
+    if !entry_id.is_empty() {
+        super::bookmarks::record_visit(&url).await;
+    }
+    
+    Ok(entry_id)
+}
+
+/// Pauses or resumes history recording for every window until changed or the app restarts.
+#[tauri::command]
+pub async fn set_history_recording(enabled: bool) -> Result<(), String> {
+    let mut manager = history_manager().await?;
+    manager.set_recording(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_history_recording() -> Result<bool, String> {
+    let manager = history_manager().await?;
+    Ok(manager.is_recording())
 }
 
 #[tauri::command]
 pub async fn remove_history_entry(entry_id: String) -> Result<(), String> {
-    let mut manager = HISTORY_MANAGER.write().await;
+    let mut manager = history_manager().await?;
     manager.remove_entry(&entry_id)
 }
 
+/// Clears the last `hours` of history, or all of it when `hours` is omitted.
 #[tauri::command]
 pub async fn clear_history(hours: Option<i64>) -> Result<(), String> {
-    let mut manager = HISTORY_MANAGER.write().await;
-    let duration = hours.map(|h| chrono::Duration::hours(h));
-    manager.clear_history(duration);
-    Ok(())
+    let duration = clear_range_hours(hours)?;
+    let mut manager = history_manager().await?;
+    manager.clear_history(duration)
+}
+
+#[tauri::command]
+pub async fn preview_clear_history(hours: Option<i64>) -> Result<ClearPreview, String> {
+    let duration = clear_range_hours(hours)?;
+    let manager = history_manager().await?;
+    manager.preview_clear_history(duration)
 }
 
 #[tauri::command]
 pub async fn search_history(query: String, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
-    let manager = HISTORY_MANAGER.read().await;
-    Ok(manager.search_history(&query, limit).into_iter().cloned().collect())
+    let manager = history_manager().await?;
+    manager.search_history(&query, limit)
 }
 
 #[tauri::command]
 pub async fn get_recent_history(limit: usize) -> Result<Vec<HistoryEntry>, String> {
-    let manager = HISTORY_MANAGER.read().await;
-    Ok(manager.get_recent_history(limit).into_iter().cloned().collect())
+    let manager = history_manager().await?;
+    manager.get_recent_history(limit)
 }
 
 #[tauri::command]
 pub async fn get_most_visited(limit: usize) -> Result<Vec<HistoryEntry>, String> {
-    let manager = HISTORY_MANAGER.read().await;
-    Ok(manager.get_most_visited(limit).into_iter().cloned().collect())
+    let manager = history_manager().await?;
+    manager.get_most_visited(limit)
 }
 
 #[tauri::command]
 pub async fn get_history_by_date(date: String) -> Result<Vec<HistoryEntry>, String> {
-    let manager = HISTORY_MANAGER.read().await;
+    let manager = history_manager().await?;
     let parsed_date = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
         .map_err(|e| format!("Invalid date format: {}", e))?;
-    Ok(manager.get_history_by_date(parsed_date).into_iter().cloned().collect())
+    manager.get_history_by_date(parsed_date)
+}
+
+#[tauri::command]
+pub async fn get_history_range(start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>, offset: usize, limit: usize) -> Result<(Vec<HistoryEntry>, usize), String> {
+    let manager = history_manager().await?;
+    manager.get_history_range(start, end, offset, limit)
+}
+
+#[tauri::command]
+pub async fn get_history_by_domain() -> Result<Vec<DomainHistory>, String> {
+    let manager = history_manager().await?;
+    manager.get_history_by_domain()
+}
+
+#[tauri::command]
+pub async fn clear_history_for_domain(domain: String) -> Result<usize, String> {
+    let mut manager = history_manager().await?;
+    manager.clear_history_for_domain(&domain)
 }
 
 #[tauri::command]
 pub async fn get_history_stats() -> Result<HistoryStats, String> {
-    let manager = HISTORY_MANAGER.read().await;
-    Ok(manager.get_stats())
+    let manager = history_manager().await?;
+    manager.get_stats()
 }
 
 #[tauri::command]
 pub async fn get_history_suggestions(partial_url: String, limit: usize) -> Result<Vec<HistoryEntry>, String> {
-    let manager = HISTORY_MANAGER.read().await;
-    Ok(manager.get_suggestions(&partial_url, limit).into_iter().cloned().collect())
+    let manager = history_manager().await?;
+    manager.get_suggestions(&partial_url, limit)
+}
+
+#[tauri::command]
+pub async fn get_browsing_sessions(gap_minutes: i64) -> Result<Vec<BrowsingSession>, String> {
+    if gap_minutes <= 0 {
+        return Err("Gap must be a positive number of minutes".to_string());
+    }
+    
+    let manager = history_manager().await?;
+    manager.get_browsing_sessions(chrono::Duration::minutes(gap_minutes))
 }
 
 #[tauri::command]
 pub async fn update_history_favicon(url: String, favicon: String) -> Result<(), String> {
-    let mut manager = HISTORY_MANAGER.write().await;
-    manager.update_favicon(&url, &favicon);
-    Ok(())
+    let mut manager = history_manager().await?;
+    manager.update_favicon(&url, &favicon)
 }
 
 #[tauri::command]
 pub async fn export_history() -> Result<String, String> {
-    let manager = HISTORY_MANAGER.read().await;
+    let manager = history_manager().await?;
     manager.export_history()
 }
 
 #[tauri::command]
 pub async fn import_history(data: String) -> Result<(), String> {
-    let mut manager = HISTORY_MANAGER.write().await;
+    let mut manager = history_manager().await?;
     manager.import_history(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, last_visit: chrono::DateTime<chrono::Utc>, visit_count: u32) -> HistoryEntry {
+        HistoryEntry {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            title: url.to_string(),
+            visit_time: last_visit,
+            visit_count,
+            last_visit,
+            favicon: None,
+            is_private: false,
+        }
+    }
+
+    #[test]
+    fn search_folds_non_ascii_case() {
+        let mut manager = HistoryManager::in_memory();
+        manager.add_visit("https://example.de/", "Über uns", false).unwrap();
+        manager.add_visit("https://example.com/", "About", false).unwrap();
+        
+        let results = manager.search_history("über", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].url, "https://example.de/");
+        
+        let suggestions = manager.get_suggestions("ÜBER", 10).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].url, "https://example.de/");
+    }
+
+    #[test]
+    fn oversized_clear_ranges_are_rejected() {
+        assert!(clear_range_hours(Some(i64::MAX)).is_err());
+        assert!(clear_range_hours(Some(0)).is_err());
+        assert!(range_cutoff(chrono::TimeDelta::MAX).is_err());
+        
+        let mut manager = HistoryManager::in_memory();
+        assert!(manager.clear_history(Some(chrono::TimeDelta::MAX)).is_err());
+        assert!(manager.preview_clear_history(clear_range_hours(Some(24)).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn repeat_visits_update_a_single_row() {
+        let mut manager = HistoryManager::in_memory();
+        let first = manager.add_visit("https://example.com/", "Example", false).unwrap();
+        let second = manager.add_visit("https://example.com/", "Example Domain", false).unwrap();
+        assert_eq!(manager.add_visit("https://example.com/secret", "Secret", true).unwrap(), "");
+        
+        let entries = manager.all_entries().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].visit_count, 2);
+        assert_eq!(entries[0].title, "Example Domain");
+    }
+
+    #[test]
+    fn history_range_is_paged_most_recent_first() {
+        let mut manager = HistoryManager::in_memory();
+        let start = chrono::Utc::now() - chrono::Duration::days(10);
+        let entries: Vec<HistoryEntry> = (0..5)
+            .map(|day| entry(&format!("https://example.com/{}", day), start + chrono::Duration::days(day), 1))
+            .collect();
+        manager.insert_entries(&entries).unwrap();
+        
+        let end = start + chrono::Duration::days(3);
+        let (page, total) = manager.get_history_range(start, end, 1, 2).unwrap();
+        
+        assert_eq!(total, 4);
+        let urls: Vec<&str> = page.iter().map(|entry| entry.url.as_str()).collect();
+        assert_eq!(urls, ["https://example.com/2", "https://example.com/1"]);
+        assert!(manager.get_history_range(end, start, 0, 10).is_err());
+    }
+
+    #[test]
+    fn history_groups_and_clears_by_domain() {
+        let mut manager = HistoryManager::in_memory();
+        let now = chrono::Utc::now();
+        manager.insert_entries(&[
+            entry("https://www.example.com/a", now, 3),
+            entry("https://docs.example.com/b", now, 2),
+            entry("https://example.org/", now, 4),
+        ]).unwrap();
+        
+        let groups = manager.get_history_by_domain().unwrap();
+        let counts: Vec<(&str, u64)> = groups.iter().map(|group| (group.domain.as_str(), group.visit_count)).collect();
+        assert_eq!(counts, [("example.com", 5), ("example.org", 4)]);
+        
+        assert_eq!(manager.clear_history_for_domain("example.com").unwrap(), 2);
+        let remaining = manager.all_entries().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].url, "https://example.org/");
+    }
+
+    #[test]
+    fn paused_recording_skips_new_visits() {
+        let mut manager = HistoryManager::in_memory();
+        manager.add_visit("https://example.com/", "Example", false).unwrap();
+        
+        manager.set_recording(false);
+        assert!(!manager.is_recording());
+        assert_eq!(manager.add_visit("https://example.com/", "Example", false).unwrap(), "");
+        assert_eq!(manager.add_visit("https://example.org/", "Other", false).unwrap(), "");
+        
+        manager.set_recording(true);
+        manager.add_visit("https://example.org/", "Other", false).unwrap();
+        let entries = manager.all_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.visit_count == 1));
+    }
+
+    #[test]
+    fn json_history_is_migrated_once() {
+        let dir = std::env::temp_dir().join(format!("sw3do-history-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join(HISTORY_JSON_FILE);
+        let exported = vec![entry("https://example.com/", chrono::Utc::now(), 3)];
+        std::fs::write(&json_path, serde_json::to_string(&exported).unwrap()).unwrap();
+        
+        let mut manager = HistoryManager::in_memory();
+        manager.migrate_json_history(&json_path).unwrap();
+        manager.remove_entry(&exported[0].id).unwrap();
+        manager.migrate_json_history(&json_path).unwrap();
+        
+        assert!(manager.all_entries().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_json_history_fails_the_migration() {
+        let dir = std::env::temp_dir().join(format!("sw3do-history-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join(HISTORY_JSON_FILE);
+        std::fs::write(&json_path, "[{\"url\": ").unwrap();
+        
+        let mut manager = HistoryManager::in_memory();
+        assert!(manager.migrate_json_history(&json_path).is_err());
+        
+        let exported = vec![entry("https://example.com/", chrono::Utc::now(), 3)];
+        std::fs::write(&json_path, serde_json::to_string(&exported).unwrap()).unwrap();
+        manager.migrate_json_history(&json_path).unwrap();
+        assert_eq!(manager.all_entries().unwrap().len(), 1);
+        
+        let mut fresh = HistoryManager::in_memory();
+        fresh.migrate_json_history(&dir.join("missing.json")).unwrap();
+        assert!(fresh.all_entries().unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn many_rows_are_queried_by_date_and_recency_in_order() {
+        let mut manager = HistoryManager::in_memory();
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let start_of_day = day.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let entries: Vec<HistoryEntry> = (0..3000)
+            .map(|n| entry(&format!("https://example.com/{}", n), start_of_day + chrono::Duration::minutes(n - 1000), 1))
+            .collect();
+        manager.insert_entries(&entries).unwrap();
+        
+        let on_day = manager.get_history_by_date(day).unwrap();
+        assert_eq!(on_day.len(), 24 * 60);
+        assert!(on_day.windows(2).all(|pair| pair[0].last_visit > pair[1].last_visit));
+        assert_eq!(on_day[0].url, format!("https://example.com/{}", 1000 + 24 * 60 - 1));
+        assert_eq!(on_day.last().unwrap().url, "https://example.com/1000");
+        
+        let recent = manager.get_recent_history(50).unwrap();
+        let urls: Vec<String> = recent.iter().map(|entry| entry.url.clone()).collect();
+        let expected: Vec<String> = (2950..3000).rev().map(|n| format!("https://example.com/{}", n)).collect();
+        assert_eq!(urls, expected);
+    }
+
+    #[test]
+    fn frequent_matches_outrank_rare_ones() {
+        let mut manager = HistoryManager::in_memory();
+        let last_week = chrono::Utc::now() - chrono::Duration::days(7);
+        let mut daily = entry("https://docs.example.com/rust", last_week, 60);
+        daily.title = "Rust documentation".to_string();
+        let mut once = entry("https://blog.example.org/rust-news", chrono::Utc::now() - chrono::Duration::days(2), 1);
+        once.title = "Rust news".to_string();
+        manager.insert_entries(&[once, daily]).unwrap();
+        
+        let results = manager.search_history("rust", None).unwrap();
+        let urls: Vec<&str> = results.iter().map(|entry| entry.url.as_str()).collect();
+        assert_eq!(urls, ["https://docs.example.com/rust", "https://blog.example.org/rust-news"]);
+        
+        assert_eq!(manager.search_history("rust", Some(1)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn clearing_a_range_removes_exactly_the_recent_entries() {
+        let mut manager = HistoryManager::in_memory();
+        let now = chrono::Utc::now();
+        manager.insert_entries(&[
+            entry("https://recent.example/a", now - chrono::Duration::minutes(5), 1),
+            entry("https://recent.example/b", now - chrono::Duration::minutes(50), 1),
+            entry("https://old.example/a", now - chrono::Duration::minutes(70), 1),
+            entry("https://old.example/b", now - chrono::Duration::days(3), 1),
+        ]).unwrap();
+        
+        manager.clear_history(clear_range_hours(Some(1)).unwrap()).unwrap();
+        
+        let mut remaining: Vec<String> = manager.all_entries().unwrap().into_iter().map(|entry| entry.url).collect();
+        remaining.sort();
+        assert_eq!(remaining, ["https://old.example/a", "https://old.example/b"]);
+        
+        manager.clear_history(None).unwrap();
+        assert!(manager.all_entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_range_pages_cover_the_range_without_overlap() {
+        let mut manager = HistoryManager::in_memory();
+        let start = from_millis((chrono::Utc::now() - chrono::Duration::days(10)).timestamp_millis());
+        let entries: Vec<HistoryEntry> = (0..6)
+            .flat_map(|day| (0..4).map(move |n| (day, n)))
+            .map(|(day, n)| entry(&format!("https://example.com/{}/{}", day, n), start + chrono::Duration::days(day) + chrono::Duration::hours(n / 2), 1))
+            .collect();
+        manager.insert_entries(&entries).unwrap();
+        
+        let end = start + chrono::Duration::days(4);
+        let mut seen: Vec<HistoryEntry> = Vec::new();
+        let mut offset = 0;
+        let total = loop {
+            let (page, total) = manager.get_history_range(start, end, offset, 3).unwrap();
+            assert!(page.len() <= 3);
+            offset += page.len();
+            let done = page.is_empty();
+            seen.extend(page);
+            if done {
+                break total;
+            }
+        };
+        
+        assert_eq!(total, 18);
+        assert_eq!(seen.len(), total);
+        let mut ids: Vec<&str> = seen.iter().map(|entry| entry.id.as_str()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), total);
+        assert!(seen.windows(2).all(|pair| pair[0].last_visit >= pair[1].last_visit));
+        assert!(seen.iter().all(|entry| entry.last_visit >= start && entry.last_visit <= end));
+    }
+
+    #[test]
+    fn sensitive_domain_visits_are_not_recorded() {
+        let mut manager = HistoryManager::in_memory();
+        let mut privacy = super::super::settings::BrowserSettings::default().privacy;
+        privacy.sensitive_domains.insert("bank.example".to_string());
+        
+        assert_eq!(record_visit(&mut manager, &privacy, "https://bank.example/login", "Bank", false).unwrap(), "");
+        assert_eq!(record_visit(&mut manager, &privacy, "https://online.bank.example/", "Bank", false).unwrap(), "");
+        assert_ne!(record_visit(&mut manager, &privacy, "https://news.example/", "News", false).unwrap(), "");
+        
+        let entries = manager.all_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "https://news.example/");
+    }
+
+    #[test]
+    fn browsing_sessions_split_on_gaps_longer_than_the_threshold() {
+        let mut manager = HistoryManager::in_memory();
+        let start = from_millis((chrono::Utc::now() - chrono::Duration::days(1)).timestamp_millis());
+        let minutes = |n: i64| start + chrono::Duration::minutes(n);
+        let mut private = entry("https://private.example/", minutes(45), 1);
+        private.is_private = true;
+        manager.insert_entries(&[
+            entry("https://example.com/1", minutes(0), 1),
+            entry("https://example.com/2", minutes(30), 1),
+            entry("https://example.com/3", minutes(61), 1),
+            private,
+            entry("https://example.com/4", minutes(200), 1),
+        ]).unwrap();
+        
+        let sessions = manager.get_browsing_sessions(chrono::Duration::minutes(30)).unwrap();
+        
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].urls, ["https://example.com/4"]);
+        assert_eq!(sessions[1].urls, ["https://example.com/3"]);
+        assert_eq!(sessions[2].urls, ["https://example.com/1", "https://example.com/2"]);
+        assert_eq!(sessions[2].start_time, minutes(0));
+        assert_eq!(sessions[2].end_time, minutes(30));
+    }
+
+    #[test]
+    fn clear_preview_matches_what_is_cleared() {
+        let mut manager = HistoryManager::in_memory();
+        let now = chrono::Utc::now();
+        let entries: Vec<HistoryEntry> = (0..12)
+            .map(|n| entry(&format!("https://example.com/{}", n), now - chrono::Duration::hours(n * 5), 1))
+            .collect();
+        manager.insert_entries(&entries).unwrap();
+        
+        let range = clear_range_hours(Some(24)).unwrap();
+        let preview = manager.preview_clear_history(range).unwrap();
+        let before = manager.all_entries().unwrap().len();
+        manager.clear_history(range).unwrap();
+        let removed = before - manager.all_entries().unwrap().len();
+        
+        assert_eq!(preview.count, 5);
+        assert_eq!(preview.count, removed);
+        assert_eq!(preview.sample_urls.len(), CLEAR_PREVIEW_SAMPLES);
+        assert_eq!(preview.sample_urls[0], "https://example.com/0");
+        assert_eq!(preview.latest.map(|time| time.timestamp_millis()), Some(now.timestamp_millis()));
+        assert_eq!(preview.earliest.map(|time| time.timestamp_millis()), Some((now - chrono::Duration::hours(20)).timestamp_millis()));
+    }
 }
\ No newline at end of file
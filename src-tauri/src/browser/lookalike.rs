@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const TOP_VISITED_LIMIT: usize = 50;
+const MIN_EDIT_DISTANCE_LABEL_LEN: usize = 6;
+
+/// Brands that are impersonated often enough to always check against.
+const SPOOFED_BRANDS: &[&str] = &[
+    "paypal.com",
+    "apple.com",
+    "icloud.com",
+    "google.com",
+    "gmail.com",
+    "microsoft.com",
+    "outlook.com",
+    "live.com",
+    "amazon.com",
+    "facebook.com",
+    "instagram.com",
+    "whatsapp.com",
+    "netflix.com",
+    "github.com",
+    "linkedin.com",
+    "twitter.com",
+    "yahoo.com",
+    "dropbox.com",
+    "coinbase.com",
+    "binance.com",
+    "chase.com",
+    "wellsfargo.com",
+    "bankofamerica.com",
+];
+
+/// Second-level labels under which registrations happen one level deeper (e.g. `example.co.uk`).
+const SECOND_LEVEL_SUFFIXES: &[&str] = &["co", "com", "org", "net", "ac", "gov", "edu"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum LookalikeReason {
+    Homoglyph,
+    EditDistance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookalikeWarning {
+    pub url: String,
+    pub domain: String,
+    pub similar_to: String,
+    pub reason: LookalikeReason,
+    pub distance: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LookalikeWarningEvent {
+    pub tab_id: String,
+    pub warning: LookalikeWarning,
+}
+
+pub fn registrable_domain(host: &str) -> String {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = host.split('.').filter(|label| !label.is_empty()).collect();
+    
+    let keep = match labels.as_slice() {
+        [.., second, tld] if tld.len() == 2 && SECOND_LEVEL_SUFFIXES.contains(second) => 3,
+        _ => 2,
+    };
+    
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+fn confusable(c: char) -> char {
+    match c {
+        'а' | 'α' => 'a',
+        'Ь' | 'в' => 'b',
+        'с' | 'ϲ' => 'c',
+        'ԁ' => 'd',
+        'е' | 'ё' | 'ε' | '3' => 'e',
+        'һ' => 'h',
+        'і' | 'ї' | 'ι' | '!' => 'i',
+        'ј' => 'j',
+        'к' | 'κ' => 'k',
+        'ӏ' | '1' | '|' => 'l',
+        'о' | 'ο' | '0' => 'o',
+        'р' | 'ρ' => 'p',
+        'ԛ' => 'q',
+        'ѕ' | '5' | '$' => 's',
+        'т' | 'τ' => 't',
+        'υ' | 'ս' => 'u',
+        'ν' | 'ѵ' => 'v',
+        'ԝ' | 'ѡ' => 'w',
+        'х' | 'χ' => 'x',
+        'у' | 'γ' => 'y',
+        'ᴢ' => 'z',
+        other => other,
+    }
+}
+
+/// Reduces a domain to the shape a reader sees: homoglyphs and digit
+/// substitutions collapse onto the Latin letters they imitate.
+pub fn skeleton(domain: &str) -> String {
+    let folded: String = domain.to_lowercase().chars().map(confusable).collect();
+    folded.replace("rn", "m").replace("vv", "w")
+}
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    
+    previous[b.len()]
+}
+
+fn max_distance(label: &str) -> usize {
+    if label.chars().count() >= 10 { 2 } else { 1 }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let host = super::network::url_host(url)?;
+    let (unicode, result) = idna::domain_to_unicode(&host);
+    Some(if result.is_ok() { unicode } else { host })
+}
+
+/// Compares the registrable domain of `url` against `known_domains`.
+/// Exact matches are trusted; anything that renders the same after homoglyph
+/// folding, or is within a small edit distance, is reported.
+pub fn find_lookalike(url: &str, known_domains: &[String]) -> Option<LookalikeWarning> {
+    let domain = registrable_domain(&host_of(url)?);
+    if domain.is_empty() || known_domains.contains(&domain) {
+        return None;
+    }
+    
+    let domain_skeleton = skeleton(&domain);
+    let domain_label = domain_skeleton.split('.').next().unwrap_or_default().to_string();
+    
+    let mut best: Option<LookalikeWarning> = None;
+    
+    for known in known_domains {
+        let known_skeleton = skeleton(known);
+        
+        let (reason, distance) = if known_skeleton == domain_skeleton {
+            (LookalikeReason::Homoglyph, edit_distance(&domain, known))
+        } else {
+            let known_label = known_skeleton.split('.').next().unwrap_or_default();
+            if known_label.chars().count() < MIN_EDIT_DISTANCE_LABEL_LEN {
+                continue;
+            }
+            
+            let distance = edit_distance(&domain_label, known_label);
+            if distance == 0 || distance > max_distance(known_label) {
+                continue;
+            }
+            (LookalikeReason::EditDistance, distance)
+        };
+        
+        let is_better = match &best {
+            None => true,
+            Some(current) => reason == LookalikeReason::Homoglyph && current.reason != LookalikeReason::Homoglyph
+                || reason == current.reason && distance < current.distance,
+        };
+        
+        if is_better {
+            best = Some(LookalikeWarning {
+                url: url.to_string(),
+                domain: domain.clone(),
+                similar_to: known.clone(),
+                reason,
+                distance,
+            });
+        }
+    }
+    
+    best
+}
+
+/// Known-good domains: the spoofed brand list plus the user's most visited sites.
+async fn known_domains() -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut domains = Vec::new();
+    
+    let brands = SPOOFED_BRANDS.iter().map(|brand| brand.to_string());
+    let visited = super::history::most_visited_urls(TOP_VISITED_LIMIT).await
+        .into_iter()
+        .filter_map(|url| host_of(&url))
+        .map(|host| registrable_domain(&host));
+    
+    for domain in brands.chain(visited) {
+        if !domain.is_empty() && seen.insert(domain.clone()) {
+            domains.push(domain);
+        }
+    }
+    
+    domains
+}
+
+pub async fn detect_lookalike(url: &str) -> Option<LookalikeWarning> {
+    find_lookalike(url, &known_domains().await)
+}
+
+#[tauri::command]
+pub async fn check_lookalike(url: String) -> Result<Option<LookalikeWarning>, String> {
+    Ok(detect_lookalike(&url).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brands() -> Vec<String> {
+        SPOOFED_BRANDS.iter().map(|brand| brand.to_string()).collect()
+    }
+
+    #[test]
+    fn digit_substitution_is_a_lookalike() {
+        let warning = find_lookalike("https://paypa1.com/signin", &brands()).unwrap();
+        
+        assert_eq!(warning.domain, "paypa1.com");
+        assert_eq!(warning.similar_to, "paypal.com");
+        assert_eq!(warning.reason, LookalikeReason::Homoglyph);
+    }
+
+    #[test]
+    fn cyrillic_letters_are_a_lookalike() {
+        let warning = find_lookalike("https://\u{0430}pple.com/", &brands()).unwrap();
+        
+        assert_eq!(warning.similar_to, "apple.com");
+        assert_eq!(warning.reason, LookalikeReason::Homoglyph);
+    }
+
+    #[test]
+    fn genuine_domains_are_not_lookalikes() {
+        for url in ["https://paypal.com/", "https://www.apple.com/iphone", "https://support.apple.com/", "https://example.org/"] {
+            assert!(find_lookalike(url, &brands()).is_none(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn small_typos_of_long_names_are_lookalikes() {
+        let warning = find_lookalike("https://githuub.com/", &brands()).unwrap();
+        assert_eq!(warning.similar_to, "github.com");
+        assert_eq!(warning.reason, LookalikeReason::EditDistance);
+    }
+}
\ No newline at end of file
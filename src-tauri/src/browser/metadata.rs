@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use url::Url;
+use once_cell::sync::Lazy;
+
+static TITLE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap()
+});
+
+static ICON_LINK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)<link\s[^>]*rel\s*=\s*["']?(?:shortcut\s+)?icon["']?[^>]*>"#).unwrap()
+});
+
+static HREF_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)href\s*=\s*["']([^"']+)["']"#).unwrap()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageMetadata {
+    pub url: String,
+    pub title: Option<String>,
+    pub favicon: Option<String>,
+}
+
+/// Extracts the page title and favicon URL from an HTML document, falling back
+/// to `/favicon.ico` on the page's origin when no icon link is declared.
+pub fn parse_page_metadata(page_url: &str, html: &str) -> PageMetadata {
+    let base_url = Url::parse(page_url).ok();
+    
+    let title = TITLE_REGEX.captures(html)
+        .map(|captures| decode_entities(captures[1].trim()))
+        .filter(|title| !title.is_empty());
+    
+    let favicon = ICON_LINK_REGEX.find(html)
+        .and_then(|link| HREF_REGEX.captures(link.as_str()))
+        .and_then(|captures| base_url.as_ref()?.join(&captures[1]).ok())
+        .or_else(|| base_url.as_ref()?.join("/favicon.ico").ok())
+        .map(|url| url.to_string());
+    
+    PageMetadata {
+        url: page_url.to_string(),
+        title,
+        favicon,
+    }
+}
+
+pub fn decode_entities(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+pub async fn fetch_page_metadata(url: &str) -> Result<PageMetadata, String> {
+    let client = super::network::http_client().await?;
+    let response = super::network::send_request(client.get(url), "fetch page").await?;
+    
+    let final_url = response.url().to_string();
+    super::security::record_response_headers(&final_url, response.headers()).await;
+    let html = response.text()
+        .await
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+    
+    Ok(parse_page_metadata(&final_url, &html))
+}
+
+#[tauri::command]
+pub async fn get_page_metadata(url: String) -> Result<PageMetadata, String> {
+    fetch_page_metadata(&url).await
+}
@@ -8,6 +8,18 @@ pub mod privacy;
 pub mod filters;
 pub mod session;
 pub mod plugins;
+pub mod doh;
+pub mod network;
+pub mod storage;
+pub mod metadata;
+pub mod lookalike;
+pub mod reports;
+pub mod favicons;
+pub mod security;
+pub mod redirects;
+pub mod reader;
+pub mod watch;
+pub mod tags;
 
 pub use engine::*;
 pub use tabs::*;
@@ -18,4 +30,16 @@ pub use settings::*;
 pub use privacy::*;
 pub use filters::*;
 pub use session::*;
-pub use plugins::*;
\ No newline at end of file
+pub use plugins::*;
+pub use doh::*;
+pub use network::*;
+pub use storage::*;
+pub use metadata::*;
+pub use lookalike::*;
+pub use reports::*;
+pub use favicons::*;
+pub use security::*;
+pub use redirects::*;
+pub use reader::*;
+pub use watch::*;
+pub use tags::*;
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use std::time::Duration;
+use super::doh::DohResolver;
+use super::settings::{NetworkSettings, PrivacySettings};
+
+/// Builds the reqwest client shared by outbound browser requests, honoring
+/// the DNS-over-HTTPS privacy setting and the configured network limits.
+pub async fn http_client() -> Result<reqwest::Client, String> {
+    let settings = super::settings::current_settings().await;
+    build_http_client(&settings.privacy, &settings.advanced.network)
+}
+
+pub fn build_http_client(privacy: &PrivacySettings, network: &NetworkSettings) -> Result<reqwest::Client, String> {
+    client_builder(privacy, network)
+        .timeout(Duration::from_secs(network.request_timeout_secs.max(1)))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+/// Client for file downloads. Same settings as `http_client` but without the
+/// overall request timeout, which would cut large downloads off midway.
+pub async fn download_client() -> Result<reqwest::Client, String> {
+    let settings = super::settings::current_settings().await;
+    client_builder(&settings.privacy, &settings.advanced.network)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+fn client_builder(privacy: &PrivacySettings, network: &NetworkSettings) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder()
+        .user_agent("Sw3do Browser/1.0")
+        .connect_timeout(Duration::from_secs(network.connect_timeout_secs.max(1)))
+        .pool_max_idle_per_host(network.max_idle_connections_per_host.max(1));
+    
+    if privacy.dns_over_https {
+        builder = builder.dns_resolver(Arc::new(DohResolver::new(privacy.dns_over_https_provider)));
+    }
+    
+    builder
+}
+
+/// Sends `request`, reporting an elapsed timeout in a form the UI can show as is.
+pub async fn send_request(request: reqwest::RequestBuilder, action: &str) -> Result<reqwest::Response, String> {
+    match request.send().await {
+        Ok(response) => Ok(response),
+        Err(e) if e.is_timeout() => {
+            let timeout_secs = super::settings::current_settings().await.advanced.network.request_timeout_secs.max(1);
+            Err(format!("Request timed out after {}s", timeout_secs))
+        }
+        Err(e) => Err(format!("Failed to {}: {}", action, e)),
+    }
+}
+
+/// True when `host` is `domain` itself or one of its subdomains.
+pub fn host_matches_domain(host: &str, domain: &str) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+pub fn url_host(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.host_str().map(|host| host.to_lowercase())
+}
\ No newline at end of file
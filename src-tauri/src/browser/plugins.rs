@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
 use uuid::Uuid;
 
+const MAX_DENIED_EVENTS: usize = 200;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plugin {
     pub id: String,
@@ -15,6 +18,9 @@ pub struct Plugin {
     pub permissions: Vec<PluginPermission>,
     pub hooks: Vec<PluginHook>,
     pub settings: HashMap<String, PluginSetting>,
+    /// Names of plugins that must be installed and enabled for this one to run.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
     pub manifest_path: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
@@ -52,6 +58,28 @@ pub enum PluginHook {
     SettingsChanged,
 }
 
+impl PluginHook {
+    /// The permission a plugin needs to receive this hook's data.
+    pub fn required_permission(&self) -> PluginPermission {
+        match self {
+            PluginHook::BeforeNavigate | PluginHook::AfterNavigate => PluginPermission::TabManagement,
+            PluginHook::BeforeRequest | PluginHook::AfterRequest => PluginPermission::NetworkAccess,
+            PluginHook::TabCreated | PluginHook::TabClosed => PluginPermission::TabManagement,
+            PluginHook::WindowCreated | PluginHook::WindowClosed => PluginPermission::WindowManagement,
+            PluginHook::BookmarkAdded | PluginHook::BookmarkRemoved => PluginPermission::BookmarkAccess,
+            PluginHook::HistoryAdded => PluginPermission::HistoryAccess,
+            PluginHook::DownloadStarted | PluginHook::DownloadCompleted => PluginPermission::FileSystemRead,
+            PluginHook::SettingsChanged => PluginPermission::SettingsAccess,
+        }
+    }
+
+    /// Name of the Tauri event the frontend plugin host listens on to run
+    /// `plugin_id`'s handler for this hook, e.g. `plugin://{id}/TabCreated`.
+    pub fn event_name(&self, plugin_id: &str) -> String {
+        format!("plugin://{}/{:?}", plugin_id, self)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginSetting {
     pub key: String,
@@ -70,6 +98,24 @@ pub enum PluginSettingType {
     Object,
 }
 
+impl PluginSettingType {
+    pub fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        let matches = match self {
+            PluginSettingType::String => value.is_string(),
+            PluginSettingType::Number => value.as_f64().is_some_and(f64::is_finite),
+            PluginSettingType::Boolean => value.is_boolean(),
+            PluginSettingType::Array => value.is_array(),
+            PluginSettingType::Object => value.is_object(),
+        };
+        
+        if matches {
+            Ok(())
+        } else {
+            Err(format!("Expected a {:?} value", self))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginManifest {
     pub name: String,
@@ -81,6 +127,8 @@ pub struct PluginManifest {
     pub hooks: Vec<PluginHook>,
     pub settings: Vec<PluginSettingDefinition>,
     pub min_browser_version: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,12 +148,23 @@ pub struct PluginEvent {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// An event withheld from a plugin that subscribed to the hook without holding its permission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeniedPluginEvent {
+    pub plugin_id: String,
+    pub hook: PluginHook,
+    pub required_permission: PluginPermission,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginStats {
     pub total_plugins: usize,
     pub enabled_plugins: usize,
     pub disabled_plugins: usize,
     pub events_processed: u64,
+    /// Events emitted to the frontend plugin host, one per receiving plugin.
+    pub events_dispatched: u64,
     pub last_event_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
@@ -117,6 +176,8 @@ pub struct PluginManager {
     pub plugins: HashMap<String, Plugin>,
     pub event_handlers: HashMap<PluginHook, Vec<String>>,
     pub stats: PluginStats,
+    /// Oldest first, capped at `MAX_DENIED_EVENTS`.
+    pub denied_events: Vec<DeniedPluginEvent>,
 }
 
 impl PluginManager {
@@ -129,17 +190,24 @@ impl PluginManager {
                 enabled_plugins: 0,
                 disabled_plugins: 0,
                 events_processed: 0,
+                events_dispatched: 0,
                 last_event_time: None,
             },
+            denied_events: Vec::new(),
         }
     }
 
+    /// Names must be unique, since dependencies refer to plugins by name.
     pub fn install_plugin(&mut self, manifest_path: &str) -> Result<String, String> {
-        let manifest_content = std::fs::read_to_string(manifest_path)
-            .map_err(|e| format!("Failed to read manifest: {}", e))?;
-        
-        let manifest: PluginManifest = serde_json::from_str(&manifest_content)
-            .map_err(|e| format!("Invalid manifest format: {}", e))?;
+        let manifest = self.validate_plugin_manifest(manifest_path)?;
+        if self.plugin_by_name(&manifest.name).is_some() {
+            return Err(format!("A plugin named {} is already installed", manifest.name));
+        }
+        for dependency in &manifest.dependencies {
+            if !self.plugin_by_name(dependency).is_some_and(|plugin| plugin.enabled) {
+                return Err(format!("Missing dependency: {}", dependency));
+            }
+        }
         
         let plugin_id = Uuid::new_v4().to_string();
         let mut settings = HashMap::new();
@@ -164,6 +232,7 @@ impl PluginManager {
             permissions: manifest.permissions,
             hooks: manifest.hooks.clone(),
             settings,
+            dependencies: manifest.dependencies,
             manifest_path: manifest_path.to_string(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
@@ -181,9 +250,25 @@ impl PluginManager {
         Ok(plugin_id)
     }
 
-    pub fn uninstall_plugin(&mut self, plugin_id: &str) -> Result<(), String> {
+    /// Refuses while other plugins depend on this one, unless `cascade` is set,
+    /// in which case the dependents are uninstalled first.
+    pub fn uninstall_plugin(&mut self, plugin_id: &str, cascade: bool) -> Result<(), String> {
+        let dependents = self.dependent_ids(plugin_id, false);
+        if !dependents.is_empty() {
+            if !cascade {
+                return Err(format!("Plugin is required by {}", self.plugin_names(&dependents)));
+            }
+            for dependent_id in &dependents {
+                // Already gone if it also depended on an earlier dependent.
+                if self.plugins.contains_key(dependent_id) {
+                    self.uninstall_plugin(dependent_id, true)?;
+                }
+            }
+        }
+        
         let plugin = self.plugins.remove(plugin_id)
             .ok_or("Plugin not found")?;
+        self.denied_events.retain(|event| event.plugin_id != plugin_id);
         
         for hook in &plugin.hooks {
             if let Some(handlers) = self.event_handlers.get_mut(hook) {
@@ -199,6 +284,16 @@ impl PluginManager {
     }
 
     pub fn enable_plugin(&mut self, plugin_id: &str) -> Result<(), String> {
+        let dependencies = self.plugins.get(plugin_id)
+            .ok_or("Plugin not found")?
+            .dependencies
+            .clone();
+        for dependency in &dependencies {
+            if !self.plugin_by_name(dependency).is_some_and(|plugin| plugin.enabled) {
+                return Err(format!("Dependency is not installed or enabled: {}", dependency));
+            }
+        }
+        
         let plugin = self.plugins.get_mut(plugin_id)
             .ok_or("Plugin not found")?;
         
@@ -215,7 +310,19 @@ impl PluginManager {
         Ok(())
     }
 
-    pub fn disable_plugin(&mut self, plugin_id: &str) -> Result<(), String> {
+    /// Refuses while enabled plugins depend on this one, unless `cascade` is
+    /// set, in which case those dependents are disabled too.
+    pub fn disable_plugin(&mut self, plugin_id: &str, cascade: bool) -> Result<(), String> {
+        let dependents = self.dependent_ids(plugin_id, true);
+        if !dependents.is_empty() {
+            if !cascade {
+                return Err(format!("Plugin is required by {}", self.plugin_names(&dependents)));
+            }
+            for dependent_id in &dependents {
+                self.disable_plugin(dependent_id, true)?;
+            }
+        }
+        
         let plugin = self.plugins.get_mut(plugin_id)
             .ok_or("Plugin not found")?;
         
@@ -235,6 +342,68 @@ impl PluginManager {
         Ok(())
     }
 
+    fn plugin_by_name(&self, name: &str) -> Option<&Plugin> {
+        self.plugins.values().find(|plugin| plugin.name == name)
+    }
+
+    fn plugin_names(&self, plugin_ids: &[String]) -> String {
+        plugin_ids.iter()
+            .filter_map(|id| self.plugins.get(id))
+            .map(|plugin| plugin.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Ids of plugins that list this one as a dependency, sorted for stable output.
+    fn dependent_ids(&self, plugin_id: &str, enabled_only: bool) -> Vec<String> {
+        let Some(name) = self.plugins.get(plugin_id).map(|plugin| plugin.name.clone()) else {
+            return Vec::new();
+        };
+        
+        let mut dependents: Vec<String> = self.plugins.values()
+            .filter(|plugin| plugin.id != plugin_id && plugin.dependencies.contains(&name))
+            .filter(|plugin| !enabled_only || plugin.enabled)
+            .map(|plugin| plugin.id.clone())
+            .collect();
+        dependents.sort();
+        dependents
+    }
+
+    pub fn get_dependents(&self, plugin_id: &str) -> Vec<&Plugin> {
+        self.dependent_ids(plugin_id, false)
+            .iter()
+            .filter_map(|id| self.plugins.get(id))
+            .collect()
+    }
+
+    /// Reorders `plugin_ids` so each plugin comes after the dependencies that
+    /// are also in the list. Otherwise the original order is kept.
+    fn dependency_order(&self, plugin_ids: Vec<String>) -> Vec<String> {
+        fn visit(manager: &PluginManager, plugin_id: &str, included: &HashSet<&str>, visited: &mut HashSet<String>, ordered: &mut Vec<String>) {
+            if !visited.insert(plugin_id.to_string()) {
+                return;
+            }
+            if let Some(plugin) = manager.plugins.get(plugin_id) {
+                for dependency in &plugin.dependencies {
+                    if let Some(dependency) = manager.plugin_by_name(dependency) {
+                        visit(manager, &dependency.id, included, visited, ordered);
+                    }
+                }
+            }
+            if included.contains(plugin_id) {
+                ordered.push(plugin_id.to_string());
+            }
+        }
+        
+        let included: HashSet<&str> = plugin_ids.iter().map(|id| id.as_str()).collect();
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::new();
+        for plugin_id in &plugin_ids {
+            visit(self, plugin_id, &included, &mut visited, &mut ordered);
+        }
+        ordered
+    }
+
     pub fn get_plugin(&self, plugin_id: &str) -> Option<&Plugin> {
         self.plugins.get(plugin_id)
     }
@@ -252,6 +421,8 @@ impl PluginManager {
             .ok_or("Plugin not found")?;
         
         if let Some(setting) = plugin.settings.get_mut(key) {
+            setting.setting_type.validate(&value)
+                .map_err(|e| format!("Invalid value for {}: {}", key, e))?;
             setting.value = value;
             plugin.updated_at = chrono::Utc::now();
             Ok(())
@@ -265,23 +436,54 @@ impl PluginManager {
             .settings.get(key)
     }
 
+    /// Enabled plugins subscribed to `hook` that hold its required permission,
+    /// dependencies before their dependents. Subscribers without the permission
+    /// are skipped and recorded in `denied_events`.
     pub fn trigger_event(&mut self, hook: PluginHook, data: serde_json::Value) -> Vec<String> {
         let mut triggered_plugins = Vec::new();
+        let required_permission = hook.required_permission();
         
         if let Some(handlers) = self.event_handlers.get(&hook) {
             for plugin_id in handlers {
-                if let Some(plugin) = self.plugins.get(plugin_id) {
-                    if plugin.enabled {
-                        triggered_plugins.push(plugin_id.clone());
-                    }
+                let Some(plugin) = self.plugins.get(plugin_id) else {
+                    continue;
+                };
+                if !plugin.enabled {
+                    continue;
+                }
+                
+                if plugin.permissions.contains(&required_permission) {
+                    triggered_plugins.push(plugin_id.clone());
+                } else {
+                    self.denied_events.push(DeniedPluginEvent {
+                        plugin_id: plugin_id.clone(),
+                        hook: hook.clone(),
+                        required_permission: required_permission.clone(),
+                        timestamp: chrono::Utc::now(),
+                    });
                 }
             }
         }
         
+        if self.denied_events.len() > MAX_DENIED_EVENTS {
+            let excess = self.denied_events.len() - MAX_DENIED_EVENTS;
+            self.denied_events.drain(..excess);
+        }
+        
         self.stats.events_processed += 1;
         self.stats.last_event_time = Some(chrono::Utc::now());
         
-        triggered_plugins
+        self.dependency_order(triggered_plugins)
+    }
+
+    pub fn record_dispatch(&mut self, count: usize) {
+        self.stats.events_dispatched += count as u64;
+    }
+
+    pub fn get_denied_events(&self, plugin_id: &str) -> Vec<&DeniedPluginEvent> {
+        self.denied_events.iter()
+            .filter(|event| event.plugin_id == plugin_id)
+            .collect()
     }
 
     pub fn has_permission(&self, plugin_id: &str, permission: &PluginPermission) -> bool {
@@ -324,9 +526,20 @@ impl PluginManager {
         let plugin = self.plugins.get_mut(plugin_id)
             .ok_or("Plugin not found")?;
         
+        // Types come from the manifest, so only values are imported, and only
+        // when every one of them is valid.
+        let mut updates = Vec::new();
         for (key, setting) in settings {
-            if plugin.settings.contains_key(&key) {
-                plugin.settings.insert(key, setting);
+            if let Some(existing) = plugin.settings.get(&key) {
+                existing.setting_type.validate(&setting.value)
+                    .map_err(|e| format!("Invalid value for {}: {}", key, e))?;
+                updates.push((key, setting.value));
+            }
+        }
+        
+        for (key, value) in updates {
+            if let Some(existing) = plugin.settings.get_mut(&key) {
+                existing.value = value;
             }
         }
         
@@ -359,10 +572,61 @@ impl PluginManager {
             return Err("Plugin version cannot be empty".to_string());
         }
         
+        let required = parse_version(&manifest.min_browser_version)
+            .ok_or_else(|| format!("Invalid min_browser_version: {}", manifest.min_browser_version))?;
+        let current = parse_version(crate::BROWSER_VERSION).unwrap_or_default();
+        if required > current {
+            return Err(format!(
+                "Plugin requires browser version {} or newer, this is {}",
+                manifest.min_browser_version, crate::BROWSER_VERSION
+            ));
+        }
+        
+        // Unknown permissions already fail to deserialize above.
+        for (index, permission) in manifest.permissions.iter().enumerate() {
+            if manifest.permissions[..index].contains(permission) {
+                return Err(format!("Duplicate permission: {:?}", permission));
+            }
+        }
+        
+        let manifest_dir = std::path::Path::new(manifest_path)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new(""));
+        let main_path = manifest_dir.join(&manifest.main);
+        if manifest.main.is_empty() || !main_path.is_file() {
+            return Err(format!("Plugin main file not found: {}", main_path.display()));
+        }
+        
+        // Resolve `..` and symlinks so `main` cannot point outside the plugin directory.
+        let canonical_dir = std::fs::canonicalize(if manifest_dir.as_os_str().is_empty() {
+            std::path::Path::new(".")
+        } else {
+            manifest_dir
+        }).map_err(|e| format!("Failed to resolve plugin directory: {}", e))?;
+        let canonical_main = std::fs::canonicalize(&main_path)
+            .map_err(|e| format!("Failed to resolve plugin main file: {}", e))?;
+        if !canonical_main.starts_with(&canonical_dir) {
+            return Err(format!("Plugin main file is outside the plugin directory: {}", manifest.main));
+        }
+        
         Ok(manifest)
     }
 }
 
+/// Parses `major.minor.patch`, with missing parts as zero and any
+/// pre-release or build suffix ignored.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(|part| part.parse()).transpose().ok()?.unwrap_or(0);
+    let patch = parts.next().map(|part| part.parse()).transpose().ok()?.unwrap_or(0);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
 #[tauri::command]
 pub async fn install_plugin(manifest_path: String) -> Result<String, String> {
     let mut manager = PLUGIN_MANAGER.write().await;
@@ -370,9 +634,9 @@ pub async fn install_plugin(manifest_path: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn uninstall_plugin(plugin_id: String) -> Result<(), String> {
+pub async fn uninstall_plugin(plugin_id: String, cascade: Option<bool>) -> Result<(), String> {
     let mut manager = PLUGIN_MANAGER.write().await;
-    manager.uninstall_plugin(&plugin_id)
+    manager.uninstall_plugin(&plugin_id, cascade.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -382,9 +646,9 @@ pub async fn enable_plugin(plugin_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn disable_plugin(plugin_id: String) -> Result<(), String> {
+pub async fn disable_plugin(plugin_id: String, cascade: Option<bool>) -> Result<(), String> {
     let mut manager = PLUGIN_MANAGER.write().await;
-    manager.disable_plugin(&plugin_id)
+    manager.disable_plugin(&plugin_id, cascade.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -393,6 +657,12 @@ pub async fn get_plugin(plugin_id: String) -> Result<Option<Plugin>, String> {
     Ok(manager.get_plugin(&plugin_id).cloned())
 }
 
+#[tauri::command]
+pub async fn get_dependents(plugin_id: String) -> Result<Vec<Plugin>, String> {
+    let manager = PLUGIN_MANAGER.read().await;
+    Ok(manager.get_dependents(&plugin_id).into_iter().cloned().collect())
+}
+
 #[tauri::command]
 pub async fn get_all_plugins() -> Result<Vec<Plugin>, String> {
     let manager = PLUGIN_MANAGER.read().await;
@@ -417,10 +687,44 @@ pub async fn get_plugin_setting(plugin_id: String, key: String) -> Result<Option
     Ok(manager.get_plugin_setting(&plugin_id, &key).cloned())
 }
 
+/// Hands each plugin its `PluginEvent` under the plugin's event name and
+/// returns the ids `emit` accepted.
+fn dispatch_plugin_event<F>(plugin_ids: Vec<String>, hook: &PluginHook, data: &serde_json::Value, mut emit: F) -> Vec<String>
+where
+    F: FnMut(&str, PluginEvent) -> bool,
+{
+    let timestamp = chrono::Utc::now();
+    plugin_ids.into_iter()
+        .filter(|plugin_id| {
+            let event = PluginEvent {
+                plugin_id: plugin_id.clone(),
+                hook: hook.clone(),
+                data: data.clone(),
+                timestamp,
+            };
+            emit(&hook.event_name(plugin_id), event)
+        })
+        .collect()
+}
+
+/// Emits a `PluginEvent` to each eligible plugin and returns the ids it was delivered to.
 #[tauri::command]
-pub async fn trigger_plugin_event(hook: PluginHook, data: serde_json::Value) -> Result<Vec<String>, String> {
+pub async fn trigger_plugin_event(app: AppHandle, hook: PluginHook, data: serde_json::Value) -> Result<Vec<String>, String> {
     let mut manager = PLUGIN_MANAGER.write().await;
-    Ok(manager.trigger_event(hook, data))
+    let plugin_ids = manager.trigger_event(hook.clone(), data.clone());
+    
+    let dispatched = dispatch_plugin_event(plugin_ids, &hook, &data, |event_name, event| {
+        app.emit(event_name, event).is_ok()
+    });
+    
+    manager.record_dispatch(dispatched.len());
+    Ok(dispatched)
+}
+
+#[tauri::command]
+pub async fn get_denied_events(plugin_id: String) -> Result<Vec<DeniedPluginEvent>, String> {
+    let manager = PLUGIN_MANAGER.read().await;
+    Ok(manager.get_denied_events(&plugin_id).into_iter().cloned().collect())
 }
 
 #[tauri::command]
@@ -463,4 +767,286 @@ pub async fn get_plugin_stats() -> Result<PluginStats, String> {
 pub async fn validate_plugin_manifest(manifest_path: String) -> Result<PluginManifest, String> {
     let manager = PLUGIN_MANAGER.read().await;
     manager.validate_plugin_manifest(&manifest_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sw3do-plugins-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn manifest(name: &str, dependencies: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "version": "1.0.0",
+            "description": "",
+            "author": "",
+            "main": "main.js",
+            "permissions": [],
+            "hooks": [],
+            "settings": [],
+            "min_browser_version": "0.0.1",
+            "dependencies": dependencies,
+        })
+    }
+
+    fn write_manifest_json(dir: &std::path::Path, file_name: &str, manifest: &serde_json::Value) -> String {
+        std::fs::write(dir.join("main.js"), "").unwrap();
+        let path = dir.join(file_name);
+        std::fs::write(&path, manifest.to_string()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn write_manifest(dir: &std::path::Path, file_name: &str, name: &str, dependencies: &[&str]) -> String {
+        write_manifest_json(dir, file_name, &manifest(name, dependencies))
+    }
+
+    #[test]
+    fn duplicate_plugin_names_are_rejected() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        manager.install_plugin(&write_manifest(&dir, "a.json", "A", &[])).unwrap();
+        manager.install_plugin(&write_manifest(&dir, "b.json", "B", &["A"])).unwrap();
+        
+        let result = manager.install_plugin(&write_manifest(&dir, "a2.json", "A", &["B"]));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(result.unwrap_err().contains("already installed"));
+        assert_eq!(manager.plugins.len(), 2);
+    }
+
+    #[test]
+    fn missing_dependencies_are_rejected() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        
+        let missing = manager.install_plugin(&write_manifest(&dir, "b.json", "B", &["A"]));
+        let a = manager.install_plugin(&write_manifest(&dir, "a.json", "A", &[])).unwrap();
+        manager.disable_plugin(&a, false).unwrap();
+        let disabled = manager.install_plugin(&write_manifest(&dir, "b.json", "B", &["A"]));
+        manager.enable_plugin(&a).unwrap();
+        let installed = manager.install_plugin(&write_manifest(&dir, "b.json", "B", &["A"]));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(missing.unwrap_err(), "Missing dependency: A");
+        assert!(disabled.is_err());
+        let b = installed.unwrap();
+        assert!(manager.uninstall_plugin(&a, false).is_err());
+        assert_eq!(manager.get_dependents(&a)[0].id, b);
+    }
+
+    #[test]
+    fn cascading_uninstall_removes_every_dependent() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        let a = manager.install_plugin(&write_manifest(&dir, "a.json", "A", &[])).unwrap();
+        manager.install_plugin(&write_manifest(&dir, "b.json", "B", &["A"])).unwrap();
+        manager.install_plugin(&write_manifest(&dir, "c.json", "C", &["A", "B"])).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        manager.disable_plugin(&a, true).unwrap();
+        assert!(manager.plugins.values().all(|plugin| !plugin.enabled));
+        
+        manager.uninstall_plugin(&a, true).unwrap();
+        assert!(manager.plugins.is_empty());
+    }
+
+    #[test]
+    fn dependencies_are_triggered_before_dependents() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        let with_hook = |name: &str, dependencies: &[&str]| {
+            let mut manifest = manifest(name, dependencies);
+            manifest["permissions"] = serde_json::json!(["TabManagement"]);
+            manifest["hooks"] = serde_json::json!(["TabCreated"]);
+            manifest
+        };
+        let a = manager.install_plugin(&write_manifest_json(&dir, "a.json", &with_hook("A", &[]))).unwrap();
+        let b = manager.install_plugin(&write_manifest_json(&dir, "b.json", &with_hook("B", &["A"]))).unwrap();
+        let c = manager.install_plugin(&write_manifest_json(&dir, "c.json", &with_hook("C", &["B"]))).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        // Handlers run in subscription order unless dependencies say otherwise.
+        manager.event_handlers.insert(PluginHook::TabCreated, vec![c.clone(), a.clone(), b.clone()]);
+        
+        assert_eq!(manager.trigger_event(PluginHook::TabCreated, serde_json::Value::Null), vec![a, b, c]);
+    }
+
+    #[test]
+    fn manifest_main_must_stay_inside_plugin_directory() {
+        let root = temp_dir();
+        let dir = root.join("plugin");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(root.join("outside.js"), "").unwrap();
+        let mut manifest = manifest("A", &[]);
+        manifest["main"] = serde_json::json!("../outside.js");
+        let manifest_path = write_manifest_json(&dir, "manifest.json", &manifest);
+        
+        let result = PluginManager::new().validate_plugin_manifest(&manifest_path);
+        
+        let _ = std::fs::remove_dir_all(&root);
+        assert!(result.unwrap_err().contains("outside the plugin directory"));
+    }
+
+    #[test]
+    fn subscribers_without_the_hook_permission_are_denied() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        let mut allowed = manifest("Allowed", &[]);
+        allowed["permissions"] = serde_json::json!(["NetworkAccess"]);
+        allowed["hooks"] = serde_json::json!(["BeforeRequest"]);
+        let mut denied = manifest("Denied", &[]);
+        denied["permissions"] = serde_json::json!(["TabManagement"]);
+        denied["hooks"] = serde_json::json!(["BeforeRequest"]);
+        let allowed = manager.install_plugin(&write_manifest_json(&dir, "allowed.json", &allowed)).unwrap();
+        let denied = manager.install_plugin(&write_manifest_json(&dir, "denied.json", &denied)).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        let triggered = manager.trigger_event(PluginHook::BeforeRequest, serde_json::json!({ "url": "https://example.com/" }));
+        
+        assert_eq!(triggered, vec![allowed.clone()]);
+        assert!(manager.get_denied_events(&allowed).is_empty());
+        let denied_events = manager.get_denied_events(&denied);
+        assert_eq!(denied_events.len(), 1);
+        assert_eq!(denied_events[0].hook, PluginHook::BeforeRequest);
+        assert_eq!(denied_events[0].required_permission, PluginPermission::NetworkAccess);
+    }
+
+    #[test]
+    fn manifest_requiring_a_newer_browser_is_rejected() {
+        let dir = temp_dir();
+        let mut too_new = manifest("A", &[]);
+        too_new["min_browser_version"] = serde_json::json!("999.0.0");
+        let manifest_path = write_manifest_json(&dir, "manifest.json", &too_new);
+        
+        let result = PluginManager::new().validate_plugin_manifest(&manifest_path);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(result.unwrap_err().contains("requires browser version 999.0.0"));
+    }
+
+    #[test]
+    fn manifest_with_missing_main_is_rejected() {
+        let dir = temp_dir();
+        let mut missing_main = manifest("A", &[]);
+        missing_main["main"] = serde_json::json!("index.js");
+        let manifest_path = write_manifest_json(&dir, "manifest.json", &missing_main);
+        
+        let result = PluginManager::new().validate_plugin_manifest(&manifest_path);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(result.unwrap_err().contains("main file not found"));
+    }
+
+    #[test]
+    fn valid_manifest_is_accepted() {
+        let dir = temp_dir();
+        let mut valid = manifest("A", &[]);
+        valid["min_browser_version"] = serde_json::json!(crate::BROWSER_VERSION);
+        valid["permissions"] = serde_json::json!(["NetworkAccess", "TabManagement"]);
+        let manifest_path = write_manifest_json(&dir, "manifest.json", &valid);
+        let mut duplicated = valid.clone();
+        duplicated["permissions"] = serde_json::json!(["NetworkAccess", "NetworkAccess"]);
+        let duplicated_path = write_manifest_json(&dir, "duplicated.json", &duplicated);
+        
+        let manager = PluginManager::new();
+        let result = manager.validate_plugin_manifest(&manifest_path);
+        let duplicated = manager.validate_plugin_manifest(&duplicated_path);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+        let manifest = result.unwrap();
+        assert_eq!(manifest.name, "A");
+        assert_eq!(manifest.permissions, vec![PluginPermission::NetworkAccess, PluginPermission::TabManagement]);
+        assert!(duplicated.unwrap_err().contains("Duplicate permission"));
+    }
+
+    fn install_with_setting(manager: &mut PluginManager) -> String {
+        let dir = temp_dir();
+        let mut with_setting = manifest("A", &[]);
+        with_setting["settings"] = serde_json::json!([{
+            "key": "enabled",
+            "setting_type": "Boolean",
+            "description": "",
+            "default_value": false,
+            "required": false,
+        }]);
+        let plugin_id = manager.install_plugin(&write_manifest_json(&dir, "manifest.json", &with_setting)).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        plugin_id
+    }
+
+    #[test]
+    fn setting_updates_must_match_the_setting_type() {
+        let mut manager = PluginManager::new();
+        let plugin_id = install_with_setting(&mut manager);
+        
+        let error = manager.update_plugin_setting(&plugin_id, "enabled", serde_json::json!("yes")).unwrap_err();
+        assert!(error.contains("Expected a Boolean value"));
+        assert_eq!(manager.get_plugin_setting(&plugin_id, "enabled").unwrap().value, serde_json::json!(false));
+        
+        manager.update_plugin_setting(&plugin_id, "enabled", serde_json::json!(true)).unwrap();
+        assert_eq!(manager.get_plugin_setting(&plugin_id, "enabled").unwrap().value, serde_json::json!(true));
+    }
+
+    #[test]
+    fn imported_settings_must_match_the_setting_type() {
+        let mut manager = PluginManager::new();
+        let plugin_id = install_with_setting(&mut manager);
+        let mut settings = manager.plugins[&plugin_id].settings.clone();
+        
+        settings.get_mut("enabled").unwrap().value = serde_json::json!("yes");
+        let invalid = serde_json::to_string(&settings).unwrap();
+        assert!(manager.import_plugin_settings(&plugin_id, &invalid).is_err());
+        assert_eq!(manager.get_plugin_setting(&plugin_id, "enabled").unwrap().value, serde_json::json!(false));
+        
+        settings.get_mut("enabled").unwrap().value = serde_json::json!(true);
+        let valid = serde_json::to_string(&settings).unwrap();
+        manager.import_plugin_settings(&plugin_id, &valid).unwrap();
+        assert_eq!(manager.get_plugin_setting(&plugin_id, "enabled").unwrap().value, serde_json::json!(true));
+    }
+
+    #[test]
+    fn events_are_emitted_once_per_eligible_plugin() {
+        let dir = temp_dir();
+        let mut manager = PluginManager::new();
+        let mut ids = Vec::new();
+        for (file, name, permission) in [("a.json", "A", "NetworkAccess"), ("b.json", "B", "NetworkAccess"), ("c.json", "C", "TabManagement")] {
+            let mut plugin = manifest(name, &[]);
+            plugin["permissions"] = serde_json::json!([permission]);
+            plugin["hooks"] = serde_json::json!(["BeforeRequest"]);
+            ids.push(manager.install_plugin(&write_manifest_json(&dir, file, &plugin)).unwrap());
+        }
+        manager.disable_plugin(&ids[1], false).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        let data = serde_json::json!({ "url": "https://example.com/" });
+        let plugin_ids = manager.trigger_event(PluginHook::BeforeRequest, data.clone());
+        let mut emitted = Vec::new();
+        let dispatched = dispatch_plugin_event(plugin_ids, &PluginHook::BeforeRequest, &data, |event_name, event| {
+            emitted.push((event_name.to_string(), event));
+            true
+        });
+        manager.record_dispatch(dispatched.len());
+        
+        assert_eq!(dispatched, vec![ids[0].clone()]);
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0].0, PluginHook::BeforeRequest.event_name(&ids[0]));
+        assert_eq!(emitted[0].1.plugin_id, ids[0]);
+        assert_eq!(emitted[0].1.data, data);
+        assert_eq!(manager.stats.events_dispatched, 1);
+    }
+
+    #[test]
+    fn failed_emits_are_not_counted_as_dispatched() {
+        let plugin_ids = vec!["a".to_string(), "b".to_string()];
+        let dispatched = dispatch_plugin_event(plugin_ids, &PluginHook::TabCreated, &serde_json::Value::Null, |event_name, _| {
+            event_name.starts_with("plugin://b/")
+        });
+        
+        assert_eq!(dispatched, vec!["b".to_string()]);
+    }
 }
\ No newline at end of file
@@ -1,238 +1,155 @@
-use serde::{Deserialize, Serialize};
-use tauri::State;
-use std::collections::HashMap;
-use std::sync::Mutex;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FilterList {
-    pub id: String,
-    pub name: String,
-    pub url: String,
-    pub enabled: bool,
-    pub last_updated: String,
-    pub rules_count: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SiteShields {
-    pub domain: String,
-    pub ads_blocked: bool,
-    pub trackers_blocked: bool,
-    pub scripts_blocked: bool,
-    pub fingerprinting_blocked: bool,
-    pub https_upgrade: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BlockingStats {
-    pub total_ads_blocked: u64,
-    pub total_trackers_blocked: u64,
-    pub total_scripts_blocked: u64,
-    pub bandwidth_saved: u64,
-    pub time_saved: u64,
-    pub last_reset: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PrivacySettings {
-    pub ad_blocking_enabled: bool,
-    pub tracker_blocking_enabled: bool,
-    pub script_blocking_enabled: bool,
-    pub fingerprinting_protection: bool,
-    pub https_everywhere: bool,
-    pub clear_data_on_exit: bool,
-    pub send_do_not_track: bool,
-    pub block_third_party_cookies: bool,
-}
-
-type PrivacyState = Mutex<HashMap<String, String>>;
-
-#[tauri::command]
-pub async fn load_filter_lists(state: State<'_, PrivacyState>) -> Result<Vec<FilterList>, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let default_lists = vec![
-        FilterList {
-            id: "easylist".to_string(),
-            name: "EasyList".to_string(),
-            url: "https://easylist.to/easylist/easylist.txt".to_string(),
-            enabled: true,
-            last_updated: chrono::Utc::now().to_rfc3339(),
-            rules_count: 0,
-        },
-        FilterList {
-            id: "easyprivacy".to_string(),
-            name: "EasyPrivacy".to_string(),
-            url: "https://easylist.to/easylist/easyprivacy.txt".to_string(),
-            enabled: true,
-            last_updated: chrono::Utc::now().to_rfc3339(),
-            rules_count: 0,
-        },
-    ];
-    
-    Ok(default_lists)
+use super::filters::{self, FilterListSummary, GlobalStats, PrivacyData, SiteShields, FILTER_ENGINE};
+use super::settings::{self, PrivacySettings};
+
+#[tauri::command]
+pub async fn load_filter_lists() -> Result<Vec<FilterListSummary>, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.filter_list_summaries())
 }
 
 #[tauri::command]
-pub async fn add_filter_list(url: String, state: State<'_, PrivacyState>) -> Result<FilterList, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let filter_list = FilterList {
-        id: uuid::Uuid::new_v4().to_string(),
-        name: "Custom Filter List".to_string(),
-        url,
-        enabled: true,
-        last_updated: chrono::Utc::now().to_rfc3339(),
-        rules_count: 0,
-    };
-    
-    Ok(filter_list)
+pub async fn add_filter_list(url: String) -> Result<FilterListSummary, String> {
+    let download = filters::fetch_filter_list(&url, None, None)
+        .await?
+        .ok_or_else(|| "Filter list server returned no content".to_string())?;
+    let mut engine = FILTER_ENGINE.write().await;
+    let summary = engine.add_filter_list(&url, download)?;
+    engine.save_filter_lists()?;
+    Ok(summary)
 }
 
 #[tauri::command]
-pub async fn remove_filter_list(list_id: String, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn remove_filter_list(list_id: String) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.remove_filter_list(&list_id)?;
+    engine.save_filter_lists()
 }
 
 #[tauri::command]
-pub async fn toggle_filter_list(list_id: String, enabled: bool, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn toggle_filter_list(list_id: String, enabled: bool) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.set_filter_list_enabled(&list_id, enabled)?;
+    engine.save_filter_lists()
 }
 
+/// Downloads are done without holding the engine lock, so requests keep
+/// being filtered while a list refreshes.
 #[tauri::command]
-pub async fn update_filter_list(list_id: String, state: State<'_, PrivacyState>) -> Result<FilterList, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let filter_list = FilterList {
-        id: list_id,
-        name: "Updated Filter List".to_string(),
-        url: "https://example.com/filter.txt".to_string(),
-        enabled: true,
-        last_updated: chrono::Utc::now().to_rfc3339(),
-        rules_count: 100,
-    };
+pub async fn update_filter_list(list_id: String) -> Result<FilterListSummary, String> {
+    let source = FILTER_ENGINE.read().await.filter_list_source(&list_id)?;
+    let result = filters::fetch_filter_list(&source.url, source.etag.as_deref(), source.last_modified.as_deref()).await;
     
-    Ok(filter_list)
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.apply_filter_list_fetches(vec![(list_id.clone(), result)]);
+    let summary = engine.filter_list_summary(&list_id)?;
+    match summary.last_update_error {
+        Some(e) => Err(e),
+        None => Ok(summary),
+    }
 }
 
 #[tauri::command]
-pub async fn load_site_shields(domain: String, state: State<'_, PrivacyState>) -> Result<SiteShields, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let shields = SiteShields {
-        domain,
-        ads_blocked: true,
-        trackers_blocked: true,
-        scripts_blocked: false,
-        fingerprinting_blocked: true,
-        https_upgrade: true,
-    };
-    
-    Ok(shields)
+pub async fn load_site_shields(domain: String) -> Result<SiteShields, String> {
+    filters::get_site_shields(domain).await
 }
 
 #[tauri::command]
-pub async fn update_site_shields_privacy(domain: String, shields: SiteShields, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn update_site_shields_privacy(domain: String, shields: SiteShields) -> Result<(), String> {
+    filters::update_site_shields(domain, shields).await
 }
 
 #[tauri::command]
-pub async fn reset_site_shields(domain: String, state: State<'_, PrivacyState>) -> Result<SiteShields, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let shields = SiteShields {
-        domain,
-        ads_blocked: true,
-        trackers_blocked: true,
-        scripts_blocked: false,
-        fingerprinting_blocked: true,
-        https_upgrade: true,
-    };
-    
+pub async fn reset_site_shields(domain: String) -> Result<SiteShields, String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    let shields = engine.reset_site_shields(&domain);
+    engine.save_shield_stats()?;
     Ok(shields)
 }
 
 #[tauri::command]
-pub async fn load_blocking_stats(state: State<'_, PrivacyState>) -> Result<BlockingStats, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let stats = BlockingStats {
-        total_ads_blocked: 1234,
-        total_trackers_blocked: 567,
-        total_scripts_blocked: 89,
-        bandwidth_saved: 1024 * 1024 * 50,
-        time_saved: 3600,
-        last_reset: chrono::Utc::now().to_rfc3339(),
-    };
-    
-    Ok(stats)
+pub async fn load_blocking_stats() -> Result<GlobalStats, String> {
+    filters::get_global_stats().await
 }
 
 #[tauri::command]
-pub async fn reset_blocking_stats(state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn reset_blocking_stats() -> Result<(), String> {
+    filters::reset_shield_stats().await.map(|_| ())
 }
 
 #[tauri::command]
-pub async fn load_privacy_settings(state: State<'_, PrivacyState>) -> Result<PrivacySettings, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    
-    let settings = PrivacySettings {
-        ad_blocking_enabled: true,
-        tracker_blocking_enabled: true,
-        script_blocking_enabled: false,
-        fingerprinting_protection: true,
-        https_everywhere: true,
-        clear_data_on_exit: false,
-        send_do_not_track: true,
-        block_third_party_cookies: true,
-    };
-    
-    Ok(settings)
+pub async fn load_privacy_settings() -> Result<PrivacySettings, String> {
+    Ok(settings::current_settings().await.privacy)
 }
 
 #[tauri::command]
-pub async fn update_privacy_settings_privacy(settings: PrivacySettings, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn update_privacy_settings_privacy(settings: serde_json::Value) -> Result<(), String> {
+    settings::update_privacy_settings(settings).await
 }
 
+/// Checks `url` as a top-level request, so rules limited to third parties do not apply.
 #[tauri::command]
-pub async fn check_url(url: String, state: State<'_, PrivacyState>) -> Result<bool, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(false)
+pub async fn check_url(url: String) -> Result<bool, String> {
+    let host = super::network::url_host(&url).ok_or_else(|| format!("Invalid URL: {}", url))?;
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.should_block_request(&url, "document", &host))
 }
 
 #[tauri::command]
-pub async fn add_custom_rule(rule: String, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn add_custom_rule(rule: String) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.add_custom_rule(&rule)?;
+    engine.save_custom_rules()
 }
 
 #[tauri::command]
-pub async fn remove_custom_rule(rule: String, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn remove_custom_rule(rule: String) -> Result<(), String> {
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.remove_custom_rule(&rule)?;
+    engine.save_custom_rules()
 }
 
 #[tauri::command]
-pub async fn get_custom_rules(state: State<'_, PrivacyState>) -> Result<Vec<String>, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(vec![])
+pub async fn get_custom_rules() -> Result<Vec<String>, String> {
+    let engine = FILTER_ENGINE.read().await;
+    Ok(engine.custom_rules.clone())
 }
 
 #[tauri::command]
-pub async fn export_privacy_data(state: State<'_, PrivacyState>) -> Result<String, String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok("{}".to_string())
+pub async fn export_privacy_data() -> Result<String, String> {
+    let engine = FILTER_ENGINE.read().await;
+    serde_json::to_string_pretty(&engine.export_privacy_data())
+        .map_err(|e| format!("Failed to export privacy data: {}", e))
 }
 
 #[tauri::command]
-pub async fn import_privacy_data(data: String, state: State<'_, PrivacyState>) -> Result<(), String> {
-    let _guard = state.lock().map_err(|e| e.to_string())?;
-    Ok(())
+pub async fn import_privacy_data(data: String) -> Result<(), String> {
+    let data: PrivacyData = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid privacy data: {}", e))?;
+    
+    let mut engine = FILTER_ENGINE.write().await;
+    engine.import_privacy_data(data)?;
+    engine.save_shield_stats()?;
+    engine.save_custom_rules()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn blocking_stats_reflect_blocked_requests() {
+        let before = load_blocking_stats().await.unwrap();
+        
+        {
+            let mut engine = FILTER_ENGINE.write().await;
+            engine.increment_blocked_count("news.example", "ad", None);
+            engine.increment_blocked_count("news.example", "tracker", Some("tracker.example"));
+            engine.increment_blocked_count("news.example", "tracker", Some("tracker.example"));
+        }
+        let after = load_blocking_stats().await.unwrap();
+        
+        assert_eq!(after.total_ads_blocked, before.total_ads_blocked + 1);
+        assert_eq!(after.total_trackers_blocked, before.total_trackers_blocked + 2);
+        assert_eq!(after.total_scripts_blocked, before.total_scripts_blocked);
+    }
 }
\ No newline at end of file
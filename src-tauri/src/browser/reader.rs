@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use once_cell::sync::Lazy;
+
+const WORDS_PER_MINUTE: usize = 200;
+const MIN_PARAGRAPH_CHARS: usize = 25;
+const MAX_LINK_DENSITY: f64 = 0.5;
+
+/// Elements that never hold article text. The regex crate has no
+/// backreferences, so each tag gets its own pattern.
+static NOISE_REGEXES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ["script", "style", "noscript", "template", "svg", "iframe", "form", "nav", "header", "footer", "aside"]
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>", tag = tag)).unwrap())
+        .collect()
+});
+
+static COMMENT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<!--.*?-->").unwrap()
+});
+
+/// Content containers from most to least specific.
+static CONTAINER_REGEXES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    ["article", "main", "body"]
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>(.*?)</{tag}\s*>", tag = tag)).unwrap())
+        .collect()
+});
+
+static BLOCK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<(h[1-6]|p|pre|blockquote|li)\b[^>]*>(.*?)</(?:h[1-6]|p|pre|blockquote|li)\s*>").unwrap()
+});
+
+static LINK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<a\b[^>]*>(.*?)</a\s*>").unwrap()
+});
+
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<[^>]*>").unwrap()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadablePage {
+    pub url: String,
+    pub title: Option<String>,
+    /// Simplified HTML made of headings, paragraphs, quotes and list items only.
+    pub content: String,
+    pub excerpt: Option<String>,
+    pub word_count: usize,
+    pub reading_time_minutes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct ReadableBlock {
+    tag: String,
+    text: String,
+}
+
+fn strip_noise(html: &str) -> String {
+    let mut cleaned = COMMENT_REGEX.replace_all(html, "").into_owned();
+    for regex in NOISE_REGEXES.iter() {
+        cleaned = regex.replace_all(&cleaned, "").into_owned();
+    }
+    cleaned
+}
+
+fn block_text(html: &str) -> String {
+    super::metadata::decode_entities(&TAG_REGEX.replace_all(html, " "))
+}
+
+/// Readable blocks from `html`: headings always, other blocks only when they
+/// are long enough and not mostly link text (menus, tag clouds, share bars).
+fn readable_blocks(html: &str) -> Vec<ReadableBlock> {
+    BLOCK_REGEX.captures_iter(html)
+        .filter_map(|captures| {
+            let tag = captures[1].to_lowercase();
+            let text = block_text(&captures[2]);
+            if text.is_empty() {
+                return None;
+            }
+            
+            if !tag.starts_with('h') {
+                let link_chars: usize = LINK_REGEX.captures_iter(&captures[2])
+                    .map(|link| block_text(&link[1]).chars().count())
+                    .sum();
+                let text_chars = text.chars().count();
+                if text_chars < MIN_PARAGRAPH_CHARS || link_chars as f64 / text_chars as f64 > MAX_LINK_DENSITY {
+                    return None;
+                }
+            }
+            
+            Some(ReadableBlock { tag, text })
+        })
+        .collect()
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn text_length(blocks: &[ReadableBlock]) -> usize {
+    blocks.iter().map(|block| block.text.len()).sum()
+}
+
+/// Blocks of the page's main text. `<article>` elements are tried first,
+/// then `<main>`, then `<body>`; within a kind the one with the most readable
+/// text wins. The whole document is the fallback when none yield any text.
+fn main_blocks(html: &str) -> Vec<ReadableBlock> {
+    let cleaned = strip_noise(html);
+    
+    CONTAINER_REGEXES.iter()
+        .find_map(|regex| {
+            regex.captures_iter(&cleaned)
+                .map(|captures| readable_blocks(&captures[1]))
+                .max_by_key(|blocks| text_length(blocks))
+                .filter(|blocks| !blocks.is_empty())
+        })
+        .unwrap_or_else(|| readable_blocks(&cleaned))
+}
+
+/// Plain text of each main-content block, in document order.
+pub fn readable_text_blocks(html: &str) -> Vec<String> {
+    main_blocks(html).into_iter().map(|block| block.text).collect()
+}
+
+pub fn extract_readable_page(page_url: &str, html: &str) -> ReadablePage {
+    let blocks = main_blocks(html);
+    
+    let content = blocks.iter()
+        .map(|block| {
+            let tag = if block.tag == "li" { "p" } else { block.tag.as_str() };
+            format!("<{tag}>{}</{tag}>", escape_html(&block.text), tag = tag)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    
+    let word_count: usize = blocks.iter()
+        .map(|block| block.text.split_whitespace().count())
+        .sum();
+    
+    ReadablePage {
+        url: page_url.to_string(),
+        title: super::metadata::parse_page_metadata(page_url, html).title,
+        content,
+        excerpt: blocks.iter()
+            .find(|block| block.tag == "p")
+            .map(|block| block.text.clone()),
+        word_count,
+        reading_time_minutes: word_count.div_ceil(WORDS_PER_MINUTE),
+    }
+}
+
+pub async fn fetch_readable_page_content(url: &str) -> Result<ReadablePage, String> {
+    let client = super::network::http_client().await?;
+    let response = super::network::send_request(client.get(url), "fetch page").await?;
+    
+    let final_url = response.url().to_string();
+    super::security::record_response_headers(&final_url, response.headers()).await;
+    let html = response.text()
+        .await
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+    
+    Ok(extract_readable_page(&final_url, &html))
+}
+
+#[tauri::command]
+pub async fn fetch_readable_page(url: String) -> Result<ReadablePage, String> {
+    fetch_readable_page_content(&url).await
+}
\ No newline at end of file
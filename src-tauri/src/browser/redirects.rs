@@ -0,0 +1,179 @@
+use url::Url;
+
+const MAX_UNWRAP_DEPTH: usize = 5;
+
+/// A redirect wrapper: links on `host` (or its subdomains) whose path starts
+/// with `path_prefix` carry the real destination in one of `params`.
+struct RedirectWrapper {
+    host: &'static str,
+    path_prefix: &'static str,
+    params: &'static [&'static str],
+}
+
+const REDIRECT_WRAPPERS: &[RedirectWrapper] = &[
+    RedirectWrapper { host: "l.facebook.com", path_prefix: "/l.php", params: &["u"] },
+    RedirectWrapper { host: "lm.facebook.com", path_prefix: "/l.php", params: &["u"] },
+    RedirectWrapper { host: "l.messenger.com", path_prefix: "/l.php", params: &["u"] },
+    RedirectWrapper { host: "l.instagram.com", path_prefix: "/", params: &["u"] },
+    RedirectWrapper { host: "out.reddit.com", path_prefix: "/", params: &["url"] },
+    RedirectWrapper { host: "youtube.com", path_prefix: "/redirect", params: &["q"] },
+    RedirectWrapper { host: "duckduckgo.com", path_prefix: "/l/", params: &["uddg"] },
+    RedirectWrapper { host: "steamcommunity.com", path_prefix: "/linkfilter", params: &["url", "u"] },
+    RedirectWrapper { host: "t.umblr.com", path_prefix: "/redirect", params: &["z"] },
+    RedirectWrapper { host: "away.vk.com", path_prefix: "/away.php", params: &["to"] },
+    RedirectWrapper { host: "slack-redir.net", path_prefix: "/link", params: &["url"] },
+];
+
+/// True for `google.com`, `google.<cc>`, `google.co.<cc>` and `google.com.<cc>`,
+/// with or without `www.`.
+fn is_google_host(host: &str) -> bool {
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let Some(suffix) = host.strip_prefix("google.") else {
+        return false;
+    };
+    let is_country_code = |label: &str| label.len() == 2 && label.bytes().all(|byte| byte.is_ascii_lowercase());
+    
+    match suffix.split_once('.') {
+        None => suffix == "com" || is_country_code(suffix),
+        Some((second_level, country)) => matches!(second_level, "co" | "com") && is_country_code(country),
+    }
+}
+
+fn wrapped_destination(url: &Url) -> Option<String> {
+    let host = url.host_str()?.to_lowercase();
+    let path = url.path();
+    
+    let params: &[&str] = if is_google_host(&host) && path == "/url" {
+        &["q", "url"]
+    } else {
+        REDIRECT_WRAPPERS.iter()
+            .find(|wrapper| super::network::host_matches_domain(&host, wrapper.host) && path.starts_with(wrapper.path_prefix))
+            .map(|wrapper| wrapper.params)?
+    };
+    
+    params.iter()
+        .find_map(|param| url.query_pairs().find(|(key, _)| key == param).map(|(_, value)| value.into_owned()))
+        .filter(|destination| destination.starts_with("http://") || destination.starts_with("https://"))
+}
+
+/// Replaces a known redirect-tracking link with the destination it wraps,
+/// following nested wrappers a few levels deep. Anything else is returned as is.
+pub fn unwrap_tracking_url(url: &str) -> String {
+    let mut current = url.to_string();
+    
+    for _ in 0..MAX_UNWRAP_DEPTH {
+        let Ok(parsed) = Url::parse(&current) else {
+            break;
+        };
+        
+        match wrapped_destination(&parsed) {
+            Some(destination) if Url::parse(&destination).is_ok() => current = destination,
+            _ => break,
+        }
+    }
+    
+    current
+}
+
+fn is_tracking_param(name: &str, tracking_params: &[String]) -> bool {
+    let name = name.to_lowercase();
+    tracking_params.iter().any(|param| {
+        let param = param.to_lowercase();
+        match param.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == param,
+        }
+    })
+}
+
+/// Drops query parameters named in `tracking_params` from an http(s) URL.
+/// The remaining parameters are kept byte for byte and in their original order.
+pub fn strip_tracking_params(url: &str, tracking_params: &[String]) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return url.to_string();
+    }
+    let Some(query) = parsed.query() else {
+        return url.to_string();
+    };
+    
+    let kept: Vec<&str> = query.split('&')
+        .filter(|pair| {
+            let name = pair.split('=').next().unwrap_or_default();
+            let name = urlencoding::decode(name).map(|name| name.into_owned()).unwrap_or_else(|_| name.to_string());
+            !is_tracking_param(&name, tracking_params)
+        })
+        .collect();
+    
+    if kept.len() == query.split('&').count() {
+        return url.to_string();
+    }
+    
+    let kept = kept.join("&");
+    parsed.set_query(if kept.is_empty() { None } else { Some(&kept) });
+    parsed.to_string()
+}
+
+/// The URL actually navigated to: redirect wrappers unwrapped and, when the
+/// privacy setting is on, tracking parameters removed.
+pub async fn clean_navigation_url(url: &str) -> String {
+    let url = unwrap_tracking_url(url);
+    let privacy = super::settings::current_settings().await.privacy;
+    
+    if privacy.strip_tracking_params {
+        strip_tracking_params(&url, &privacy.tracking_params)
+    } else {
+        url
+    }
+}
+
+#[tauri::command]
+pub async fn unwrap_tracking_link(url: String) -> Result<String, String> {
+    Ok(unwrap_tracking_url(&url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_google_domains_count_as_google() {
+        for host in ["google.com", "www.google.de", "google.co.uk", "google.com.br"] {
+            assert!(is_google_host(host), "{}", host);
+        }
+        for host in ["google.evil.com", "google.example", "google.co.evil.com", "notgoogle.com"] {
+            assert!(!is_google_host(host), "{}", host);
+        }
+    }
+
+    #[test]
+    fn google_redirects_on_other_hosts_are_left_alone() {
+        let url = "https://google.evil.com/url?q=https://example.com/";
+        assert_eq!(unwrap_tracking_url(url), url);
+        assert_eq!(unwrap_tracking_url("https://www.google.co.uk/url?q=https://example.com/"), "https://example.com/");
+    }
+
+    #[test]
+    fn google_and_facebook_wrappers_are_unwrapped() {
+        assert_eq!(
+            unwrap_tracking_url("https://www.google.com/url?sa=t&q=https%3A%2F%2Fexample.com%2Fpage%3Fid%3D1&usg=abc"),
+            "https://example.com/page?id=1"
+        );
+        assert_eq!(
+            unwrap_tracking_url("https://l.facebook.com/l.php?u=https%3A%2F%2Fnews.example%2Fstory&h=AT0"),
+            "https://news.example/story"
+        );
+        
+        let nested = format!(
+            "https://l.facebook.com/l.php?u={}",
+            urlencoding::encode("https://www.google.com/url?q=https://example.com/")
+        );
+        assert_eq!(unwrap_tracking_url(&nested), "https://example.com/");
+        
+        let unsafe_target = "https://l.facebook.com/l.php?u=javascript%3Aalert(1)";
+        assert_eq!(unwrap_tracking_url(unsafe_target), unsafe_target);
+        assert_eq!(unwrap_tracking_url("https://example.com/l.php?u=https://a.example/"), "https://example.com/l.php?u=https://a.example/");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::filters::{GlobalStats, SiteShields};
+use super::history::HistoryEntry;
+
+const TOP_DOMAINS_LIMIT: usize = 10;
+const ESTIMATED_MS_SAVED_PER_BLOCK: u64 = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum StatsRange {
+    Today,
+    Week,
+    Month,
+    All,
+}
+
+impl StatsRange {
+    pub fn since(&self, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            StatsRange::Today => now.date_naive().and_hms_opt(0, 0, 0).map(|start| start.and_utc()),
+            StatsRange::Week => Some(now - chrono::Duration::days(7)),
+            StatsRange::Month => Some(now - chrono::Duration::days(30)),
+            StatsRange::All => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainVisits {
+    pub domain: String,
+    pub visits: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedDomain {
+    pub domain: String,
+    pub ads_blocked: u64,
+    pub trackers_blocked: u64,
+    pub scripts_blocked: u64,
+    pub total_blocked: u64,
+}
+
+/// History keeps one visit count per page, not a timestamp per visit, so
+/// visit counts are lifetime totals of the pages last visited in the range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryInsights {
+    pub lifetime_visits: u64,
+    pub unique_sites: u64,
+    pub top_domains: Vec<DomainVisits>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub range: StatsRange,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub global_stats: GlobalStats,
+    pub estimated_time_saved_secs: u64,
+    pub history: HistoryInsights,
+    pub top_blocked_domains: Vec<BlockedDomain>,
+}
+
+/// Builds the report from raw data. History entries and site shields are
+/// filtered by their timestamps; the global counters are lifetime totals
+/// since `last_reset` because they are not recorded per event, and so are
+/// the visit counts of the pages in range.
+pub fn build_stats_report(
+    range: StatsRange,
+    now: chrono::DateTime<chrono::Utc>,
+    global_stats: GlobalStats,
+    shields: &[SiteShields],
+    history: &[HistoryEntry],
+) -> StatsReport {
+    let since = range.since(now);
+    let in_range = |time: chrono::DateTime<chrono::Utc>| since.is_none_or(|since| time >= since);
+    
+    let mut domain_visits: HashMap<String, u64> = HashMap::new();
+    let mut lifetime_visits = 0;
+    
+    for entry in history.iter().filter(|entry| !entry.is_private && in_range(entry.last_visit)) {
+        lifetime_visits += entry.visit_count as u64;
+        if let Some(domain) = super::network::url_host(&entry.url) {
+            *domain_visits.entry(domain).or_insert(0) += entry.visit_count as u64;
+        }
+    }
+    
+    let unique_sites = domain_visits.len() as u64;
+    let mut top_domains: Vec<DomainVisits> = domain_visits
+        .into_iter()
+        .map(|(domain, visits)| DomainVisits { domain, visits })
+        .collect();
+    top_domains.sort_by(|a, b| b.visits.cmp(&a.visits).then_with(|| a.domain.cmp(&b.domain)));
+    top_domains.truncate(TOP_DOMAINS_LIMIT);
+    
+    let mut top_blocked_domains: Vec<BlockedDomain> = shields
+        .iter()
+        .filter(|shields| in_range(shields.last_updated))
+        .map(|shields| {
+            let ads_blocked = shields.ads_blocked as u64;
+            let trackers_blocked = shields.trackers_blocked as u64;
+            let scripts_blocked = shields.scripts_blocked as u64;
+            BlockedDomain {
+                domain: shields.domain.clone(),
+                ads_blocked,
+                trackers_blocked,
+                scripts_blocked,
+                total_blocked: ads_blocked + trackers_blocked + scripts_blocked,
+            }
+        })
+        .filter(|domain| domain.total_blocked > 0)
+        .collect();
+    top_blocked_domains.sort_by(|a, b| b.total_blocked.cmp(&a.total_blocked).then_with(|| a.domain.cmp(&b.domain)));
+    top_blocked_domains.truncate(TOP_DOMAINS_LIMIT);
+    
+    let total_blocked = global_stats.total_ads_blocked
+        + global_stats.total_trackers_blocked
+        + global_stats.total_scripts_blocked;
+    
+    StatsReport {
+        range,
+        since,
+        generated_at: now,
+        estimated_time_saved_secs: total_blocked * ESTIMATED_MS_SAVED_PER_BLOCK / 1000,
+        global_stats,
+        history: HistoryInsights {
+            lifetime_visits,
+            unique_sites,
+            top_domains,
+        },
+        top_blocked_domains,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Flattens the report into `section,key,value` rows.
+pub fn stats_report_to_csv(report: &StatsReport) -> String {
+    let mut rows = vec!["section,key,value".to_string()];
+    let mut push = |section: &str, key: &str, value: String| {
+        rows.push(format!("{},{},{}", section, csv_field(key), csv_field(&value)));
+    };
+    
+    push("report", "range", format!("{:?}", report.range));
+    push("report", "since", report.since.map(|since| since.to_rfc3339()).unwrap_or_default());
+    push("report", "generated_at", report.generated_at.to_rfc3339());
+    push("blocking", "ads_blocked", report.global_stats.total_ads_blocked.to_string());
+    push("blocking", "trackers_blocked", report.global_stats.total_trackers_blocked.to_string());
+    push("blocking", "scripts_blocked", report.global_stats.total_scripts_blocked.to_string());
+    push("blocking", "bandwidth_saved", report.global_stats.bandwidth_saved.to_string());
+    push("blocking", "time_saved_secs", report.estimated_time_saved_secs.to_string());
+    push("history", "lifetime_visits", report.history.lifetime_visits.to_string());
+    push("history", "unique_sites", report.history.unique_sites.to_string());
+    
+    for domain in &report.history.top_domains {
+        push("top_visited", &domain.domain, domain.visits.to_string());
+    }
+    
+    for domain in &report.top_blocked_domains {
+        push("top_blocked", &domain.domain, domain.total_blocked.to_string());
+    }
+    
+    rows.join("\n")
+}
+
+pub async fn generate_report(range: StatsRange) -> StatsReport {
+    let (global_stats, shields) = super::filters::blocking_snapshot().await;
+    let history = super::history::history_snapshot().await;
+    build_stats_report(range, chrono::Utc::now(), global_stats, &shields, &history)
+}
+
+#[tauri::command]
+pub async fn generate_stats_report(range: StatsRange) -> Result<StatsReport, String> {
+    Ok(generate_report(range).await)
+}
+
+#[tauri::command]
+pub async fn export_stats_report(range: StatsRange, format: String) -> Result<String, String> {
+    let report = generate_report(range).await;
+    
+    match format.as_str() {
+        "json" => serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to export stats report: {}", e)),
+        "csv" => Ok(stats_report_to_csv(&report)),
+        _ => Err(format!("Unsupported report format: {}", format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visit_counts_are_lifetime_totals_of_pages_in_range() {
+        let now = chrono::Utc::now();
+        let entry = |url: &str, visit_count: u32, days_ago: i64| HistoryEntry {
+            id: url.to_string(),
+            url: url.to_string(),
+            title: String::new(),
+            visit_time: now - chrono::Duration::days(400),
+            visit_count,
+            last_visit: now - chrono::Duration::days(days_ago),
+            favicon: None,
+            is_private: false,
+        };
+        let history = [entry("https://a.example/", 50, 1), entry("https://b.example/", 7, 20)];
+        
+        let report = build_stats_report(StatsRange::Week, now, GlobalStats::default(), &[], &history);
+        
+        assert_eq!(report.history.lifetime_visits, 50);
+        assert_eq!(report.history.unique_sites, 1);
+    }
+}
\ No newline at end of file
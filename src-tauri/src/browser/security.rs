@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use url::Url;
+use tokio::sync::RwLock;
+use once_cell::sync::Lazy;
+
+const INTERNAL_SCHEMES: &[&str] = &["sw3do", "about", "tauri"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SecurityState {
+    Secure,
+    MixedContent,
+    Insecure,
+    Local,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityInfo {
+    pub url: String,
+    pub scheme: String,
+    pub state: SecurityState,
+    pub is_secure: bool,
+    pub mixed_content: bool,
+    pub certificate_issuer: Option<String>,
+    pub hsts: bool,
+}
+
+static SECURITY_STORE: Lazy<RwLock<SecurityStore>> = Lazy::new(|| {
+    RwLock::new(SecurityStore::new())
+});
+
+/// What has been observed about loaded pages: HSTS hosts from response
+/// headers and mixed content reported by the page for a given tab URL.
+pub struct SecurityStore {
+    hsts_hosts: HashSet<String>,
+    mixed_content: HashMap<String, String>,
+}
+
+impl SecurityStore {
+    pub fn new() -> Self {
+        Self {
+            hsts_hosts: HashSet::new(),
+            mixed_content: HashMap::new(),
+        }
+    }
+
+    pub fn record_hsts(&mut self, host: &str) {
+        self.hsts_hosts.insert(host.to_lowercase());
+    }
+
+    /// HSTS with `includeSubDomains` is not tracked separately, so only exact hosts count.
+    pub fn is_hsts(&self, host: &str) -> bool {
+        self.hsts_hosts.contains(&host.to_lowercase())
+    }
+
+    pub fn report_mixed_content(&mut self, tab_id: &str, page_url: &str) {
+        self.mixed_content.insert(tab_id.to_string(), page_url.to_string());
+    }
+
+    /// A report only applies while the tab is still showing the page it was made for.
+    pub fn has_mixed_content(&self, tab_id: &str, page_url: &str) -> bool {
+        self.mixed_content.get(tab_id).map(|url| url == page_url).unwrap_or(false)
+    }
+
+    pub fn forget_tab(&mut self, tab_id: &str) {
+        self.mixed_content.remove(tab_id);
+    }
+}
+
+pub fn classify_security(url: &str, mixed_content: bool, hsts: bool) -> SecurityInfo {
+    let scheme = Url::parse(url)
+        .map(|parsed| parsed.scheme().to_string())
+        .unwrap_or_default();
+    
+    let state = match scheme.as_str() {
+        "https" | "wss" if mixed_content => SecurityState::MixedContent,
+        "https" | "wss" => SecurityState::Secure,
+        "file" => SecurityState::Local,
+        scheme if INTERNAL_SCHEMES.contains(&scheme) => SecurityState::Internal,
+        _ => SecurityState::Insecure,
+    };
+    
+    SecurityInfo {
+        url: url.to_string(),
+        is_secure: matches!(state, SecurityState::Secure | SecurityState::Internal),
+        mixed_content: mixed_content && state == SecurityState::MixedContent,
+        certificate_issuer: None,
+        hsts: hsts && matches!(state, SecurityState::Secure | SecurityState::MixedContent),
+        scheme,
+        state,
+    }
+}
+
+/// Notes HSTS from a response made on the page's behalf.
+pub async fn record_response_headers(url: &str, headers: &reqwest::header::HeaderMap) {
+    if !headers.contains_key(reqwest::header::STRICT_TRANSPORT_SECURITY) {
+        return;
+    }
+    
+    if let Ok(parsed) = Url::parse(url) {
+        if parsed.scheme() != "https" {
+            return;
+        }
+        
+        if let Some(host) = parsed.host_str() {
+            SECURITY_STORE.write().await.record_hsts(host);
+        }
+    }
+}
+
+pub async fn forget_tab(tab_id: &str) {
+    SECURITY_STORE.write().await.forget_tab(tab_id);
+}
+
+#[tauri::command]
+pub async fn get_tab_security_info(tab_id: String) -> Result<SecurityInfo, String> {
+    let url = super::tabs::tab_url(&tab_id).await
+        .ok_or("Tab not found")?;
+    
+    let store = SECURITY_STORE.read().await;
+    let mixed_content = store.has_mixed_content(&tab_id, &url);
+    let hsts = Url::parse(&url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(|host| store.is_hsts(host)))
+        .unwrap_or(false);
+    
+    Ok(classify_security(&url, mixed_content, hsts))
+}
+
+#[tauri::command]
+pub async fn report_mixed_content(tab_id: String, page_url: String) -> Result<(), String> {
+    SECURITY_STORE.write().await.report_mixed_content(&tab_id, &page_url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_classified_by_scheme() {
+        let secure = classify_security("https://example.com/", false, true);
+        assert_eq!(secure.state, SecurityState::Secure);
+        assert!(secure.is_secure && secure.hsts && !secure.mixed_content);
+        
+        let insecure = classify_security("http://example.com/", true, true);
+        assert_eq!(insecure.state, SecurityState::Insecure);
+        assert!(!insecure.is_secure && !insecure.hsts && !insecure.mixed_content);
+        
+        let internal = classify_security("sw3do://settings", false, false);
+        assert_eq!(internal.state, SecurityState::Internal);
+        assert!(internal.is_secure);
+        
+        assert_eq!(classify_security("file:///home/user/page.html", false, false).state, SecurityState::Local);
+        assert_eq!(classify_security("not a url", false, false).state, SecurityState::Insecure);
+    }
+
+    #[test]
+    fn mixed_content_downgrades_a_secure_page() {
+        let mut store = SecurityStore::new();
+        store.report_mixed_content("tab", "https://example.com/a");
+        
+        let mixed = store.has_mixed_content("tab", "https://example.com/a");
+        let info = classify_security("https://example.com/a", mixed, false);
+        assert!(mixed);
+        assert_eq!(info.state, SecurityState::MixedContent);
+        assert!(info.mixed_content && !info.is_secure);
+        
+        assert!(!store.has_mixed_content("tab", "https://example.com/b"));
+        store.forget_tab("tab");
+        assert!(!store.has_mixed_content("tab", "https://example.com/a"));
+    }
+}
\ No newline at end of file
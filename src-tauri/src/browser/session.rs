@@ -1,11 +1,26 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
+use super::storage;
+
+/// Snapshot of the current session, rewritten while the browser runs and
+/// removed on a clean exit, so finding it at startup means the last run crashed.
+const CRASH_RECOVERY_FILE: &str = "crash-recovery.json";
+const AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_SAVED_SESSIONS: usize = 20;
+const MAX_SESSION_NAME_LENGTH: usize = 100;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionData {
+    /// Key under which the session is kept in `saved_sessions`; sessions
+    /// exported before ids existed get one assigned on import.
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
     pub windows: Vec<WindowSession>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_saved: chrono::DateTime<chrono::Utc>,
@@ -64,6 +79,9 @@ pub struct SessionManager {
     pub current_session: Option<SessionData>,
     pub saved_sessions: HashMap<String, SessionData>,
     pub auto_save_enabled: bool,
+    pub max_saved_sessions: usize,
+    pub recovered_session: Option<SessionData>,
+    last_auto_saved: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl Default for ScrollPosition {
@@ -84,18 +102,48 @@ impl Default for WindowBounds {
     }
 }
 
+/// Trims a user-supplied session name; blank names clear it. Names need not
+/// be unique but are limited to `MAX_SESSION_NAME_LENGTH` characters.
+fn normalize_session_name(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.chars().count() > MAX_SESSION_NAME_LENGTH {
+        return Err(format!("Session name must be at most {} characters", MAX_SESSION_NAME_LENGTH));
+    }
+    Ok(Some(name.to_string()).filter(|name| !name.is_empty()))
+}
+
+/// What goes into the crash recovery file: private windows are left out and
+/// form data is dropped, so nothing from them reaches disk.
+fn recovery_snapshot(session: &SessionData) -> SessionData {
+    let mut snapshot = session.clone();
+    snapshot.windows.retain(|window| !window.is_private);
+    for tab in snapshot.windows.iter_mut().flat_map(|window| window.tabs.iter_mut()) {
+        tab.form_data = None;
+    }
+    snapshot
+}
+
 impl SessionManager {
     pub fn new() -> Self {
         Self {
             current_session: None,
             saved_sessions: HashMap::new(),
             auto_save_enabled: true,
+            max_saved_sessions: DEFAULT_MAX_SAVED_SESSIONS,
+            recovered_session: storage::load_json(CRASH_RECOVERY_FILE),
+            last_auto_saved: None,
         }
     }
 
-    pub fn create_session(&mut self) -> String {
+    pub fn create_session(&mut self, name: Option<&str>) -> Result<String, String> {
+        let name = match name {
+            Some(name) => normalize_session_name(name)?,
+            None => None,
+        };
         let session_id = Uuid::new_v4().to_string();
         let session = SessionData {
+            id: session_id.clone(),
+            name,
             windows: Vec::new(),
             created_at: chrono::Utc::now(),
             last_saved: chrono::Utc::now(),
@@ -103,21 +151,45 @@ impl SessionManager {
         
         self.current_session = Some(session.clone());
         self.saved_sessions.insert(session_id.clone(), session);
+        self.prune_saved_sessions();
         
-        session_id
+        Ok(session_id)
     }
 
+    /// Saves the current session over its existing entry, so saving the same
+    /// session repeatedly keeps a single copy.
     pub fn save_current_session(&mut self) -> Result<String, String> {
         if let Some(ref mut session) = self.current_session {
+            if session.id.is_empty() {
+                session.id = Uuid::new_v4().to_string();
+            }
             session.last_saved = chrono::Utc::now();
-            let session_id = Uuid::new_v4().to_string();
+            let session_id = session.id.clone();
             self.saved_sessions.insert(session_id.clone(), session.clone());
+            self.prune_saved_sessions();
             Ok(session_id)
         } else {
             Err("No current session to save".to_string())
         }
     }
 
+    /// Saves a named snapshot of the current session as a new entry, leaving
+    /// the current session and its own saved entry untouched.
+    pub fn save_session_as(&mut self, name: &str) -> Result<String, String> {
+        let name = normalize_session_name(name)?;
+        let mut snapshot = self.current_session.clone()
+            .ok_or("No current session to save")?;
+        
+        snapshot.id = Uuid::new_v4().to_string();
+        snapshot.name = name;
+        snapshot.last_saved = chrono::Utc::now();
+        let session_id = snapshot.id.clone();
+        self.saved_sessions.insert(session_id.clone(), snapshot);
+        self.prune_saved_sessions();
+        
+        Ok(session_id)
+    }
+
     pub fn restore_session(&mut self, session_id: &str) -> Result<SessionData, String> {
         let session = self.saved_sessions.get(session_id)
             .ok_or("Session not found")?;
@@ -132,6 +204,8 @@ impl SessionManager {
             session.last_saved = chrono::Utc::now();
         } else {
             let session = SessionData {
+                id: Uuid::new_v4().to_string(),
+                name: None,
                 windows: vec![window_session],
                 created_at: chrono::Utc::now(),
                 last_saved: chrono::Utc::now(),
@@ -213,6 +287,22 @@ impl SessionManager {
         sessions
     }
 
+    /// Renames a saved session, and the current session too when it is the
+    /// same one so the next save keeps the new name. A blank name clears it.
+    pub fn rename_session(&mut self, session_id: &str, name: &str) -> Result<(), String> {
+        let name = normalize_session_name(name)?;
+        let session = self.saved_sessions.get_mut(session_id)
+            .ok_or("Session not found")?;
+        session.name = name.clone();
+        
+        if let Some(ref mut current) = self.current_session {
+            if current.id == session_id {
+                current.name = name;
+            }
+        }
+        Ok(())
+    }
+
     pub fn delete_session(&mut self, session_id: &str) -> Result<(), String> {
         self.saved_sessions.remove(session_id)
             .ok_or("Session not found")?;
@@ -224,6 +314,29 @@ impl SessionManager {
         self.saved_sessions.retain(|_, session| session.last_saved >= cutoff);
     }
 
+    /// Evicts the least recently saved sessions until at most
+    /// `max_saved_sessions` remain.
+    pub fn prune_saved_sessions(&mut self) {
+        if self.saved_sessions.len() <= self.max_saved_sessions {
+            return;
+        }
+        
+        let mut by_age: Vec<(String, chrono::DateTime<chrono::Utc>)> = self.saved_sessions.iter()
+            .map(|(id, session)| (id.clone(), session.last_saved))
+            .collect();
+        by_age.sort_by_key(|(_, last_saved)| *last_saved);
+        
+        let excess = self.saved_sessions.len() - self.max_saved_sessions;
+        for (id, _) in by_age.into_iter().take(excess) {
+            self.saved_sessions.remove(&id);
+        }
+    }
+
+    pub fn set_max_saved_sessions(&mut self, limit: usize) {
+        self.max_saved_sessions = limit.max(1);
+        self.prune_saved_sessions();
+    }
+
     pub fn export_session(&self, session_id: &str) -> Result<String, String> {
         let session = self.saved_sessions.get(session_id)
             .ok_or("Session not found")?;
@@ -233,11 +346,13 @@ impl SessionManager {
     }
 
     pub fn import_session(&mut self, data: &str) -> Result<String, String> {
-        let session: SessionData = serde_json::from_str(data)
+        let mut session: SessionData = serde_json::from_str(data)
             .map_err(|e| format!("Failed to parse session data: {}", e))?;
         
         let session_id = Uuid::new_v4().to_string();
+        session.id = session_id.clone();
         self.saved_sessions.insert(session_id.clone(), session);
+        self.prune_saved_sessions();
         
         Ok(session_id)
     }
@@ -253,12 +368,61 @@ impl SessionManager {
     pub fn should_auto_save(&self) -> bool {
         self.auto_save_enabled
     }
+
+    /// Writes the current session, minus private windows and form data, to
+    /// the crash recovery file if it changed since the last write.
+    pub fn auto_save(&mut self) -> Result<(), String> {
+        if !self.should_auto_save() {
+            return Ok(());
+        }
+        
+        let Some(session) = &self.current_session else {
+            return Ok(());
+        };
+        if self.last_auto_saved == Some(session.last_saved) {
+            return Ok(());
+        }
+        
+        storage::save_json(CRASH_RECOVERY_FILE, &recovery_snapshot(session))?;
+        self.last_auto_saved = Some(session.last_saved);
+        Ok(())
+    }
+
+    /// Makes the session left behind by a crashed run current again. Returns
+    /// `None` when the previous run exited cleanly or it was already recovered.
+    pub fn recover_session(&mut self) -> Option<SessionData> {
+        let session = self.recovered_session.take()?;
+        self.current_session = Some(session.clone());
+        Some(session)
+    }
+
+    /// Called on a clean exit: stops auto-saving and removes the recovery file
+    /// so the next launch doesn't offer to restore.
+    pub fn clear_crash_recovery(&mut self) {
+        self.auto_save_enabled = false;
+        let _ = std::fs::remove_file(storage::profile_dir().join(CRASH_RECOVERY_FILE));
+    }
+}
+
+pub fn start_session_auto_save() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            tokio::time::sleep(AUTO_SAVE_INTERVAL).await;
+            let _ = SESSION_MANAGER.write().await.auto_save();
+        }
+    });
+}
+
+pub fn clear_crash_recovery() {
+    tauri::async_runtime::block_on(async {
+        SESSION_MANAGER.write().await.clear_crash_recovery();
+    });
 }
 
 #[tauri::command]
-pub async fn create_session() -> Result<String, String> {
+pub async fn create_session(name: Option<String>) -> Result<String, String> {
     let mut manager = SESSION_MANAGER.write().await;
-    Ok(manager.create_session())
+    manager.create_session(name.as_deref())
 }
 
 #[tauri::command]
@@ -267,6 +431,12 @@ pub async fn save_current_session() -> Result<String, String> {
     manager.save_current_session()
 }
 
+#[tauri::command]
+pub async fn save_session_as(name: String) -> Result<String, String> {
+    let mut manager = SESSION_MANAGER.write().await;
+    manager.save_session_as(&name)
+}
+
 #[tauri::command]
 pub async fn restore_session(session_id: String) -> Result<SessionData, String> {
     let mut manager = SESSION_MANAGER.write().await;
@@ -335,6 +505,12 @@ pub async fn get_saved_sessions() -> Result<Vec<(String, SessionData)>, String>
     Ok(manager.get_saved_sessions().into_iter().map(|(id, session)| (id.clone(), session.clone())).collect())
 }
 
+#[tauri::command]
+pub async fn rename_session(session_id: String, name: String) -> Result<(), String> {
+    let mut manager = SESSION_MANAGER.write().await;
+    manager.rename_session(&session_id, &name)
+}
+
 #[tauri::command]
 pub async fn delete_session(session_id: String) -> Result<(), String> {
     let mut manager = SESSION_MANAGER.write().await;
@@ -348,6 +524,13 @@ pub async fn clear_old_sessions(days: i64) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+pub async fn set_max_saved_sessions(limit: usize) -> Result<(), String> {
+    let mut manager = SESSION_MANAGER.write().await;
+    manager.set_max_saved_sessions(limit);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn export_session(session_id: String) -> Result<String, String> {
     let manager = SESSION_MANAGER.read().await;
@@ -371,4 +554,119 @@ pub async fn enable_auto_save(enabled: bool) -> Result<(), String> {
     let mut manager = SESSION_MANAGER.write().await;
     manager.enable_auto_save(enabled);
     Ok(())
+}
+
+#[tauri::command]
+pub async fn recover_session() -> Result<Option<SessionData>, String> {
+    let mut manager = SESSION_MANAGER.write().await;
+    Ok(manager.recover_session())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> SessionManager {
+        SessionManager {
+            current_session: None,
+            saved_sessions: HashMap::new(),
+            auto_save_enabled: true,
+            max_saved_sessions: DEFAULT_MAX_SAVED_SESSIONS,
+            recovered_session: None,
+            last_auto_saved: None,
+        }
+    }
+
+    fn window(id: &str, is_private: bool) -> WindowSession {
+        let now = chrono::Utc::now();
+        WindowSession {
+            id: id.to_string(),
+            is_private,
+            tabs: vec![TabSession {
+                id: format!("{}-tab", id),
+                url: "https://example.com/form".to_string(),
+                title: "Form".to_string(),
+                favicon: None,
+                history: Vec::new(),
+                history_index: 0,
+                scroll_position: ScrollPosition::default(),
+                form_data: Some("{\"card\":\"4111\"}".to_string()),
+                created_at: now,
+                last_accessed: now,
+            }],
+            active_tab_index: Some(0),
+            bounds: WindowBounds::default(),
+        }
+    }
+
+    #[test]
+    fn crash_recovery_leaves_out_private_windows_and_form_data() {
+        let mut manager = manager();
+        manager.create_session(None).unwrap();
+        manager.add_window_to_session(window("normal", false));
+        manager.add_window_to_session(window("private", true));
+        
+        let snapshot = recovery_snapshot(manager.get_current_session().unwrap());
+        assert_eq!(snapshot.windows.len(), 1);
+        assert_eq!(snapshot.windows[0].id, "normal");
+        assert!(snapshot.windows[0].tabs[0].form_data.is_none());
+        
+        manager.recovered_session = Some(snapshot);
+        assert_eq!(manager.recover_session().unwrap().windows.len(), 1);
+        assert!(manager.recover_session().is_none());
+    }
+
+    #[test]
+    fn saving_the_current_session_keeps_one_copy() {
+        let mut manager = manager();
+        let session_id = manager.create_session(Some("Work")).unwrap();
+        
+        assert_eq!(manager.save_current_session().unwrap(), session_id);
+        assert_eq!(manager.save_current_session().unwrap(), session_id);
+        assert_eq!(manager.saved_sessions.len(), 1);
+        
+        let snapshot_id = manager.save_session_as("Snapshot").unwrap();
+        assert_ne!(snapshot_id, session_id);
+        assert_eq!(manager.saved_sessions.len(), 2);
+        assert_eq!(manager.get_current_session().unwrap().id, session_id);
+    }
+
+    #[test]
+    fn saved_sessions_are_capped_by_evicting_the_oldest() {
+        let mut manager = manager();
+        let start = chrono::Utc::now();
+        let mut ids = Vec::new();
+        for index in 0..4 {
+            let session_id = manager.create_session(None).unwrap();
+            manager.saved_sessions.get_mut(&session_id).unwrap().last_saved = start + chrono::Duration::seconds(index);
+            ids.push(session_id);
+        }
+        
+        manager.set_max_saved_sessions(2);
+        
+        assert_eq!(manager.saved_sessions.len(), 2);
+        assert!(manager.saved_sessions.contains_key(&ids[2]) && manager.saved_sessions.contains_key(&ids[3]));
+        
+        manager.set_max_saved_sessions(0);
+        assert_eq!(manager.max_saved_sessions, 1);
+        assert!(manager.saved_sessions.contains_key(&ids[3]));
+    }
+
+    #[test]
+    fn renaming_updates_the_current_session_and_validates_names() {
+        let mut manager = manager();
+        let session_id = manager.create_session(Some("  Work  ")).unwrap();
+        assert_eq!(manager.saved_sessions[&session_id].name.as_deref(), Some("Work"));
+        
+        manager.rename_session(&session_id, "Research").unwrap();
+        assert_eq!(manager.saved_sessions[&session_id].name.as_deref(), Some("Research"));
+        assert_eq!(manager.get_current_session().unwrap().name.as_deref(), Some("Research"));
+        
+        manager.rename_session(&session_id, "   ").unwrap();
+        assert!(manager.saved_sessions[&session_id].name.is_none());
+        
+        let too_long = "x".repeat(MAX_SESSION_NAME_LENGTH + 1);
+        assert!(manager.rename_session(&session_id, &too_long).is_err());
+        assert!(manager.rename_session("missing", "Name").is_err());
+    }
 }
\ No newline at end of file
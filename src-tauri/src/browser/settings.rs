@@ -1,7 +1,22 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
 use once_cell::sync::Lazy;
+use super::doh::DohProvider;
+use super::storage;
+
+const MAX_CUSTOM_CSS_BYTES: usize = 64 * 1024;
+const SETTINGS_FILE: &str = "settings.json";
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 15;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_IDLE_CONNECTIONS_PER_HOST: usize = 6;
+/// Query parameters stripped before navigation. A trailing `*` matches a prefix.
+const DEFAULT_TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "gclid", "dclid", "gbraid", "wbraid", "fbclid", "msclkid", "yclid", "twclid", "ttclid",
+    "mc_cid", "mc_eid", "_hsenc", "_hsmi", "igshid", "mkt_tok", "oly_anon_id", "oly_enc_id", "vero_id",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserSettings {
@@ -11,6 +26,61 @@ pub struct BrowserSettings {
     pub search: SearchSettings,
     pub downloads: DownloadSettings,
     pub advanced: AdvancedSettings,
+    #[serde(default)]
+    pub kiosk: KioskSettings,
+}
+
+/// Restricts browsing to an allowlist of domains for public/kiosk deployments.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct KioskSettings {
+    pub enabled: bool,
+    pub allowlist: Vec<String>,
+}
+
+impl KioskSettings {
+    /// Outside kiosk mode everything is allowed. In kiosk mode only allowlisted
+    /// domains and their subdomains are, over http(s).
+    pub fn allows(&self, url: &str) -> bool {
+        !self.enabled || allowlist_allows(&self.allowlist, url)
+    }
+}
+
+/// Kiosk state for the webview navigation hook, which runs on the UI thread
+/// and so can't wait on `SETTINGS_MANAGER`. Kept in step by the commands that
+/// change `kiosk`.
+static KIOSK_ENABLED: AtomicBool = AtomicBool::new(false);
+static KIOSK_ALLOWLIST: Lazy<std::sync::RwLock<Vec<String>>> = Lazy::new(Default::default);
+
+fn allowlist_allows(allowlist: &[String], url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return false;
+    }
+    
+    parsed.host_str()
+        .map(|host| allowlist.iter().any(|domain| super::network::host_matches_domain(host, domain)))
+        .unwrap_or(false)
+}
+
+fn publish_kiosk_settings(kiosk: &KioskSettings) {
+    if let Ok(mut allowlist) = KIOSK_ALLOWLIST.write() {
+        *allowlist = kiosk.allowlist.clone();
+    }
+    KIOSK_ENABLED.store(kiosk.enabled, Ordering::Release);
+}
+
+/// Same check as `KioskSettings::allows` without touching the settings lock.
+pub fn kiosk_allows(url: &str) -> bool {
+    Lazy::force(&SETTINGS_MANAGER);
+    if !KIOSK_ENABLED.load(Ordering::Acquire) {
+        return true;
+    }
+    
+    KIOSK_ALLOWLIST.read()
+        .map(|allowlist| allowlist_allows(&allowlist, url))
+        .unwrap_or(false)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +92,8 @@ pub struct GeneralSettings {
     pub show_bookmarks_bar: bool,
     pub enable_notifications: bool,
     pub language: String,
+    #[serde(default)]
+    pub startup_tabs: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +106,42 @@ pub struct PrivacySettings {
     pub clear_data_on_exit: bool,
     pub send_do_not_track: bool,
     pub enable_private_browsing_by_default: bool,
+    #[serde(default)]
+    pub dns_over_https: bool,
+    #[serde(default)]
+    pub dns_over_https_provider: DohProvider,
+    #[serde(default)]
+    pub sensitive_domains: HashSet<String>,
+    #[serde(default = "default_strip_tracking_params")]
+    pub strip_tracking_params: bool,
+    #[serde(default = "default_tracking_params")]
+    pub tracking_params: Vec<String>,
+    /// Refresh enabled filter lists in the background. Off unless the user opts in.
+    #[serde(default)]
+    pub auto_update_filter_lists: bool,
+    #[serde(default = "default_filter_update_interval_hours")]
+    pub filter_update_interval_hours: u32,
+}
+
+fn default_strip_tracking_params() -> bool {
+    true
+}
+
+fn default_tracking_params() -> Vec<String> {
+    DEFAULT_TRACKING_PARAMS.iter().map(|param| param.to_string()).collect()
+}
+
+fn default_filter_update_interval_hours() -> u32 {
+    24
+}
+
+impl PrivacySettings {
+    /// Sensitive domains (and their subdomains) are never recorded in history.
+    pub fn is_sensitive_url(&self, url: &str) -> bool {
+        super::network::url_host(url)
+            .map(|host| self.sensitive_domains.iter().any(|domain| super::network::host_matches_domain(&host, domain)))
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,14 +153,76 @@ pub struct AppearanceSettings {
     pub show_tab_previews: bool,
     pub compact_mode: bool,
     pub custom_css: Option<String>,
+    #[serde(default)]
+    pub domain_css: HashMap<String, String>,
+}
+
+impl AppearanceSettings {
+    /// Global CSS first, then every matching domain rule from least to most
+    /// specific so that subdomain rules win the cascade.
+    pub fn resolve_custom_css(&self, host: &str) -> Option<String> {
+        let mut domains: Vec<(&String, &String)> = self.domain_css
+            .iter()
+            .filter(|(domain, _)| super::network::host_matches_domain(host, domain))
+            .collect();
+        domains.sort_by_key(|(domain, _)| domain.len());
+        
+        let css: Vec<&str> = self.custom_css
+            .iter()
+            .map(|css| css.as_str())
+            .chain(domains.into_iter().map(|(_, css)| css.as_str()))
+            .filter(|css| !css.trim().is_empty())
+            .collect();
+        
+        if css.is_empty() {
+            None
+        } else {
+            Some(css.join("\n"))
+        }
+    }
+
+    /// `system` and any unrecognised value follow the OS theme.
+    pub fn follows_system_theme(&self) -> bool {
+        !self.theme.eq_ignore_ascii_case("light") && !self.theme.eq_ignore_ascii_case("dark")
+    }
+
+    /// Falls back to light when following the OS and its theme is unknown.
+    pub fn resolve_theme(&self, os_theme: Option<ResolvedTheme>) -> ResolvedTheme {
+        if self.follows_system_theme() {
+            os_theme.unwrap_or(ResolvedTheme::Light)
+        } else if self.theme.eq_ignore_ascii_case("dark") {
+            ResolvedTheme::Dark
+        } else {
+            ResolvedTheme::Light
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ResolvedTheme {
+    Light,
+    Dark,
+}
+
+impl From<tauri::Theme> for ResolvedTheme {
+    fn from(theme: tauri::Theme) -> Self {
+        match theme {
+            tauri::Theme::Dark => ResolvedTheme::Dark,
+            _ => ResolvedTheme::Light,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchSettings {
     pub search_engines: HashMap<String, SearchEngine>,
+    #[serde(default)]
+    pub engine_order: Vec<String>,
     pub default_engine: String,
     pub enable_search_suggestions: bool,
     pub show_search_in_address_bar: bool,
+    #[serde(default)]
+    pub search_domain_blocklist: HashSet<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +233,45 @@ pub struct SearchEngine {
     pub icon: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderedSearchEngine {
+    pub id: String,
+    #[serde(flatten)]
+    pub engine: SearchEngine,
+}
+
+impl SearchSettings {
+    /// Engines in picker order. Engines missing from `engine_order` (e.g. from
+    /// settings saved before it existed) follow, sorted by id.
+    pub fn ordered_engines(&self) -> Vec<OrderedSearchEngine> {
+        let mut ids: Vec<&String> = self.engine_order
+            .iter()
+            .filter(|id| self.search_engines.contains_key(*id))
+            .collect();
+        
+        let mut unordered: Vec<&String> = self.search_engines
+            .keys()
+            .filter(|id| !self.engine_order.contains(id))
+            .collect();
+        unordered.sort();
+        ids.extend(unordered);
+        
+        ids.into_iter()
+            .map(|id| OrderedSearchEngine {
+                id: id.clone(),
+                engine: self.search_engines[id].clone(),
+            })
+            .collect()
+    }
+
+    /// Results from a blocklisted domain or any of its subdomains are dropped.
+    pub fn is_blocked_search_result(&self, url: &str) -> bool {
+        super::network::url_host(url)
+            .map(|host| self.search_domain_blocklist.iter().any(|domain| super::network::host_matches_domain(&host, domain)))
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadSettings {
     pub download_directory: String,
@@ -81,6 +290,41 @@ pub struct AdvancedSettings {
     pub user_agent: Option<String>,
     pub proxy_settings: ProxySettings,
     pub developer_mode: bool,
+    #[serde(default)]
+    pub data_saver: bool,
+    #[serde(default)]
+    pub network: NetworkSettings,
+}
+
+/// Limits applied to every outbound request made through the shared HTTP client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkSettings {
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: u64,
+    /// Idle keep-alive connections kept open per host. Does not limit how many
+    /// connections are open at once.
+    #[serde(alias = "max_connections_per_host")]
+    pub max_idle_connections_per_host: usize,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            max_idle_connections_per_host: DEFAULT_MAX_IDLE_CONNECTIONS_PER_HOST,
+        }
+    }
+}
+
+/// What the browser is allowed to load once data saver is taken into account.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataUsagePolicy {
+    pub data_saver: bool,
+    pub load_images: bool,
+    pub show_tab_previews: bool,
+    pub prefetch_favicons: bool,
+    pub prefer_reader_mode: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +353,19 @@ pub struct SettingsManager {
     pub settings: BrowserSettings,
 }
 
+impl BrowserSettings {
+    pub fn data_usage_policy(&self) -> DataUsagePolicy {
+        let data_saver = self.advanced.data_saver;
+        DataUsagePolicy {
+            data_saver,
+            load_images: self.advanced.enable_images && !data_saver,
+            show_tab_previews: self.appearance.show_tab_previews && !data_saver,
+            prefetch_favicons: !data_saver,
+            prefer_reader_mode: data_saver,
+        }
+    }
+}
+
 impl Default for BrowserSettings {
     fn default() -> Self {
         let mut search_engines = HashMap::new();
@@ -143,6 +400,7 @@ impl Default for BrowserSettings {
                 show_bookmarks_bar: true,
                 enable_notifications: true,
                 language: "en-US".to_string(),
+                startup_tabs: Vec::new(),
             },
             privacy: PrivacySettings {
                 block_ads: true,
@@ -153,6 +411,13 @@ impl Default for BrowserSettings {
                 clear_data_on_exit: false,
                 send_do_not_track: true,
                 enable_private_browsing_by_default: false,
+                dns_over_https: false,
+                dns_over_https_provider: DohProvider::Cloudflare,
+                sensitive_domains: HashSet::new(),
+                strip_tracking_params: default_strip_tracking_params(),
+                tracking_params: default_tracking_params(),
+                auto_update_filter_lists: false,
+                filter_update_interval_hours: default_filter_update_interval_hours(),
             },
             appearance: AppearanceSettings {
                 theme: "system".to_string(),
@@ -162,12 +427,15 @@ impl Default for BrowserSettings {
                 show_tab_previews: true,
                 compact_mode: false,
                 custom_css: None,
+                domain_css: HashMap::new(),
             },
             search: SearchSettings {
                 search_engines,
+                engine_order: vec!["google".to_string(), "duckduckgo".to_string(), "bing".to_string()],
                 default_engine: "duckduckgo".to_string(),
                 enable_search_suggestions: true,
                 show_search_in_address_bar: true,
+                search_domain_blocklist: HashSet::new(),
             },
             downloads: DownloadSettings {
                 download_directory: dirs::download_dir()
@@ -193,48 +461,194 @@ impl Default for BrowserSettings {
                     password: None,
                 },
                 developer_mode: false,
+                data_saver: false,
+                network: NetworkSettings::default(),
             },
+            kiosk: KioskSettings::default(),
         }
     }
 }
 
+/// Applies the top-level fields present in `updates` over `current`. Fields
+/// left out keep their values, so a partial payload, or one from a frontend
+/// that predates a field, doesn't reset it.
+fn merge_settings<T: Serialize + DeserializeOwned>(current: &T, updates: serde_json::Value) -> Result<T, String> {
+    let serde_json::Value::Object(updates) = updates else {
+        return Err("Settings update must be an object".to_string());
+    };
+    let mut merged = serde_json::to_value(current)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    if let serde_json::Value::Object(fields) = &mut merged {
+        fields.extend(updates);
+    }
+    serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))
+}
+
 impl SettingsManager {
     pub fn new() -> Self {
-        Self {
-            settings: BrowserSettings::default(),
-        }
+        let settings: BrowserSettings = storage::load_json(SETTINGS_FILE).unwrap_or_default();
+        publish_kiosk_settings(&settings.kiosk);
+        Self { settings }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        storage::save_json(SETTINGS_FILE, &self.settings)
     }
 
     pub fn get_settings(&self) -> &BrowserSettings {
         &self.settings
     }
 
-    pub fn update_general_settings(&mut self, settings: GeneralSettings) {
-        self.settings.general = settings;
+    pub fn update_general_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        self.settings.general = merge_settings(&self.settings.general, updates)?;
+        Ok(())
+    }
+
+    pub fn add_startup_tab(&mut self, url: &str) -> Result<(), String> {
+        url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+        
+        if !self.settings.general.startup_tabs.iter().any(|u| u == url) {
+            self.settings.general.startup_tabs.push(url.to_string());
+        }
+        
+        Ok(())
+    }
+
+    pub fn remove_startup_tab(&mut self, url: &str) -> Result<(), String> {
+        let startup_tabs = &mut self.settings.general.startup_tabs;
+        let original_len = startup_tabs.len();
+        startup_tabs.retain(|u| u != url);
+        
+        if startup_tabs.len() == original_len {
+            return Err("Startup tab not found".to_string());
+        }
+        
+        Ok(())
     }
 
-    pub fn update_privacy_settings(&mut self, settings: PrivacySettings) {
-        self.settings.privacy = settings;
+    pub fn update_privacy_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        self.settings.privacy = merge_settings(&self.settings.privacy, updates)?;
+        Ok(())
+    }
+
+    pub fn set_dns_over_https(&mut self, enabled: bool, provider: DohProvider) {
+        self.settings.privacy.dns_over_https = enabled;
+        self.settings.privacy.dns_over_https_provider = provider;
+    }
+
+    pub fn add_sensitive_domain(&mut self, domain: &str) -> Result<(), String> {
+        let domain = normalize_domain(domain)?;
+        self.settings.privacy.sensitive_domains.insert(domain);
+        Ok(())
     }
 
-    pub fn update_appearance_settings(&mut self, settings: AppearanceSettings) {
+    pub fn remove_sensitive_domain(&mut self, domain: &str) -> Result<(), String> {
+        let domain = normalize_domain(domain)?;
+        if !self.settings.privacy.sensitive_domains.remove(&domain) {
+            return Err("Sensitive domain not found".to_string());
+        }
+        Ok(())
+    }
+
+    /// `domain_css` is only replaced when the update includes it.
+    pub fn update_appearance_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        let settings: AppearanceSettings = merge_settings(&self.settings.appearance, updates)?;
+        let oversized = settings.custom_css.iter()
+            .chain(settings.domain_css.values())
+            .any(|css| css.len() > MAX_CUSTOM_CSS_BYTES);
+        if oversized {
+            return Err(format!("Custom CSS exceeds {} KB", MAX_CUSTOM_CSS_BYTES / 1024));
+        }
+        
         self.settings.appearance = settings;
+        Ok(())
+    }
+
+    pub fn set_domain_css(&mut self, domain: &str, css: &str) -> Result<(), String> {
+        if css.len() > MAX_CUSTOM_CSS_BYTES {
+            return Err(format!("Custom CSS exceeds {} KB", MAX_CUSTOM_CSS_BYTES / 1024));
+        }
+        
+        let domain = normalize_domain(domain)?;
+        if css.trim().is_empty() {
+            self.settings.appearance.domain_css.remove(&domain);
+        } else {
+            self.settings.appearance.domain_css.insert(domain, css.to_string());
+        }
+        Ok(())
     }
 
-    pub fn update_search_settings(&mut self, settings: SearchSettings) {
-        self.settings.search = settings;
+    pub fn get_domain_css(&self, domain: &str) -> Result<Option<String>, String> {
+        let domain = normalize_domain(domain)?;
+        Ok(self.settings.appearance.domain_css.get(&domain).cloned())
     }
 
-    pub fn update_download_settings(&mut self, settings: DownloadSettings) {
-        self.settings.downloads = settings;
+    pub fn clear_domain_css(&mut self, domain: &str) -> Result<(), String> {
+        let domain = normalize_domain(domain)?;
+        self.settings.appearance.domain_css.remove(&domain)
+            .ok_or("No custom CSS for domain")?;
+        Ok(())
     }
 
-    pub fn update_advanced_settings(&mut self, settings: AdvancedSettings) {
-        self.settings.advanced = settings;
+    pub fn update_search_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        self.settings.search = merge_settings(&self.settings.search, updates)?;
+        Ok(())
+    }
+
+    pub fn update_download_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        self.settings.downloads = merge_settings(&self.settings.downloads, updates)?;
+        Ok(())
+    }
+
+    pub fn update_advanced_settings(&mut self, updates: serde_json::Value) -> Result<(), String> {
+        self.settings.advanced = merge_settings(&self.settings.advanced, updates)?;
+        Ok(())
+    }
+
+    pub fn set_kiosk_allowlist(&mut self, domains: &[String]) -> Result<(), String> {
+        let mut allowlist = Vec::new();
+        for domain in domains {
+            let domain = normalize_domain(domain)?;
+            if !allowlist.contains(&domain) {
+                allowlist.push(domain);
+            }
+        }
+        
+        self.settings.kiosk.allowlist = allowlist;
+        Ok(())
+    }
+
+    pub fn set_kiosk_mode(&mut self, enabled: bool) -> Result<(), String> {
+        if enabled && self.settings.kiosk.allowlist.is_empty() {
+            return Err("Kiosk mode needs at least one allowed domain".to_string());
+        }
+        
+        self.settings.kiosk.enabled = enabled;
+        Ok(())
+    }
+
+    pub fn set_network_settings(&mut self, network: NetworkSettings) -> Result<(), String> {
+        if network.connect_timeout_secs == 0 || network.request_timeout_secs == 0 {
+            return Err("Timeouts must be at least one second".to_string());
+        }
+        if network.max_idle_connections_per_host == 0 {
+            return Err("At least one idle connection per host is required".to_string());
+        }
+        
+        self.settings.advanced.network = network;
+        Ok(())
+    }
+
+    pub fn set_data_saver(&mut self, enabled: bool) {
+        self.settings.advanced.data_saver = enabled;
     }
 
     pub fn add_search_engine(&mut self, id: &str, engine: SearchEngine) {
-        self.settings.search.search_engines.insert(id.to_string(), engine);
+        let search = &mut self.settings.search;
+        search.search_engines.insert(id.to_string(), engine);
+        if !search.engine_order.iter().any(|existing| existing == id) {
+            search.engine_order.push(id.to_string());
+        }
     }
 
     pub fn remove_search_engine(&mut self, id: &str) -> Result<(), String> {
@@ -244,6 +658,7 @@ impl SettingsManager {
         
         self.settings.search.search_engines.remove(id)
             .ok_or("Search engine not found")?;
+        self.settings.search.engine_order.retain(|existing| existing != id);
         
         Ok(())
     }
@@ -274,11 +689,54 @@ impl SettingsManager {
         Ok(())
     }
 
+    pub fn reorder_search_engines(&mut self, order: Vec<String>) -> Result<(), String> {
+        let search = &mut self.settings.search;
+        
+        for (index, id) in order.iter().enumerate() {
+            if !search.search_engines.contains_key(id) {
+                return Err(format!("Search engine not found: {}", id));
+            }
+            if order[..index].contains(id) {
+                return Err(format!("Search engine listed twice: {}", id));
+            }
+        }
+        
+        let remaining: Vec<String> = search.ordered_engines()
+            .into_iter()
+            .map(|entry| entry.id)
+            .filter(|id| !order.contains(id))
+            .collect();
+        
+        search.engine_order = order;
+        search.engine_order.extend(remaining);
+        Ok(())
+    }
+
+    pub fn block_search_domain(&mut self, domain: &str) -> Result<(), String> {
+        let domain = normalize_domain(domain)?;
+        self.settings.search.search_domain_blocklist.insert(domain);
+        Ok(())
+    }
+
+    pub fn unblock_search_domain(&mut self, domain: &str) -> Result<(), String> {
+        let domain = normalize_domain(domain)?;
+        if !self.settings.search.search_domain_blocklist.remove(&domain) {
+            return Err("Search domain not found in blocklist".to_string());
+        }
+        Ok(())
+    }
+
     pub fn get_search_url(&self, query: &str) -> Option<String> {
+        self.get_search_url_for_engine(query, &self.settings.search.default_engine).ok()
+    }
+
+    /// Builds a query URL for one specific engine without touching the default.
+    pub fn get_search_url_for_engine(&self, query: &str, engine_id: &str) -> Result<String, String> {
         let engine = self.settings.search.search_engines
-            .get(&self.settings.search.default_engine)?;
+            .get(engine_id)
+            .ok_or_else(|| format!("Search engine not found: {}", engine_id))?;
         
-        Some(engine.url.replace("{searchTerms}", &urlencoding::encode(query)))
+        Ok(engine.url.replace("{searchTerms}", &urlencoding::encode(query)))
     }
 
     pub fn get_suggestion_url(&self, query: &str) -> Option<String> {
@@ -291,6 +749,41 @@ impl SettingsManager {
     }
 }
 
+/// Accepts a bare domain or a URL and returns its lowercase host.
+fn normalize_domain(domain: &str) -> Result<String, String> {
+    let domain = domain.trim();
+    let host = super::network::url_host(domain)
+        .unwrap_or_else(|| domain.trim_matches('/').to_lowercase());
+    
+    if host.is_empty() || host.contains('/') || host.contains(char::is_whitespace) {
+        return Err(format!("Invalid domain: {}", domain));
+    }
+    
+    Ok(host.trim_start_matches("www.").to_string())
+}
+
+pub async fn current_settings() -> BrowserSettings {
+    SETTINGS_MANAGER.read().await.settings.clone()
+}
+
+fn os_theme(app: &AppHandle) -> Option<ResolvedTheme> {
+    app.webview_windows()
+        .values()
+        .find_map(|window| window.theme().ok())
+        .map(ResolvedTheme::from)
+}
+
+/// Called from the window event handler. Only matters while the appearance
+/// setting follows the system theme.
+pub fn handle_os_theme_changed(app: AppHandle, theme: tauri::Theme) {
+    tauri::async_runtime::spawn(async move {
+        let follows_system = SETTINGS_MANAGER.read().await.settings.appearance.follows_system_theme();
+        if follows_system {
+            let _ = app.emit("theme://changed", ResolvedTheme::from(theme));
+        }
+    });
+}
+
 #[tauri::command]
 pub async fn get_settings() -> Result<BrowserSettings, String> {
     let manager = SETTINGS_MANAGER.read().await;
@@ -298,71 +791,184 @@ pub async fn get_settings() -> Result<BrowserSettings, String> {
 }
 
 #[tauri::command]
-pub async fn update_general_settings(settings: GeneralSettings) -> Result<(), String> {
+pub async fn update_general_settings(settings: serde_json::Value) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.update_general_settings(settings)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn update_privacy_settings(settings: serde_json::Value) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.update_privacy_settings(settings)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn set_dns_over_https(enabled: bool, provider: DohProvider) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.set_dns_over_https(enabled, provider);
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn add_sensitive_domain(domain: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.add_sensitive_domain(&domain)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn remove_sensitive_domain(domain: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.remove_sensitive_domain(&domain)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn get_sensitive_domains() -> Result<Vec<String>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    let mut domains: Vec<String> = manager.settings.privacy.sensitive_domains.iter().cloned().collect();
+    domains.sort();
+    Ok(domains)
+}
+
+#[tauri::command]
+pub async fn update_appearance_settings(settings: serde_json::Value) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.update_appearance_settings(settings)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn resolve_theme(app: AppHandle) -> Result<ResolvedTheme, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.appearance.resolve_theme(os_theme(&app)))
+}
+
+#[tauri::command]
+pub async fn set_domain_css(domain: String, css: String) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_general_settings(settings);
-    Ok(())
+    manager.set_domain_css(&domain, &css)?;
+    manager.save()
 }
 
 #[tauri::command]
-pub async fn update_privacy_settings(settings: PrivacySettings) -> Result<(), String> {
+pub async fn get_domain_css(domain: String) -> Result<Option<String>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    manager.get_domain_css(&domain)
+}
+
+#[tauri::command]
+pub async fn clear_domain_css(domain: String) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_privacy_settings(settings);
-    Ok(())
+    manager.clear_domain_css(&domain)?;
+    manager.save()
 }
 
 #[tauri::command]
-pub async fn update_appearance_settings(settings: AppearanceSettings) -> Result<(), String> {
+pub async fn update_search_settings(settings: serde_json::Value) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_appearance_settings(settings);
-    Ok(())
+    manager.update_search_settings(settings)?;
+    manager.save()
 }
 
 #[tauri::command]
-pub async fn update_search_settings(settings: SearchSettings) -> Result<(), String> {
+pub async fn update_download_settings(settings: serde_json::Value) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_search_settings(settings);
-    Ok(())
+    manager.update_download_settings(settings)?;
+    manager.save()
 }
 
 #[tauri::command]
-pub async fn update_download_settings(settings: DownloadSettings) -> Result<(), String> {
+pub async fn update_advanced_settings(settings: serde_json::Value) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_download_settings(settings);
-    Ok(())
+    manager.update_advanced_settings(settings)?;
+    manager.save()
 }
 
 #[tauri::command]
-pub async fn update_advanced_settings(settings: AdvancedSettings) -> Result<(), String> {
+pub async fn set_kiosk_allowlist(domains: Vec<String>) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.update_advanced_settings(settings);
-    Ok(())
+    manager.set_kiosk_allowlist(&domains)?;
+    publish_kiosk_settings(&manager.settings.kiosk);
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn set_kiosk_mode(enabled: bool) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.set_kiosk_mode(enabled)?;
+    publish_kiosk_settings(&manager.settings.kiosk);
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn set_network_settings(network: NetworkSettings) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.set_network_settings(network)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn get_network_settings() -> Result<NetworkSettings, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.advanced.network.clone())
+}
+
+#[tauri::command]
+pub async fn get_kiosk_settings() -> Result<KioskSettings, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.kiosk.clone())
+}
+
+#[tauri::command]
+pub async fn set_data_saver(app: AppHandle, enabled: bool) -> Result<DataUsagePolicy, String> {
+    let policy = {
+        let mut manager = SETTINGS_MANAGER.write().await;
+        manager.set_data_saver(enabled);
+        manager.save()?;
+        manager.settings.data_usage_policy()
+    };
+    
+    app.emit("settings://data-saver", policy.clone())
+        .map_err(|e| format!("Failed to emit data saver state: {}", e))?;
+    Ok(policy)
+}
+
+#[tauri::command]
+pub async fn get_data_usage_policy() -> Result<DataUsagePolicy, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.data_usage_policy())
 }
 
 #[tauri::command]
 pub async fn add_search_engine(id: String, engine: SearchEngine) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
     manager.add_search_engine(&id, engine);
-    Ok(())
+    manager.save()
 }
 
 #[tauri::command]
 pub async fn remove_search_engine(id: String) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.remove_search_engine(&id)
+    manager.remove_search_engine(&id)?;
+    manager.save()
 }
 
 #[tauri::command]
 pub async fn set_default_search_engine(id: String) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.set_default_search_engine(&id)
+    manager.set_default_search_engine(&id)?;
+    manager.save()
 }
 
 #[tauri::command]
 pub async fn reset_settings_to_defaults() -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
     manager.reset_to_defaults();
-    Ok(())
+    publish_kiosk_settings(&manager.settings.kiosk);
+    manager.save()
 }
 
 #[tauri::command]
@@ -374,7 +980,72 @@ pub async fn export_settings() -> Result<String, String> {
 #[tauri::command]
 pub async fn import_settings(data: String) -> Result<(), String> {
     let mut manager = SETTINGS_MANAGER.write().await;
-    manager.import_settings(&data)
+    manager.import_settings(&data)?;
+    publish_kiosk_settings(&manager.settings.kiosk);
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn add_startup_tab(url: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.add_startup_tab(&url)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn remove_startup_tab(url: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.remove_startup_tab(&url)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn get_startup_tabs() -> Result<Vec<String>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.general.startup_tabs.clone())
+}
+
+#[tauri::command]
+pub async fn list_search_engines() -> Result<Vec<OrderedSearchEngine>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(manager.settings.search.ordered_engines())
+}
+
+#[tauri::command]
+pub async fn reorder_search_engines(order: Vec<String>) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.reorder_search_engines(order)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn block_search_domain(domain: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.block_search_domain(&domain)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn unblock_search_domain(domain: String) -> Result<(), String> {
+    let mut manager = SETTINGS_MANAGER.write().await;
+    manager.unblock_search_domain(&domain)?;
+    manager.save()
+}
+
+#[tauri::command]
+pub async fn get_search_domain_blocklist() -> Result<Vec<String>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    let mut domains: Vec<String> = manager.settings.search.search_domain_blocklist.iter().cloned().collect();
+    domains.sort();
+    Ok(domains)
+}
+
+#[tauri::command]
+pub async fn filter_blocked_search_results(urls: Vec<String>) -> Result<Vec<String>, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    Ok(urls.into_iter()
+        .filter(|url| !manager.settings.search.is_blocked_search_result(url))
+        .collect())
 }
 
 #[tauri::command]
@@ -383,8 +1054,267 @@ pub async fn get_search_url(query: String) -> Result<Option<String>, String> {
     Ok(manager.get_search_url(&query))
 }
 
+#[tauri::command]
+pub async fn get_search_url_for_engine(query: String, engine_id: String) -> Result<String, String> {
+    let manager = SETTINGS_MANAGER.read().await;
+    manager.get_search_url_for_engine(&query, &engine_id)
+}
+
 #[tauri::command]
 pub async fn get_suggestion_url(query: String) -> Result<Option<String>, String> {
     let manager = SETTINGS_MANAGER.read().await;
     Ok(manager.get_suggestion_url(&query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> SettingsManager {
+        SettingsManager {
+            settings: BrowserSettings::default(),
+        }
+    }
+
+    #[test]
+    fn general_update_keeps_fields_it_leaves_out() {
+        let mut manager = manager();
+        manager.add_startup_tab("https://example.com/").unwrap();
+        
+        manager.update_general_settings(serde_json::json!({ "homepage": "https://start.example.org/" })).unwrap();
+        
+        assert_eq!(manager.settings.general.homepage, "https://start.example.org/");
+        assert_eq!(manager.settings.general.startup_tabs, vec!["https://example.com/".to_string()]);
+    }
+
+    #[test]
+    fn partial_privacy_update_keeps_other_fields() {
+        let mut manager = manager();
+        manager.add_sensitive_domain("bank.example.com").unwrap();
+        manager.settings.privacy.auto_update_filter_lists = true;
+        manager.settings.privacy.filter_update_interval_hours = 6;
+        
+        manager.update_privacy_settings(serde_json::json!({ "block_ads": false })).unwrap();
+        
+        let privacy = &manager.settings.privacy;
+        assert!(!privacy.block_ads);
+        assert!(privacy.sensitive_domains.contains("bank.example.com"));
+        assert!(privacy.auto_update_filter_lists);
+        assert_eq!(privacy.filter_update_interval_hours, 6);
+        assert_eq!(privacy.tracking_params, default_tracking_params());
+    }
+
+    #[test]
+    fn appearance_update_keeps_domain_css_unless_given() {
+        let mut manager = manager();
+        manager.set_domain_css("example.com", "body { color: red; }").unwrap();
+        
+        manager.update_appearance_settings(serde_json::json!({ "theme": "dark" })).unwrap();
+        assert_eq!(manager.settings.appearance.theme, "dark");
+        assert_eq!(manager.settings.appearance.domain_css.len(), 1);
+        
+        manager.update_appearance_settings(serde_json::json!({ "domain_css": {} })).unwrap();
+        assert!(manager.settings.appearance.domain_css.is_empty());
+    }
+
+    #[test]
+    fn advanced_update_keeps_data_saver() {
+        let mut manager = manager();
+        manager.set_data_saver(true);
+        
+        manager.update_advanced_settings(serde_json::json!({ "enable_javascript": false })).unwrap();
+        
+        assert!(!manager.settings.advanced.enable_javascript);
+        assert!(manager.settings.advanced.data_saver);
+    }
+
+    #[test]
+    fn network_settings_read_the_old_connection_limit_name() {
+        let network: NetworkSettings = serde_json::from_value(serde_json::json!({
+            "connect_timeout_secs": 10,
+            "request_timeout_secs": 15,
+            "max_connections_per_host": 4,
+        })).unwrap();
+        
+        assert_eq!(network.max_idle_connections_per_host, 4);
+    }
+
+    #[test]
+    fn data_saver_turns_off_images_previews_and_favicon_prefetch() {
+        let mut manager = manager();
+        let policy = manager.settings.data_usage_policy();
+        assert!(policy.load_images && policy.show_tab_previews && policy.prefetch_favicons);
+        assert!(!policy.prefer_reader_mode);
+        
+        manager.set_data_saver(true);
+        
+        let policy = manager.settings.data_usage_policy();
+        assert!(policy.data_saver);
+        assert!(!policy.load_images);
+        assert!(!policy.show_tab_previews);
+        assert!(!policy.prefetch_favicons);
+        assert!(policy.prefer_reader_mode);
+    }
+
+    #[test]
+    fn domain_css_can_be_set_read_and_cleared() {
+        let mut manager = manager();
+        manager.set_domain_css("https://www.Example.com/page", "body { color: red; }").unwrap();
+        
+        assert_eq!(manager.get_domain_css("example.com").unwrap().as_deref(), Some("body { color: red; }"));
+        
+        manager.set_domain_css("example.com", "body { color: blue; }").unwrap();
+        assert_eq!(manager.get_domain_css("example.com").unwrap().as_deref(), Some("body { color: blue; }"));
+        
+        manager.clear_domain_css("example.com").unwrap();
+        assert_eq!(manager.get_domain_css("example.com").unwrap(), None);
+        assert!(manager.clear_domain_css("example.com").is_err());
+    }
+
+    #[test]
+    fn oversized_domain_css_is_rejected() {
+        let mut manager = manager();
+        let css = "a".repeat(MAX_CUSTOM_CSS_BYTES + 1);
+        
+        assert!(manager.set_domain_css("example.com", &css).is_err());
+        assert!(manager.settings.appearance.domain_css.is_empty());
+    }
+
+    #[test]
+    fn custom_css_combines_global_and_matching_domains() {
+        let mut manager = manager();
+        manager.settings.appearance.custom_css = Some("html { font-size: 18px; }".to_string());
+        manager.set_domain_css("example.com", "body { color: red; }").unwrap();
+        manager.set_domain_css("news.example.com", "body { color: blue; }").unwrap();
+        manager.set_domain_css("other.org", "body { color: green; }").unwrap();
+        
+        let appearance = &manager.settings.appearance;
+        assert_eq!(
+            appearance.resolve_custom_css("news.example.com").as_deref(),
+            Some("html { font-size: 18px; }\nbody { color: red; }\nbody { color: blue; }"),
+        );
+        assert_eq!(
+            appearance.resolve_custom_css("shop.example.com").as_deref(),
+            Some("html { font-size: 18px; }\nbody { color: red; }"),
+        );
+        assert_eq!(appearance.resolve_custom_css("example.net").as_deref(), Some("html { font-size: 18px; }"));
+    }
+
+    #[test]
+    fn kiosk_allows_allowlisted_domains_and_their_subdomains() {
+        let mut manager = manager();
+        manager.set_kiosk_allowlist(&["https://www.example.com/".to_string()]).unwrap();
+        manager.set_kiosk_mode(true).unwrap();
+        
+        let kiosk = &manager.settings.kiosk;
+        assert!(kiosk.allows("https://example.com/"));
+        assert!(kiosk.allows("https://shop.example.com/cart"));
+        assert!(!kiosk.allows("https://example.org/"));
+        assert!(!kiosk.allows("https://notexample.com/"));
+        assert!(!kiosk.allows("file:///etc/passwd"));
+    }
+
+    #[test]
+    fn kiosk_mode_off_allows_everything() {
+        let mut manager = manager();
+        manager.set_kiosk_allowlist(&["example.com".to_string()]).unwrap();
+        
+        assert!(manager.settings.kiosk.allows("https://example.org/"));
+        assert!(manager.set_kiosk_allowlist(&[]).is_ok());
+        assert!(manager.set_kiosk_mode(true).is_err());
+    }
+
+    #[test]
+    fn startup_tabs_can_be_added_listed_and_removed() {
+        let mut manager = manager();
+        manager.add_startup_tab("https://mail.example.com/").unwrap();
+        manager.add_startup_tab("https://news.example.com/").unwrap();
+        manager.add_startup_tab("https://mail.example.com/").unwrap();
+        assert!(manager.add_startup_tab("not a url").is_err());
+        
+        assert_eq!(manager.settings.general.startup_tabs, vec![
+            "https://mail.example.com/".to_string(),
+            "https://news.example.com/".to_string(),
+        ]);
+        
+        manager.remove_startup_tab("https://mail.example.com/").unwrap();
+        assert_eq!(manager.settings.general.startup_tabs, vec!["https://news.example.com/".to_string()]);
+        assert!(manager.remove_startup_tab("https://mail.example.com/").is_err());
+    }
+
+    fn engine_ids(manager: &SettingsManager) -> Vec<String> {
+        manager.settings.search.ordered_engines().into_iter().map(|entry| entry.id).collect()
+    }
+
+    #[test]
+    fn reordering_search_engines_and_appending_new_ones() {
+        let mut manager = manager();
+        
+        manager.reorder_search_engines(vec!["bing".to_string(), "google".to_string()]).unwrap();
+        assert_eq!(engine_ids(&manager), vec!["bing", "google", "duckduckgo"]);
+        
+        manager.add_search_engine("startpage", SearchEngine {
+            name: "Startpage".to_string(),
+            url: "https://www.startpage.com/do/search?q={searchTerms}".to_string(),
+            suggest_url: None,
+            icon: None,
+        });
+        assert_eq!(engine_ids(&manager), vec!["bing", "google", "duckduckgo", "startpage"]);
+        
+        assert!(manager.reorder_search_engines(vec!["missing".to_string()]).is_err());
+        assert!(manager.reorder_search_engines(vec!["bing".to_string(), "bing".to_string()]).is_err());
+        assert_eq!(engine_ids(&manager), vec!["bing", "google", "duckduckgo", "startpage"]);
+        
+        manager.remove_search_engine("google").unwrap();
+        assert_eq!(engine_ids(&manager), vec!["bing", "duckduckgo", "startpage"]);
+    }
+
+    #[test]
+    fn search_url_for_a_specific_engine() {
+        let manager = manager();
+        
+        assert_eq!(
+            manager.get_search_url_for_engine("rust & tauri", "bing").unwrap(),
+            "https://www.bing.com/search?q=rust%20%26%20tauri"
+        );
+        assert_eq!(manager.get_search_url("rust").unwrap(), "https://duckduckgo.com/?q=rust");
+        assert_eq!(
+            manager.get_search_url_for_engine("rust", "altavista").unwrap_err(),
+            "Search engine not found: altavista"
+        );
+    }
+
+    #[test]
+    fn blocked_search_domains_cover_subdomains() {
+        let mut manager = manager();
+        manager.block_search_domain("https://Pinterest.com/").unwrap();
+        manager.block_search_domain("spam.example").unwrap();
+        let search = &manager.settings.search;
+        
+        assert!(search.is_blocked_search_result("https://www.pinterest.com/pin/1"));
+        assert!(search.is_blocked_search_result("https://pinterest.com/"));
+        assert!(search.is_blocked_search_result("http://a.b.spam.example/page"));
+        assert!(!search.is_blocked_search_result("https://notpinterest.com/"));
+        assert!(!search.is_blocked_search_result("https://example.org/pinterest.com"));
+        
+        manager.unblock_search_domain("pinterest.com").unwrap();
+        assert!(!manager.settings.search.is_blocked_search_result("https://www.pinterest.com/pin/1"));
+        assert!(manager.unblock_search_domain("pinterest.com").is_err());
+    }
+
+    #[test]
+    fn explicit_theme_wins_over_the_os_theme() {
+        let mut appearance = BrowserSettings::default().appearance;
+        
+        appearance.theme = "dark".to_string();
+        assert_eq!(appearance.resolve_theme(Some(ResolvedTheme::Light)), ResolvedTheme::Dark);
+        assert!(!appearance.follows_system_theme());
+        
+        appearance.theme = "Light".to_string();
+        assert_eq!(appearance.resolve_theme(Some(ResolvedTheme::Dark)), ResolvedTheme::Light);
+        
+        appearance.theme = "system".to_string();
+        assert_eq!(appearance.resolve_theme(Some(ResolvedTheme::Dark)), ResolvedTheme::Dark);
+        assert_eq!(appearance.resolve_theme(None), ResolvedTheme::Light);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+
+/// Kept in the default profile directory; holds the path of a relocated profile.
+const PROFILE_LOCATION_FILE: &str = "profile-location";
+
+static PROFILE_DIR: Lazy<RwLock<PathBuf>> = Lazy::new(|| {
+    let location = std::fs::read_to_string(default_profile_dir().join(PROFILE_LOCATION_FILE))
+        .ok()
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| path.is_absolute());
+    RwLock::new(location.unwrap_or_else(default_profile_dir))
+});
+
+fn default_profile_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default())
+        .join("sw3do-browser")
+}
+
+/// Directory holding the browser profile's persisted state.
+pub fn profile_dir() -> PathBuf {
+    PROFILE_DIR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn ensure_writable(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Profile directory is not writable: {}", e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Copies `files` into `dir`, where none of them exist yet. On failure the
+/// copies are removed again, leaving `dir` as it was.
+fn copy_files(files: &[PathBuf], dir: &Path) -> Result<(), String> {
+    for file in files {
+        if let Some(name) = file.file_name() {
+            if let Err(e) = std::fs::copy(file, dir.join(name)) {
+                remove_copies(files, dir);
+                return Err(format!("Failed to copy {}: {}", file.display(), e));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove_copies(files: &[PathBuf], dir: &Path) {
+    for name in files.iter().filter_map(|file| file.file_name()) {
+        let _ = std::fs::remove_file(dir.join(name));
+    }
+}
+
+/// Moves every persisted file into `new_dir` and makes it the profile
+/// directory, remembered across launches. Refuses to overwrite files
+/// already present in `new_dir`. Everything is copied before the switch and
+/// the originals are deleted only after it, so a failure part way leaves the
+/// profile where it was.
+pub fn relocate_profile_dir(new_dir: &Path) -> Result<PathBuf, String> {
+    if !new_dir.is_absolute() {
+        return Err("Profile directory must be an absolute path".to_string());
+    }
+    ensure_writable(new_dir)?;
+    
+    let new_dir = new_dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve profile directory: {}", e))?;
+    let mut current = PROFILE_DIR.write().unwrap_or_else(|e| e.into_inner());
+    if current.canonicalize().is_ok_and(|dir| dir == new_dir) {
+        return Ok(new_dir);
+    }
+    
+    let files: Vec<PathBuf> = match std::fs::read_dir(&*current) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.file_name().is_some_and(|name| name != PROFILE_LOCATION_FILE))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    
+    if let Some(existing) = files.iter().find_map(|file| file.file_name().map(|name| new_dir.join(name)).filter(|target| target.exists())) {
+        return Err(format!("{} already exists", existing.display()));
+    }
+    
+    copy_files(&files, &new_dir)?;
+    
+    let default_dir = default_profile_dir();
+    let location_file = default_dir.join(PROFILE_LOCATION_FILE);
+    if default_dir.canonicalize().is_ok_and(|dir| dir == new_dir) {
+        let _ = std::fs::remove_file(&location_file);
+    } else if let Err(e) = std::fs::create_dir_all(&default_dir)
+        .and_then(|_| std::fs::write(&location_file, new_dir.to_string_lossy().as_bytes()))
+    {
+        remove_copies(&files, &new_dir);
+        return Err(format!("Failed to record profile location: {}", e));
+    }
+    
+    *current = new_dir.clone();
+    for file in &files {
+        let _ = std::fs::remove_file(file);
+    }
+    Ok(new_dir)
+}
+
+pub fn load_json<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let data = std::fs::read_to_string(profile_dir().join(file_name)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+/// Writes to a temporary file first so a crash mid-write never leaves a truncated file behind.
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) -> Result<(), String> {
+    let dir = profile_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    
+    let data = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file_name, e))?;
+    
+    let path = dir.join(file_name);
+    let temp_path = dir.join(format!("{}.tmp", file_name));
+    std::fs::write(&temp_path, data)
+        .map_err(|e| format!("Failed to write {}: {}", file_name, e))?;
+    std::fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to write {}: {}", file_name, e))
+}
+
+#[tauri::command]
+pub async fn get_profile_directory() -> Result<String, String> {
+    Ok(profile_dir().to_string_lossy().into_owned())
+}
+
+#[tauri::command]
+pub async fn set_profile_directory(path: String) -> Result<String, String> {
+    let relocate = tokio::task::spawn_blocking(move || relocate_profile_dir(Path::new(&path)));
+    let dir = super::history::with_history_closed(relocate).await
+        .map_err(|e| format!("Failed to move profile: {}", e))??;
+    Ok(dir.to_string_lossy().into_owned())
+}
\ No newline at end of file
@@ -1,8 +1,25 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tokio::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use base64::Engine;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{RwLock, Semaphore};
 use once_cell::sync::Lazy;
 use uuid::Uuid;
+use super::session::HistoryEntry;
+use super::storage;
+
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_CONCURRENT_METADATA_FETCHES: usize = 4;
+const MIN_SUGGESTED_GROUP_SIZE: usize = 2;
+const MAX_TAB_SHARE_BYTES: usize = 4 * 1024;
+const MAX_SHARE_EXCERPT_CHARS: usize = 500;
+const MAX_CLOSED_WINDOWS: usize = 10;
+const DEFAULT_CLOSED_TAB_LIMIT: usize = 25;
+const TAB_STATE_FILE: &str = "tabs.json";
+const TAB_STATE_SAVE_DELAY: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tab {
@@ -14,10 +31,16 @@ pub struct Tab {
     pub is_loading: bool,
     pub is_pinned: bool,
     pub is_muted: bool,
+    pub is_audible: bool,
+    pub is_discarded: bool,
     pub is_private: bool,
     pub zoom_level: f64,
     pub can_go_back: bool,
     pub can_go_forward: bool,
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default)]
+    pub history_index: usize,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_accessed: chrono::DateTime<chrono::Utc>,
 }
@@ -31,18 +54,145 @@ pub struct TabStats {
     pub loading_tabs: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayoutTab {
+    pub url: String,
+    pub title: String,
+    pub is_pinned: bool,
+    pub is_muted: bool,
+    pub zoom_level: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowLayout {
+    pub tabs: Vec<LayoutTab>,
+    pub active_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedWindow {
+    pub window_id: String,
+    pub layout: WindowLayout,
+    pub closed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A window's non-private tabs as saved for the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedWindowTabs {
+    pub tabs: Vec<Tab>,
+    pub active_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedTabInfo {
+    pub tab_id: String,
+    pub window_id: String,
+    /// Position in the window's tab strip when it was closed.
+    pub index: usize,
+    pub url: String,
+    pub title: String,
+    pub favicon: Option<String>,
+    pub history: Vec<HistoryEntry>,
+    pub history_index: usize,
+    pub closed_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestedGroup {
+    pub name: String,
+    pub tab_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowTabRef {
+    pub window_id: String,
+    pub tab_id: String,
+}
+
+/// Disagreements between `tabs`, `window_tabs` and `active_tabs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Tabs that are not listed in their own window's tab strip.
+    pub orphan_tabs: Vec<String>,
+    /// Strip entries pointing at a tab that no longer exists.
+    pub missing_tabs: Vec<WindowTabRef>,
+    /// Strip entries for a tab that belongs to another window.
+    pub misplaced_tabs: Vec<WindowTabRef>,
+    /// Repeated entries of the same tab within one strip.
+    pub duplicate_tabs: Vec<WindowTabRef>,
+    /// Windows whose active tab is missing or not part of the window.
+    pub dangling_active_tabs: Vec<String>,
+    /// Windows with tabs but no active tab.
+    pub windows_without_active_tab: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.orphan_tabs.is_empty()
+            && self.missing_tabs.is_empty()
+            && self.misplaced_tabs.is_empty()
+            && self.duplicate_tabs.is_empty()
+            && self.dangling_active_tabs.is_empty()
+            && self.windows_without_active_tab.is_empty()
+    }
+}
+
+/// What a share code carries. Kept small so the code fits in a link or chat message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TabShare {
+    pub url: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryPressurePolicy {
+    pub threshold_mb: u64,
+    pub min_tabs_to_keep: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabDiscardedEvent {
+    pub tab_id: String,
+    pub window_id: String,
+    pub used_memory_mb: u64,
+}
+
 static TAB_MANAGER: Lazy<RwLock<TabManager>> = Lazy::new(|| {
     RwLock::new(TabManager::new())
 });
 
+static TAB_STATE_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
 pub struct TabManager {
     pub tabs: HashMap<String, Tab>,
     pub window_tabs: HashMap<String, Vec<String>>,
     pub active_tabs: HashMap<String, String>,
+    /// Per window, the tabs in the order they were last activated, most recent first.
+    pub mru_tabs: HashMap<String, Vec<String>>,
+    pub memory_pressure_policy: Option<MemoryPressurePolicy>,
+    /// Most recently closed first.
+    pub closed_windows: Vec<ClosedWindow>,
+    /// Most recently closed first; private tabs are never recorded.
+    pub closed_tabs: Vec<ClosedTabInfo>,
+    pub closed_tab_limit: usize,
+    /// Tabs saved by the previous launch, held until `restore_tabs` takes them.
+    pub restorable_windows: Vec<SavedWindowTabs>,
+    /// Whether `restore_tabs` has run. Until it has, the tab state file still
+    /// holds the previous session and is not written.
+    pub tab_state_restored: bool,
 }
 
 impl Tab {
     pub fn new(window_id: String, url: String, is_private: bool) -> Self {
+        let now = chrono::Utc::now();
+        let history = vec![HistoryEntry {
+            url: url.clone(),
+            title: url.clone(),
+            timestamp: now,
+        }];
+        
         Self {
             id: Uuid::new_v4().to_string(),
             window_id,
@@ -52,23 +202,69 @@ impl Tab {
             is_loading: false,
             is_pinned: false,
             is_muted: false,
+            is_audible: false,
+            is_discarded: false,
             is_private,
             zoom_level: 1.0,
             can_go_back: false,
             can_go_forward: false,
-            created_at: chrono::Utc::now(),
-            last_accessed: chrono::Utc::now(),
+            history,
+            history_index: 0,
+            created_at: now,
+            last_accessed: now,
         }
     }
 
+    /// Navigating to a new URL drops any forward entries, like a real back stack.
     pub fn update_url(&mut self, url: String, title: Option<String>) {
+        let is_current = self.history.get(self.history_index)
+            .map(|entry| entry.url == url)
+            .unwrap_or(false);
+        
+        if !is_current {
+            self.history.truncate(self.history_index + 1);
+            self.history.push(HistoryEntry {
+                url: url.clone(),
+                title: url.clone(),
+                timestamp: chrono::Utc::now(),
+            });
+            self.history_index = self.history.len() - 1;
+        }
+        
         self.url = url;
         if let Some(title) = title {
+            if let Some(entry) = self.history.get_mut(self.history_index) {
+                entry.title = title.clone();
+            }
             self.title = title;
         }
+        self.sync_navigation_state();
         self.last_accessed = chrono::Utc::now();
     }
 
+    /// Moves through the back stack without adding an entry. Returns the URL
+    /// now current, or `None` when already at that end of the stack.
+    pub fn step_history(&mut self, forward: bool) -> Option<String> {
+        let index = if forward {
+            Some(self.history_index + 1).filter(|index| *index < self.history.len())?
+        } else {
+            self.history_index.checked_sub(1)?
+        };
+        
+        let entry = self.history.get(index)?.clone();
+        self.history_index = index;
+        self.url = entry.url.clone();
+        self.title = entry.title;
+        self.sync_navigation_state();
+        self.last_accessed = chrono::Utc::now();
+        Some(entry.url)
+    }
+
+    fn sync_navigation_state(&mut self) {
+        self.can_go_back = self.history_index > 0;
+        self.can_go_forward = self.history_index + 1 < self.history.len();
+    }
+
     pub fn set_loading(&mut self, loading: bool) {
         self.is_loading = loading;
         self.last_accessed = chrono::Utc::now();
@@ -94,6 +290,13 @@ impl TabManager {
             tabs: HashMap::new(),
             window_tabs: HashMap::new(),
             active_tabs: HashMap::new(),
+            mru_tabs: HashMap::new(),
+            memory_pressure_policy: None,
+            closed_windows: Vec::new(),
+            closed_tabs: Vec::new(),
+            closed_tab_limit: DEFAULT_CLOSED_TAB_LIMIT,
+            restorable_windows: storage::load_json(TAB_STATE_FILE).unwrap_or_default(),
+            tab_state_restored: false,
         }
     }
 
@@ -112,33 +315,77 @@ impl TabManager {
         }
         
         if !self.active_tabs.contains_key(&window_id) {
-            self.active_tabs.insert(window_id, tab_id.clone());
+            self.activate(&window_id, &tab_id);
         }
         
         tab_id
     }
 
+    fn activate(&mut self, window_id: &str, tab_id: &str) {
+        self.active_tabs.insert(window_id.to_string(), tab_id.to_string());
+        
+        let mru = self.mru_tabs.entry(window_id.to_string()).or_default();
+        mru.retain(|id| id != tab_id);
+        mru.insert(0, tab_id.to_string());
+    }
+
+    /// The window's tabs, most recently activated first. Tabs never activated
+    /// follow in strip order.
+    pub fn get_mru_order(&self, window_id: &str) -> Vec<String> {
+        let strip = self.window_tabs.get(window_id).cloned().unwrap_or_default();
+        let mut order: Vec<String> = self.mru_tabs.get(window_id)
+            .map(|mru| mru.iter().filter(|id| strip.contains(id)).cloned().collect())
+            .unwrap_or_default();
+        
+        for tab_id in strip {
+            if !order.contains(&tab_id) {
+                order.push(tab_id);
+            }
+        }
+        
+        order
+    }
+
     pub fn close_tab(&mut self, tab_id: &str) -> Result<(), String> {
-        let tab = self.tabs.get(tab_id).ok_or("Tab not found")?;
+        let tab = self.tabs.remove(tab_id).ok_or("Tab not found")?;
         let window_id = tab.window_id.clone();
         
-        self.tabs.remove(tab_id);
+        if let Some(mru) = self.mru_tabs.get_mut(&window_id) {
+            mru.retain(|id| id != tab_id);
+        }
         
         if let Some(window_tabs) = self.window_tabs.get_mut(&window_id) {
+            let index = window_tabs.iter().position(|id| id == tab_id).unwrap_or(window_tabs.len());
             window_tabs.retain(|id| id != tab_id);
             
-            if let Some(active_tab_id) = self.active_tabs.get(&window_id) {
-                if active_tab_id == tab_id {
-                    if let Some(new_active) = window_tabs.first() {
-                        self.active_tabs.insert(window_id.clone(), new_active.clone());
-                    } else {
-                        self.active_tabs.remove(&window_id);
-                    }
-                }
+            if !tab.is_private && self.closed_tab_limit > 0 {
+                self.closed_tabs.insert(0, ClosedTabInfo {
+                    tab_id: tab.id,
+                    window_id: window_id.clone(),
+                    index,
+                    url: tab.url,
+                    title: tab.title,
+                    favicon: tab.favicon,
+                    history: tab.history,
+                    history_index: tab.history_index,
+                    closed_at: chrono::Utc::now(),
+                });
+                self.closed_tabs.truncate(self.closed_tab_limit);
             }
             
             if window_tabs.is_empty() {
                 self.window_tabs.remove(&window_id);
+                self.mru_tabs.remove(&window_id);
+            }
+        }
+        
+        // Fall back to the tab the user was on before, not the leftmost one.
+        if self.active_tabs.get(&window_id).is_some_and(|active_tab_id| active_tab_id == tab_id) {
+            match self.get_mru_order(&window_id).into_iter().next() {
+                Some(new_active) => self.activate(&window_id, &new_active),
+                None => {
+                    self.active_tabs.remove(&window_id);
+                }
             }
         }
         
@@ -181,10 +428,11 @@ impl TabManager {
             return Err("Tab does not belong to this window".to_string());
         }
         
-        self.active_tabs.insert(window_id.to_string(), tab_id.to_string());
+        self.activate(window_id, tab_id);
         
         if let Some(tab) = self.tabs.get_mut(tab_id) {
             tab.last_accessed = chrono::Utc::now();
+            tab.is_discarded = false;
         }
         
         Ok(())
@@ -210,6 +458,10 @@ impl TabManager {
             new_tab.title = original_tab.title;
             new_tab.favicon = original_tab.favicon;
             new_tab.zoom_level = original_tab.zoom_level;
+            new_tab.history = original_tab.history;
+            new_tab.history_index = original_tab.history_index;
+            new_tab.can_go_back = original_tab.can_go_back;
+            new_tab.can_go_forward = original_tab.can_go_forward;
         }
         
         Ok(new_tab_id)
@@ -223,6 +475,9 @@ impl TabManager {
         if let Some(from_tabs) = self.window_tabs.get_mut(from_window) {
             from_tabs.retain(|id| id != tab_id);
         }
+        if let Some(mru) = self.mru_tabs.get_mut(from_window) {
+            mru.retain(|id| id != tab_id);
+        }
         
         tab.window_id = to_window.to_string();
         self.tabs.insert(tab_id.to_string(), tab);
@@ -240,6 +495,56 @@ impl TabManager {
         Ok(())
     }
 
+    /// Closes the unpinned tabs of `window_id` for which `predicate` holds,
+    /// given each tab's position in the strip. Returns the closed tab ids.
+    pub fn close_tabs_by_predicate<F>(&mut self, window_id: &str, predicate: F) -> Vec<String>
+    where
+        F: Fn(usize, &Tab) -> bool,
+    {
+        let to_close: Vec<String> = self.get_window_tabs(window_id)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, tab)| !tab.is_pinned && predicate(*index, tab))
+            .map(|(_, tab)| tab.id.clone())
+            .collect();
+        
+        for tab_id in &to_close {
+            let _ = self.close_tab(tab_id);
+        }
+        
+        to_close
+    }
+
+    /// Bulk closes around `tab_id`, which becomes active if the active tab was closed.
+    fn close_tabs_around<F>(&mut self, window_id: &str, tab_id: &str, predicate: F) -> Result<Vec<String>, String>
+    where
+        F: Fn(usize, &Tab) -> bool,
+    {
+        if self.tabs.get(tab_id).is_none_or(|tab| tab.window_id != window_id) {
+            return Err("Tab not found in window".to_string());
+        }
+        
+        let active_before = self.active_tabs.get(window_id).cloned();
+        let closed = self.close_tabs_by_predicate(window_id, predicate);
+        
+        if active_before.is_some_and(|active| closed.contains(&active)) {
+            self.activate(window_id, tab_id);
+        }
+        
+        Ok(closed)
+    }
+
+    pub fn close_tabs_to_right(&mut self, window_id: &str, tab_id: &str) -> Result<Vec<String>, String> {
+        let position = self.window_tabs.get(window_id)
+            .and_then(|tab_ids| tab_ids.iter().position(|id| id == tab_id))
+            .ok_or("Tab not found in window")?;
+        self.close_tabs_around(window_id, tab_id, |index, _| index > position)
+    }
+
+    pub fn close_other_tabs(&mut self, window_id: &str, tab_id: &str) -> Result<Vec<String>, String> {
+        self.close_tabs_around(window_id, tab_id, |_, tab| tab.id != tab_id)
+    }
+
     pub fn pin_tab(&mut self, tab_id: &str) -> Result<(), String> {
         let tab = self.tabs.get_mut(tab_id)
             .ok_or("Tab not found")?;
@@ -272,6 +577,152 @@ impl TabManager {
         Ok(())
     }
 
+    /// Opens the configured startup URLs as pinned tabs at the front of the window,
+    /// ahead of any tabs that were already restored into it. A URL that is already
+    /// open there, e.g. restored from the last session, is pinned and moved to the
+    /// front instead of being opened again.
+    pub fn open_startup_tabs(&mut self, window_id: &str, urls: &[String]) -> Vec<String> {
+        let mut tab_ids: Vec<String> = Vec::new();
+        
+        for (index, url) in urls.iter().enumerate() {
+            let open_tab = self.get_window_tabs(window_id)
+                .into_iter()
+                .find(|tab| &tab.url == url && !tab.is_private && !tab_ids.contains(&tab.id))
+                .map(|tab| tab.id.clone());
+            let tab_id = open_tab.unwrap_or_else(|| self.create_tab(window_id.to_string(), url.clone(), false));
+            
+            if let Some(tab) = self.tabs.get_mut(&tab_id) {
+                tab.is_pinned = true;
+            }
+            
+            if let Some(window_tabs) = self.window_tabs.get_mut(window_id) {
+                window_tabs.retain(|id| id != &tab_id);
+                window_tabs.insert(index.min(window_tabs.len()), tab_id.clone());
+            }
+            
+            tab_ids.push(tab_id);
+        }
+        
+        tab_ids
+    }
+
+    /// Loaded, non-private web tabs whose title and favicon can be refetched,
+    /// as `(tab_id, url)`.
+    fn metadata_refresh_targets(&self, window_id: Option<&str>) -> Vec<(String, String)> {
+        let tabs = match window_id {
+            Some(window_id) => self.get_window_tabs(window_id),
+            None => self.get_all_tabs(),
+        };
+        
+        tabs.into_iter()
+            .filter(|tab| !tab.is_private && !tab.is_discarded)
+            .filter(|tab| tab.url.starts_with("http://") || tab.url.starts_with("https://"))
+            .map(|tab| (tab.id.clone(), tab.url.clone()))
+            .collect()
+    }
+
+    pub fn update_tab_metadata(&mut self, tab_id: &str, title: Option<String>, favicon: Option<String>) -> Result<(), String> {
+        let tab = self.tabs.get_mut(tab_id)
+            .ok_or("Tab not found")?;
+        
+        if let Some(title) = title {
+            tab.title = title;
+        }
+        
+        if favicon.is_some() {
+            tab.favicon = favicon;
+        }
+        
+        Ok(())
+    }
+
+    pub fn set_tab_audible(&mut self, tab_id: &str, audible: bool) -> Result<(), String> {
+        let tab = self.tabs.get_mut(tab_id)
+            .ok_or("Tab not found")?;
+        
+        tab.is_audible = audible;
+        Ok(())
+    }
+
+    pub fn set_closed_tab_limit(&mut self, limit: usize) {
+        self.closed_tab_limit = limit;
+        self.closed_tabs.truncate(limit);
+    }
+
+    /// Reopens the most recently closed tab at its old position in its original
+    /// window, or at the end of `window_id` when the original is in neither
+    /// `open_windows` nor the tab strips. The reopened tab becomes active.
+    pub fn reopen_last_closed_tab(&mut self, window_id: &str, open_windows: &HashSet<String>) -> Option<String> {
+        if self.closed_tabs.is_empty() {
+            return None;
+        }
+        let closed = self.closed_tabs.remove(0);
+        
+        let original_open = open_windows.contains(&closed.window_id) || self.window_tabs.contains_key(&closed.window_id);
+        let target_window = if original_open { closed.window_id.clone() } else { window_id.to_string() };
+        
+        let mut tab = Tab::new(target_window.clone(), closed.url, false);
+        tab.title = closed.title;
+        tab.favicon = closed.favicon;
+        if !closed.history.is_empty() {
+            tab.history_index = closed.history_index.min(closed.history.len() - 1);
+            tab.history = closed.history;
+            tab.sync_navigation_state();
+        }
+        
+        let tab_id = tab.id.clone();
+        self.tabs.insert(tab_id.clone(), tab);
+        let strip = self.window_tabs.entry(target_window.clone()).or_default();
+        if original_open {
+            strip.insert(closed.index.min(strip.len()), tab_id.clone());
+        } else {
+            strip.push(tab_id.clone());
+        }
+        self.activate(&target_window, &tab_id);
+        
+        Some(tab_id)
+    }
+
+    pub fn set_memory_pressure_policy(&mut self, policy: Option<MemoryPressurePolicy>) {
+        self.memory_pressure_policy = policy;
+    }
+
+    /// Tabs that may be discarded under memory pressure, least recently used first.
+    /// Pinned, audible, active and already discarded tabs are never candidates, and
+    /// at least `min_tabs_to_keep` tabs are always left loaded.
+    pub fn select_discard_victims(&self, min_tabs_to_keep: usize) -> Vec<String> {
+        let loaded_tabs = self.tabs.values().filter(|t| !t.is_discarded).count();
+        let discardable = loaded_tabs.saturating_sub(min_tabs_to_keep);
+        
+        let mut candidates: Vec<&Tab> = self.tabs.values()
+            .filter(|t| !t.is_discarded && !t.is_pinned && !t.is_audible)
+            .filter(|t| self.active_tabs.get(&t.window_id) != Some(&t.id))
+            .collect();
+        
+        candidates.sort_by_key(|tab| tab.last_accessed);
+        candidates.truncate(discardable);
+        candidates.into_iter().map(|t| t.id.clone()).collect()
+    }
+
+    pub fn discard_tab(&mut self, tab_id: &str) -> Result<(), String> {
+        let tab = self.tabs.get_mut(tab_id)
+            .ok_or("Tab not found")?;
+        
+        tab.is_discarded = true;
+        tab.is_loading = false;
+        Ok(())
+    }
+
+    pub fn go_back(&mut self, tab_id: &str) -> Result<String, String> {
+        let tab = self.tabs.get_mut(tab_id).ok_or("Tab not found")?;
+        tab.step_history(false).ok_or_else(|| "Cannot go back".to_string())
+    }
+
+    pub fn go_forward(&mut self, tab_id: &str) -> Result<String, String> {
+        let tab = self.tabs.get_mut(tab_id).ok_or("Tab not found")?;
+        tab.step_history(true).ok_or_else(|| "Cannot go forward".to_string())
+    }
+
     pub fn set_tab_loading(&mut self, tab_id: &str, loading: bool) -> Result<(), String> {
         let tab = self.tabs.get_mut(tab_id)
             .ok_or("Tab not found")?;
@@ -338,6 +789,215 @@ impl TabManager {
         }
     }
 
+    pub fn capture_window_layout(&self, window_id: &str) -> WindowLayout {
+        let tabs = self.get_window_tabs(window_id);
+        let active_tab_id = self.active_tabs.get(window_id);
+        
+        WindowLayout {
+            active_index: tabs.iter().position(|tab| Some(&tab.id) == active_tab_id),
+            tabs: tabs.iter()
+                .map(|tab| LayoutTab {
+                    url: tab.url.clone(),
+                    title: tab.title.clone(),
+                    is_pinned: tab.is_pinned,
+                    is_muted: tab.is_muted,
+                    zoom_level: tab.zoom_level,
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuilds the window's tab strip to match `layout`. Existing tabs are
+    /// reused when their URL matches so they don't have to reload; the rest
+    /// are closed like any other tab and missing ones created. Returns the tab
+    /// ids in order and the ids of the closed tabs.
+    pub fn restore_window_layout(&mut self, window_id: &str, layout: &WindowLayout) -> Result<(Vec<String>, Vec<String>), String> {
+        if let Some(index) = layout.active_index {
+            if index >= layout.tabs.len() {
+                return Err("Active tab index is out of range".to_string());
+            }
+        }
+        
+        let mut unused: Vec<String> = self.window_tabs.get(window_id).cloned().unwrap_or_default();
+        let is_private = unused.first()
+            .and_then(|id| self.tabs.get(id))
+            .map(|tab| tab.is_private)
+            .unwrap_or(false);
+        
+        let mut ordered = Vec::new();
+        for layout_tab in &layout.tabs {
+            let reusable = unused.iter()
+                .position(|id| self.tabs.get(id).map(|tab| tab.url == layout_tab.url).unwrap_or(false));
+            
+            let tab_id = match reusable {
+                Some(position) => unused.remove(position),
+                None => {
+                    let tab_id = self.create_tab(window_id.to_string(), layout_tab.url.clone(), is_private);
+                    if let Some(tab) = self.tabs.get_mut(&tab_id) {
+                        tab.title = layout_tab.title.clone();
+                    }
+                    tab_id
+                }
+            };
+            
+            if let Some(tab) = self.tabs.get_mut(&tab_id) {
+                tab.is_pinned = layout_tab.is_pinned;
+                tab.is_muted = layout_tab.is_muted;
+                tab.set_zoom_level(layout_tab.zoom_level);
+            }
+            ordered.push(tab_id);
+        }
+        
+        for tab_id in &unused {
+            let _ = self.close_tab(tab_id);
+        }
+        
+        if ordered.is_empty() {
+            self.window_tabs.remove(window_id);
+            self.active_tabs.remove(window_id);
+            self.mru_tabs.remove(window_id);
+            return Ok((ordered, unused));
+        }
+        
+        self.window_tabs.insert(window_id.to_string(), ordered.clone());
+        let active_tab_id = ordered[layout.active_index.unwrap_or(0)].clone();
+        self.set_active_tab(window_id, &active_tab_id)?;
+        
+        Ok((ordered, unused))
+    }
+
+    /// Clusters a window's tabs by registrable domain, keeping tab-strip order
+    /// within each group. Pinned tabs and domains with too few tabs are left alone.
+    pub fn suggest_tab_groups(&self, window_id: &str) -> Vec<SuggestedGroup> {
+        let mut groups: Vec<SuggestedGroup> = Vec::new();
+        
+        for tab in self.get_window_tabs(window_id) {
+            if tab.is_pinned {
+                continue;
+            }
+            
+            let Some(host) = super::network::url_host(&tab.url) else {
+                continue;
+            };
+            let domain = super::lookalike::registrable_domain(&host);
+            
+            match groups.iter_mut().find(|group| group.name == domain) {
+                Some(group) => group.tab_ids.push(tab.id.clone()),
+                None => groups.push(SuggestedGroup {
+                    name: domain,
+                    tab_ids: vec![tab.id.clone()],
+                }),
+            }
+        }
+        
+        groups.retain(|group| group.tab_ids.len() >= MIN_SUGGESTED_GROUP_SIZE);
+        groups.sort_by_key(|group| std::cmp::Reverse(group.tab_ids.len()));
+        groups
+    }
+
+    pub fn export_tab_share(&self, tab_id: &str, excerpt: Option<String>) -> Result<String, String> {
+        let tab = self.tabs.get(tab_id).ok_or("Tab not found")?;
+        let excerpt = excerpt
+            .map(|text| text.trim().chars().take(MAX_SHARE_EXCERPT_CHARS).collect::<String>())
+            .filter(|text| !text.is_empty());
+        
+        encode_tab_share(&TabShare {
+            url: tab.url.clone(),
+            title: tab.title.clone(),
+            excerpt,
+        })
+    }
+
+    pub fn import_tab_share(&mut self, window_id: &str, code: &str) -> Result<String, String> {
+        let share = decode_tab_share(code)?;
+        let tab_id = self.create_tab(window_id.to_string(), share.url, false);
+        if let Some(tab) = self.tabs.get_mut(&tab_id) {
+            tab.title = share.title;
+        }
+        Ok(tab_id)
+    }
+
+    pub fn verify_tab_integrity(&self) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        
+        for (window_id, tab_ids) in &self.window_tabs {
+            let mut seen = HashSet::new();
+            for tab_id in tab_ids {
+                let entry = WindowTabRef {
+                    window_id: window_id.clone(),
+                    tab_id: tab_id.clone(),
+                };
+                match self.tabs.get(tab_id) {
+                    None => report.missing_tabs.push(entry),
+                    Some(tab) if &tab.window_id != window_id => report.misplaced_tabs.push(entry),
+                    Some(_) if !seen.insert(tab_id) => report.duplicate_tabs.push(entry),
+                    Some(_) => {}
+                }
+            }
+            
+            if !tab_ids.is_empty() && !self.active_tabs.contains_key(window_id) {
+                report.windows_without_active_tab.push(window_id.clone());
+            }
+        }
+        
+        report.orphan_tabs = self.tabs.values()
+            .filter(|tab| !self.window_tabs.get(&tab.window_id).is_some_and(|tab_ids| tab_ids.contains(&tab.id)))
+            .map(|tab| tab.id.clone())
+            .collect();
+        
+        report.dangling_active_tabs = self.active_tabs.iter()
+            .filter(|(window_id, tab_id)| self.tabs.get(*tab_id).is_none_or(|tab| &tab.window_id != *window_id))
+            .map(|(window_id, _)| window_id.clone())
+            .collect();
+        
+        report.orphan_tabs.sort();
+        report.dangling_active_tabs.sort();
+        report.windows_without_active_tab.sort();
+        for entries in [&mut report.missing_tabs, &mut report.misplaced_tabs, &mut report.duplicate_tabs] {
+            entries.sort_by(|a, b| (&a.window_id, &a.tab_id).cmp(&(&b.window_id, &b.tab_id)));
+        }
+        
+        report
+    }
+
+    /// Drops bad strip entries, reattaches orphan tabs at the end of their
+    /// window's strip and points each window at a tab it actually owns.
+    /// Returns what was found before repairing.
+    pub fn repair_tab_integrity(&mut self) -> IntegrityReport {
+        let report = self.verify_tab_integrity();
+        if report.is_consistent() {
+            return report;
+        }
+        
+        let tabs = &self.tabs;
+        for (window_id, tab_ids) in self.window_tabs.iter_mut() {
+            let mut seen = HashSet::new();
+            tab_ids.retain(|tab_id| {
+                tabs.get(tab_id).is_some_and(|tab| &tab.window_id == window_id) && seen.insert(tab_id.clone())
+            });
+        }
+        
+        for tab_id in &report.orphan_tabs {
+            if let Some(tab) = self.tabs.get(tab_id) {
+                self.window_tabs.entry(tab.window_id.clone()).or_default().push(tab_id.clone());
+            }
+        }
+        
+        self.window_tabs.retain(|_, tab_ids| !tab_ids.is_empty());
+        
+        let window_tabs = &self.window_tabs;
+        self.active_tabs.retain(|window_id, tab_id| {
+            window_tabs.get(window_id).is_some_and(|tab_ids| tab_ids.contains(tab_id))
+        });
+        for (window_id, tab_ids) in &self.window_tabs {
+            if !self.active_tabs.contains_key(window_id) {
+                self.active_tabs.insert(window_id.clone(), tab_ids[0].clone());
+            }
+        }
+        
+        report
+    }
+
     pub fn close_window_tabs(&mut self, window_id: &str) {
         if let Some(tab_ids) = self.window_tabs.remove(window_id) {
             for tab_id in tab_ids {
@@ -345,25 +1005,290 @@ impl TabManager {
             }
         }
         self.active_tabs.remove(window_id);
+        self.mru_tabs.remove(window_id);
+    }
+
+    /// Closes the window's tabs, remembering its layout for restore unless
+    /// the window is private or empty.
+    pub fn close_window(&mut self, window_id: &str, is_private: bool) {
+        let tabs = self.get_window_tabs(window_id);
+        let remember = !is_private && !tabs.is_empty() && !tabs.iter().any(|tab| tab.is_private);
+        
+        if remember {
+            let layout = self.capture_window_layout(window_id);
+            self.closed_windows.insert(0, ClosedWindow {
+                window_id: window_id.to_string(),
+                layout,
+                closed_at: chrono::Utc::now(),
+            });
+            self.closed_windows.truncate(MAX_CLOSED_WINDOWS);
+        }
+        
+        self.close_window_tabs(window_id);
+    }
+
+    /// Every window's non-private tabs in strip order. Windows left with no
+    /// tabs are skipped.
+    pub fn saved_windows(&self) -> Vec<SavedWindowTabs> {
+        let mut window_ids: Vec<&String> = self.window_tabs.keys().collect();
+        window_ids.sort();
+        
+        window_ids.into_iter()
+            .filter_map(|window_id| {
+                let tabs: Vec<Tab> = self.get_window_tabs(window_id)
+                    .into_iter()
+                    .filter(|tab| !tab.is_private)
+                    .cloned()
+                    .collect();
+                if tabs.is_empty() {
+                    return None;
+                }
+                
+                let active_index = self.active_tabs.get(window_id)
+                    .and_then(|active_id| tabs.iter().position(|tab| &tab.id == active_id));
+                Some(SavedWindowTabs { tabs, active_index })
+            })
+            .collect()
+    }
+
+    /// The tab state to write, or `None` while the previous session has not
+    /// been restored yet and must stay on disk.
+    pub fn tab_state_to_save(&self) -> Option<Vec<SavedWindowTabs>> {
+        self.tab_state_restored.then(|| self.saved_windows())
+    }
+
+    /// Drops the previous session without restoring it, so tab state is saved again.
+    pub fn discard_restorable_windows(&mut self) {
+        self.restorable_windows.clear();
+        self.tab_state_restored = true;
+    }
+
+    /// Recreates saved tabs, history included, in `window_id` under fresh ids.
+    pub fn restore_saved_window(&mut self, window_id: &str, saved: &SavedWindowTabs) -> Vec<String> {
+        let mut tab_ids = Vec::new();
+        
+        for saved_tab in &saved.tabs {
+            let mut tab = saved_tab.clone();
+            tab.id = Uuid::new_v4().to_string();
+            tab.window_id = window_id.to_string();
+            tab.is_loading = false;
+            tab.is_audible = false;
+            
+            tab_ids.push(tab.id.clone());
+            self.window_tabs.entry(window_id.to_string()).or_default().push(tab.id.clone());
+            self.tabs.insert(tab.id.clone(), tab);
+        }
+        
+        let active = saved.active_index
+            .and_then(|index| tab_ids.get(index))
+            .or_else(|| tab_ids.first());
+        if let Some(active) = active {
+            self.active_tabs.entry(window_id.to_string()).or_insert_with(|| active.clone());
+        }
+        
+        tab_ids
+    }
+
+    pub fn take_last_closed_window(&mut self) -> Option<ClosedWindow> {
+        if self.closed_windows.is_empty() {
+            None
+        } else {
+            Some(self.closed_windows.remove(0))
+        }
+    }
+}
+
+pub fn encode_tab_share(share: &TabShare) -> Result<String, String> {
+    let json = serde_json::to_vec(share)
+        .map_err(|e| format!("Failed to serialize tab share: {}", e))?;
+    if json.len() > MAX_TAB_SHARE_BYTES {
+        return Err("Tab is too large to share".to_string());
+    }
+    
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json))
+}
+
+/// Decodes a share code, rejecting oversized payloads and anything that
+/// would open a non-web URL.
+pub fn decode_tab_share(code: &str) -> Result<TabShare, String> {
+    let code = code.trim();
+    if code.len() > MAX_TAB_SHARE_BYTES * 4 / 3 + 4 {
+        return Err("Share code is too large".to_string());
+    }
+    
+    let json = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(code)
+        .map_err(|_| "Share code is not valid".to_string())?;
+    if json.len() > MAX_TAB_SHARE_BYTES {
+        return Err("Share code is too large".to_string());
+    }
+    
+    let share: TabShare = serde_json::from_slice(&json)
+        .map_err(|_| "Share code is not valid".to_string())?;
+    
+    let url = url::Url::parse(&share.url)
+        .map_err(|_| format!("Invalid shared URL: {}", share.url))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("Unsupported shared URL: {}", share.url));
+    }
+    if share.excerpt.as_ref().is_some_and(|text| text.chars().count() > MAX_SHARE_EXCERPT_CHARS) {
+        return Err("Shared excerpt is too long".to_string());
+    }
+    
+    Ok(share)
+}
+
+fn used_memory_mb() -> u64 {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    system.used_memory() / (1024 * 1024)
+}
+
+/// Periodically checks system memory and, while usage is above the configured
+/// threshold, discards the least recently used eligible tab and closes its webview.
+pub fn start_memory_pressure_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(MEMORY_CHECK_INTERVAL).await;
+            
+            let policy = TAB_MANAGER.read().await.memory_pressure_policy.clone();
+            let Some(policy) = policy else {
+                continue;
+            };
+            
+            let used_memory_mb = used_memory_mb();
+            if used_memory_mb < policy.threshold_mb {
+                continue;
+            }
+            
+            let discarded = {
+                let mut manager = TAB_MANAGER.write().await;
+                let victim = manager.select_discard_victims(policy.min_tabs_to_keep).into_iter().next();
+                victim.and_then(|tab_id| {
+                    manager.discard_tab(&tab_id).ok()?;
+                    manager.get_tab(&tab_id).cloned()
+                })
+            };
+            
+            if let Some(tab) = discarded {
+                if let Some(webview) = app.get_webview_window(&format!("webview-{}", tab.id)) {
+                    let _ = webview.close();
+                }
+                
+                let _ = app.emit("tab://discarded", TabDiscardedEvent {
+                    tab_id: tab.id,
+                    window_id: tab.window_id,
+                    used_memory_mb,
+                });
+            }
+        }
+    });
+}
+
+/// Runs `restore_tabs` at launch so tab state is restored, and saved again,
+/// without waiting on the frontend.
+pub fn start_tab_restore(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let _ = restore_tabs(app).await;
+    });
+}
+
+/// Tabs change in bursts, so writes are coalesced: the first change schedules
+/// a save and later ones within the delay ride along with it.
+fn schedule_tab_state_save() {
+    if TAB_STATE_SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
     }
+    
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(TAB_STATE_SAVE_DELAY).await;
+        TAB_STATE_SAVE_PENDING.store(false, Ordering::SeqCst);
+        
+        if let Some(windows) = TAB_MANAGER.read().await.tab_state_to_save() {
+            let _ = storage::save_json(TAB_STATE_FILE, &windows);
+        }
+    });
+}
+
+pub async fn close_window(window_id: &str, is_private: bool) {
+    TAB_MANAGER.write().await.close_window(window_id, is_private);
+    schedule_tab_state_save();
+}
+
+pub async fn take_last_closed_window() -> Option<ClosedWindow> {
+    TAB_MANAGER.write().await.take_last_closed_window()
+}
+
+/// Puts a closed window back at the front of the list, e.g. when reopening it failed.
+pub async fn return_closed_window(closed: ClosedWindow) {
+    TAB_MANAGER.write().await.closed_windows.insert(0, closed);
+}
+
+pub async fn restore_window_tabs(window_id: &str, layout: &WindowLayout) -> Result<Vec<String>, String> {
+    let (tab_ids, closed) = TAB_MANAGER.write().await.restore_window_layout(window_id, layout)?;
+    schedule_tab_state_save();
+    for closed_id in &closed {
+        super::security::forget_tab(closed_id).await;
+    }
+    Ok(tab_ids)
+}
+
+/// A window's tab ids in strip order, and its active tab.
+pub async fn window_tab_ids(window_id: &str) -> (Vec<String>, Option<String>) {
+    let manager = TAB_MANAGER.read().await;
+    (
+        manager.window_tabs.get(window_id).cloned().unwrap_or_default(),
+        manager.active_tabs.get(window_id).cloned(),
+    )
+}
+
+pub async fn tab_url(tab_id: &str) -> Option<String> {
+    let manager = TAB_MANAGER.read().await;
+    manager.get_tab(tab_id).map(|tab| tab.url.clone())
 }
 
 #[tauri::command]
 pub async fn create_tab(window_id: String, url: String, is_private: bool) -> Result<String, String> {
     let mut manager = TAB_MANAGER.write().await;
-    Ok(manager.create_tab(window_id, url, is_private))
+    let tab_id = manager.create_tab(window_id, url, is_private);
+    schedule_tab_state_save();
+    Ok(tab_id)
 }
 
 #[tauri::command]
 pub async fn close_tab(tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.close_tab(&tab_id)
+    manager.close_tab(&tab_id)?;
+    schedule_tab_state_save();
+    super::security::forget_tab(&tab_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn close_tabs_to_right(window_id: String, tab_id: String) -> Result<Vec<String>, String> {
+    let closed = TAB_MANAGER.write().await.close_tabs_to_right(&window_id, &tab_id)?;
+    schedule_tab_state_save();
+    for closed_id in &closed {
+        super::security::forget_tab(closed_id).await;
+    }
+    Ok(closed)
+}
+
+#[tauri::command]
+pub async fn close_other_tabs(window_id: String, tab_id: String) -> Result<Vec<String>, String> {
+    let closed = TAB_MANAGER.write().await.close_other_tabs(&window_id, &tab_id)?;
+    schedule_tab_state_save();
+    for closed_id in &closed {
+        super::security::forget_tab(closed_id).await;
+    }
+    Ok(closed)
 }
 
 #[tauri::command]
 pub async fn update_tab_url(tab_id: String, url: String, title: Option<String>) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.update_tab_url(&tab_id, url, title)
+    manager.update_tab_url(&tab_id, url, title)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
@@ -378,46 +1303,78 @@ pub async fn get_all_tabs() -> Result<Vec<Tab>, String> {
     Ok(manager.get_all_tabs().into_iter().cloned().collect())
 }
 
+#[tauri::command]
+pub async fn get_window_tabs(window_id: String) -> Result<Vec<Tab>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.get_window_tabs(&window_id).into_iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn get_active_tab(window_id: String) -> Result<Option<Tab>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.get_active_tab(&window_id).cloned())
+}
+
+#[tauri::command]
+pub async fn get_mru_order(window_id: String) -> Result<Vec<String>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.get_mru_order(&window_id))
+}
+
 #[tauri::command]
 pub async fn set_active_tab(window_id: String, tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.set_active_tab(&window_id, &tab_id)
+    manager.set_active_tab(&window_id, &tab_id)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn duplicate_tab(tab_id: String) -> Result<String, String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.duplicate_tab(&tab_id)
+    let tab_id = manager.duplicate_tab(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(tab_id)
 }
 
 #[tauri::command]
 pub async fn move_tab(tab_id: String, from_window: String, to_window: String, index: Option<usize>) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.move_tab(&tab_id, &from_window, &to_window, index)
+    manager.move_tab(&tab_id, &from_window, &to_window, index)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn pin_tab(tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.pin_tab(&tab_id)
+    manager.pin_tab(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn unpin_tab(tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.unpin_tab(&tab_id)
+    manager.unpin_tab(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn mute_tab(tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.mute_tab(&tab_id)
+    manager.mute_tab(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn unmute_tab(tab_id: String) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.unmute_tab(&tab_id)
+    manager.unmute_tab(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(())
 }
 
 #[tauri::command]
@@ -433,47 +1390,638 @@ pub async fn stop_tab_loading(tab_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn go_back(tab_id: String) -> Result<(), String> {
-    let manager = TAB_MANAGER.read().await;
-    if let Some(tab) = manager.get_tab(&tab_id) {
-        if tab.can_go_back {
-            Ok(())
-        } else {
-            Err("Cannot go back".to_string())
+pub async fn go_back(app: AppHandle, tab_id: String) -> Result<(), String> {
+    let url = TAB_MANAGER.write().await.go_back(&tab_id)?;
+    schedule_tab_state_save();
+    super::engine::set_webview_location(&app, &tab_id, &url)
+}
+
+#[tauri::command]
+pub async fn go_forward(app: AppHandle, tab_id: String) -> Result<(), String> {
+    let url = TAB_MANAGER.write().await.go_forward(&tab_id)?;
+    schedule_tab_state_save();
+    super::engine::set_webview_location(&app, &tab_id, &url)
+}
+
+#[tauri::command]
+pub async fn zoom_in(tab_id: String) -> Result<f64, String> {
+    let mut manager = TAB_MANAGER.write().await;
+    let zoom_level = manager.zoom_in(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(zoom_level)
+}
+
+#[tauri::command]
+pub async fn zoom_out(tab_id: String) -> Result<f64, String> {
+    let mut manager = TAB_MANAGER.write().await;
+    let zoom_level = manager.zoom_out(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(zoom_level)
+}
+
+#[tauri::command]
+pub async fn reset_zoom(tab_id: String) -> Result<f64, String> {
+    let mut manager = TAB_MANAGER.write().await;
+    let zoom_level = manager.reset_zoom(&tab_id)?;
+    schedule_tab_state_save();
+    Ok(zoom_level)
+}
+
+#[tauri::command]
+pub async fn open_startup_tabs(window_id: String) -> Result<Vec<String>, String> {
+    let urls = super::settings::current_settings().await.general.startup_tabs;
+    let mut manager = TAB_MANAGER.write().await;
+    let tab_ids = manager.open_startup_tabs(&window_id, &urls);
+    schedule_tab_state_save();
+    Ok(tab_ids)
+}
+
+/// Re-fetches titles and favicons for restored tabs, whose stored values may be
+/// stale, a few at a time. Private and discarded tabs are left untouched.
+#[tauri::command]
+pub async fn refresh_restored_tabs(window_id: Option<String>) -> Result<Vec<String>, String> {
+    if !super::settings::current_settings().await.data_usage_policy().prefetch_favicons {
+        return Ok(Vec::new());
+    }
+    
+    let targets = TAB_MANAGER.read().await.metadata_refresh_targets(window_id.as_deref());
+    let fetched = fetch_tabs_metadata(targets, |url| async move {
+        super::metadata::fetch_page_metadata(&url).await
+    }).await;
+    
+    let mut manager = TAB_MANAGER.write().await;
+    Ok(fetched.into_iter()
+        .filter(|(tab_id, metadata)| manager.update_tab_metadata(tab_id, metadata.title.clone(), metadata.favicon.clone()).is_ok())
+        .map(|(tab_id, _)| tab_id)
+        .collect())
+}
+
+/// Fetches metadata for each `(tab_id, url)` with `fetch`, at most
+/// `MAX_CONCURRENT_METADATA_FETCHES` at once. Failed fetches are left out.
+async fn fetch_tabs_metadata<F, Fut>(targets: Vec<(String, String)>, fetch: F) -> Vec<(String, super::metadata::PageMetadata)>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<super::metadata::PageMetadata, String>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_METADATA_FETCHES));
+    let mut fetches = tokio::task::JoinSet::new();
+    
+    for (tab_id, url) in targets {
+        let semaphore = semaphore.clone();
+        let metadata = fetch(url);
+        fetches.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            let metadata = metadata.await.ok()?;
+            Some((tab_id, metadata))
+        });
+    }
+    
+    let mut fetched = Vec::new();
+    while let Some(result) = fetches.join_next().await {
+        if let Ok(Some(entry)) = result {
+            fetched.push(entry);
         }
-    } else {
-        Err("Tab not found".to_string())
     }
+    fetched
+}
+
+#[tauri::command]
+pub async fn capture_window_layout(window_id: String) -> Result<WindowLayout, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.capture_window_layout(&window_id))
 }
 
 #[tauri::command]
-pub async fn go_forward(tab_id: String) -> Result<(), String> {
+pub async fn restore_window_layout(window_id: String, layout: WindowLayout) -> Result<Vec<String>, String> {
+    restore_window_tabs(&window_id, &layout).await
+}
+
+#[tauri::command]
+pub async fn verify_tab_integrity() -> Result<IntegrityReport, String> {
     let manager = TAB_MANAGER.read().await;
-    if let Some(tab) = manager.get_tab(&tab_id) {
-        if tab.can_go_forward {
-            Ok(())
+    Ok(manager.verify_tab_integrity())
+}
+
+#[tauri::command]
+pub async fn repair_tab_integrity() -> Result<IntegrityReport, String> {
+    let mut manager = TAB_MANAGER.write().await;
+    let report = manager.repair_tab_integrity();
+    schedule_tab_state_save();
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn export_tab_share(tab_id: String, excerpt: Option<String>) -> Result<String, String> {
+    let manager = TAB_MANAGER.read().await;
+    manager.export_tab_share(&tab_id, excerpt)
+}
+
+#[tauri::command]
+pub async fn import_tab_share(window_id: String, code: String) -> Result<String, String> {
+    let mut manager = TAB_MANAGER.write().await;
+    let tab_id = manager.import_tab_share(&window_id, &code)?;
+    schedule_tab_state_save();
+    Ok(tab_id)
+}
+
+/// Reopens the windows and tabs saved by the previous launch, each in a new
+/// browser window, when `restore_tabs_on_startup` is on, then opens the startup
+/// tabs whatever the setting. Only the first call per launch does anything.
+/// Returns the new window ids.
+///
+/// Tab state is not saved until this has run, so a crash before then leaves the
+/// previous session on disk. With the setting off the previous session is
+/// dropped and the saved state overwritten.
+#[tauri::command]
+pub async fn restore_tabs(app: AppHandle) -> Result<Vec<String>, String> {
+    let restore = super::settings::current_settings().await.general.restore_tabs_on_startup;
+    
+    let saved = {
+        let mut manager = TAB_MANAGER.write().await;
+        if manager.tab_state_restored {
+            return Ok(Vec::new());
+        }
+        if restore {
+            std::mem::take(&mut manager.restorable_windows)
         } else {
-            Err("Cannot go forward".to_string())
+            manager.discard_restorable_windows();
+            Vec::new()
         }
-    } else {
-        Err("Tab not found".to_string())
+    };
+    
+    let mut window_ids = Vec::new();
+    for (index, saved_window) in saved.iter().enumerate() {
+        let window_id = match super::engine::create_browser_window(app.clone(), false).await {
+            Ok(window_id) => window_id,
+            Err(e) => {
+                TAB_MANAGER.write().await.restorable_windows = saved[index..].to_vec();
+                return Err(e);
+            }
+        };
+        TAB_MANAGER.write().await.restore_saved_window(&window_id, saved_window);
+        window_ids.push(window_id);
     }
+    
+    TAB_MANAGER.write().await.tab_state_restored = true;
+    let startup_result = open_startup_tabs_at_launch(&app, &mut window_ids).await;
+    schedule_tab_state_save();
+    startup_result.map(|_| window_ids)
+}
+
+/// Opens the startup tabs in the first restored window, or in a new window when
+/// nothing was restored.
+async fn open_startup_tabs_at_launch(app: &AppHandle, window_ids: &mut Vec<String>) -> Result<(), String> {
+    let urls = super::settings::current_settings().await.general.startup_tabs;
+    if urls.is_empty() {
+        return Ok(());
+    }
+    
+    let window_id = match window_ids.first() {
+        Some(window_id) => window_id.clone(),
+        None => {
+            let window_id = super::engine::create_browser_window(app.clone(), false).await?;
+            window_ids.push(window_id.clone());
+            window_id
+        }
+    };
+    
+    TAB_MANAGER.write().await.open_startup_tabs(&window_id, &urls);
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn zoom_in(tab_id: String) -> Result<f64, String> {
+pub async fn get_recently_closed_windows() -> Result<Vec<ClosedWindow>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.closed_windows.clone())
+}
+
+#[tauri::command]
+pub async fn suggest_tab_groups(window_id: String) -> Result<Vec<SuggestedGroup>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.suggest_tab_groups(&window_id))
+}
+
+#[tauri::command]
+pub async fn set_tab_audible(tab_id: String, audible: bool) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.zoom_in(&tab_id)
+    manager.set_tab_audible(&tab_id, audible)
 }
 
 #[tauri::command]
-pub async fn zoom_out(tab_id: String) -> Result<f64, String> {
+pub async fn reopen_last_closed_tab(window_id: String) -> Result<Option<String>, String> {
+    let open_windows = super::engine::window_ids().await;
+    let tab_id = TAB_MANAGER.write().await.reopen_last_closed_tab(&window_id, &open_windows);
+    if tab_id.is_some() {
+        schedule_tab_state_save();
+    }
+    Ok(tab_id)
+}
+
+#[tauri::command]
+pub async fn get_recently_closed() -> Result<Vec<ClosedTabInfo>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.closed_tabs.clone())
+}
+
+#[tauri::command]
+pub async fn set_closed_tab_limit(limit: usize) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.zoom_out(&tab_id)
+    manager.set_closed_tab_limit(limit);
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn reset_zoom(tab_id: String) -> Result<f64, String> {
+pub async fn set_memory_pressure_policy(threshold_mb: Option<u64>, min_tabs_to_keep: usize) -> Result<(), String> {
     let mut manager = TAB_MANAGER.write().await;
-    manager.reset_zoom(&tab_id)
+    manager.set_memory_pressure_policy(threshold_mb.map(|threshold_mb| MemoryPressurePolicy {
+        threshold_mb,
+        min_tabs_to_keep,
+    }));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_memory_pressure_policy() -> Result<Option<MemoryPressurePolicy>, String> {
+    let manager = TAB_MANAGER.read().await;
+    Ok(manager.memory_pressure_policy.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> TabManager {
+        TabManager {
+            tabs: HashMap::new(),
+            window_tabs: HashMap::new(),
+            active_tabs: HashMap::new(),
+            mru_tabs: HashMap::new(),
+            memory_pressure_policy: None,
+            closed_windows: Vec::new(),
+            closed_tabs: Vec::new(),
+            closed_tab_limit: DEFAULT_CLOSED_TAB_LIMIT,
+            restorable_windows: Vec::new(),
+            tab_state_restored: false,
+        }
+    }
+
+    #[test]
+    fn restoring_a_layout_closes_unused_tabs_normally() {
+        let mut manager = manager();
+        let kept = manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        let unused = manager.create_tab("w".to_string(), "https://b.example/".to_string(), false);
+        let layout = WindowLayout {
+            tabs: vec![LayoutTab {
+                url: "https://a.example/".to_string(),
+                title: "A".to_string(),
+                is_pinned: false,
+                is_muted: false,
+                zoom_level: 1.0,
+            }],
+            active_index: Some(0),
+        };
+        
+        let (tab_ids, closed) = manager.restore_window_layout("w", &layout).unwrap();
+        
+        assert_eq!(tab_ids, vec![kept]);
+        assert_eq!(closed, vec![unused.clone()]);
+        assert!(!manager.tabs.contains_key(&unused));
+        assert_eq!(manager.closed_tabs[0].tab_id, unused);
+    }
+
+    #[test]
+    fn saved_tab_state_restores_the_window() {
+        let mut manager = manager();
+        manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        let second = manager.create_tab("w".to_string(), "https://b.example/".to_string(), false);
+        manager.create_tab("w".to_string(), "https://private.example/".to_string(), true);
+        manager.update_tab_url(&second, "https://b.example/next".to_string(), None).unwrap();
+        manager.pin_tab(&second).unwrap();
+        manager.set_active_tab("w", &second).unwrap();
+        
+        assert!(manager.tab_state_to_save().is_none());
+        manager.tab_state_restored = true;
+        let json = serde_json::to_string(&manager.tab_state_to_save().unwrap()).unwrap();
+        
+        manager.close_window_tabs("w");
+        assert!(manager.tabs.is_empty());
+        
+        let saved: Vec<SavedWindowTabs> = serde_json::from_str(&json).unwrap();
+        assert_eq!(saved.len(), 1);
+        let tab_ids = manager.restore_saved_window("w", &saved[0]);
+        
+        let tabs = manager.get_window_tabs("w");
+        assert_eq!(tab_ids.len(), 2);
+        assert_eq!(tabs[0].url, "https://a.example/");
+        assert_eq!(tabs[1].url, "https://b.example/next");
+        assert!(tabs[1].is_pinned);
+        assert!(tabs[1].can_go_back);
+        assert_eq!(manager.get_active_tab("w").unwrap().id, tab_ids[1]);
+    }
+
+    #[test]
+    fn discarding_the_previous_session_allows_saving() {
+        let mut manager = manager();
+        manager.restorable_windows = vec![SavedWindowTabs { tabs: Vec::new(), active_index: None }];
+        
+        manager.discard_restorable_windows();
+        
+        assert!(manager.restorable_windows.is_empty());
+        assert_eq!(manager.tab_state_to_save().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn startup_tabs_open_pinned_at_the_front() {
+        let mut manager = manager();
+        let restored = manager.create_tab("w".to_string(), "https://restored.example/".to_string(), false);
+        let urls = vec!["https://mail.example/".to_string(), "https://news.example/".to_string()];
+        
+        let startup = manager.open_startup_tabs("w", &urls);
+        
+        let tabs = manager.get_window_tabs("w");
+        assert_eq!(tabs.len(), 3);
+        assert_eq!(tabs[0].id, startup[0]);
+        assert_eq!(tabs[1].id, startup[1]);
+        assert_eq!(tabs[2].id, restored);
+        assert!(tabs[0].is_pinned && tabs[1].is_pinned);
+        assert_eq!(tabs[0].url, "https://mail.example/");
+        assert!(!tabs[2].is_pinned);
+    }
+
+    #[test]
+    fn startup_tabs_reuse_restored_copies() {
+        let mut last_launch = manager();
+        last_launch.tab_state_restored = true;
+        let urls = vec!["https://mail.example/".to_string()];
+        last_launch.open_startup_tabs("w", &urls);
+        last_launch.create_tab("w".to_string(), "https://other.example/".to_string(), false);
+        let saved = last_launch.tab_state_to_save().unwrap();
+        
+        let mut next_launch = manager();
+        next_launch.restore_saved_window("w", &saved[0]);
+        let startup = next_launch.open_startup_tabs("w", &urls);
+        
+        let tabs = next_launch.get_window_tabs("w");
+        assert_eq!(tabs.len(), 2);
+        assert_eq!(tabs[0].id, startup[0]);
+        assert_eq!(tabs[0].url, "https://mail.example/");
+        assert!(tabs[0].is_pinned);
+    }
+
+    #[test]
+    fn discard_victims_are_least_recently_used_unprotected_tabs() {
+        let mut manager = manager();
+        let active = manager.create_tab("w".to_string(), "https://active.example/".to_string(), false);
+        let ids: Vec<String> = ["old", "pinned", "audible", "newer", "newest"].iter()
+            .map(|name| manager.create_tab("w".to_string(), format!("https://{}.example/", name), false))
+            .collect();
+        let now = chrono::Utc::now();
+        for (minutes, id) in [(50, &ids[0]), (60, &ids[1]), (60, &ids[2]), (20, &ids[3]), (10, &ids[4])] {
+            manager.tabs.get_mut(id).unwrap().last_accessed = now - chrono::Duration::minutes(minutes);
+        }
+        manager.pin_tab(&ids[1]).unwrap();
+        manager.set_tab_audible(&ids[2], true).unwrap();
+        
+        assert_eq!(manager.select_discard_victims(0), vec![ids[0].clone(), ids[3].clone(), ids[4].clone()]);
+        assert!(!manager.select_discard_victims(0).contains(&active));
+        
+        assert_eq!(manager.select_discard_victims(4), vec![ids[0].clone(), ids[3].clone()]);
+        assert!(manager.select_discard_victims(6).is_empty());
+        
+        manager.discard_tab(&ids[0]).unwrap();
+        assert_eq!(manager.select_discard_victims(4), vec![ids[3].clone()]);
+    }
+
+    #[test]
+    fn closed_window_restores_into_a_new_window() {
+        let mut manager = manager();
+        let first = manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        let second = manager.create_tab("w".to_string(), "https://b.example/".to_string(), false);
+        manager.tabs.get_mut(&first).unwrap().title = "A".to_string();
+        manager.pin_tab(&first).unwrap();
+        manager.mute_tab(&second).unwrap();
+        manager.set_active_tab("w", &second).unwrap();
+        manager.create_tab("private".to_string(), "https://c.example/".to_string(), true);
+        
+        manager.close_window("private", true);
+        manager.close_window("w", false);
+        assert!(manager.get_window_tabs("w").is_empty());
+        
+        let closed = manager.take_last_closed_window().unwrap();
+        assert_eq!(closed.window_id, "w");
+        assert!(manager.take_last_closed_window().is_none());
+        let (tab_ids, unused) = manager.restore_window_layout("w2", &closed.layout).unwrap();
+        
+        assert!(unused.is_empty());
+        let tabs = manager.get_window_tabs("w2");
+        assert_eq!(tabs.len(), 2);
+        assert_eq!(tabs[0].url, "https://a.example/");
+        assert_eq!(tabs[0].title, "A");
+        assert!(tabs[0].is_pinned);
+        assert_eq!(tabs[1].url, "https://b.example/");
+        assert!(tabs[1].is_muted);
+        assert_eq!(manager.get_active_tab("w2").unwrap().id, tab_ids[1]);
+    }
+
+    #[tokio::test]
+    async fn restored_tab_titles_update_from_fetched_metadata() {
+        let mut last_launch = manager();
+        let stale = last_launch.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        last_launch.tabs.get_mut(&stale).unwrap().title = "Old title".to_string();
+        last_launch.create_tab("w".to_string(), "https://unreachable.example/".to_string(), false);
+        last_launch.create_tab("w".to_string(), "about:settings".to_string(), false);
+        let discarded = last_launch.create_tab("w".to_string(), "https://discarded.example/".to_string(), false);
+        last_launch.tabs.get_mut(&discarded).unwrap().is_discarded = true;
+        last_launch.tab_state_restored = true;
+        let saved = last_launch.tab_state_to_save().unwrap();
+        
+        let mut manager = manager();
+        manager.restore_saved_window("w", &saved[0]);
+        let targets = manager.metadata_refresh_targets(Some("w"));
+        assert_eq!(targets.len(), 2);
+        
+        let fetched = fetch_tabs_metadata(targets, |url| async move {
+            if url.contains("unreachable") {
+                return Err("Connection refused".to_string());
+            }
+            Ok(super::super::metadata::PageMetadata {
+                title: Some("New title".to_string()),
+                favicon: Some(format!("{}favicon.ico", url)),
+                url,
+            })
+        }).await;
+        for (tab_id, metadata) in fetched {
+            manager.update_tab_metadata(&tab_id, metadata.title, metadata.favicon).unwrap();
+        }
+        
+        let tabs = manager.get_window_tabs("w");
+        assert_eq!(tabs[0].title, "New title");
+        assert_eq!(tabs[0].favicon.as_deref(), Some("https://a.example/favicon.ico"));
+        assert_ne!(tabs[1].title, "New title");
+    }
+
+    #[test]
+    fn duplicate_keeps_the_navigation_history() {
+        let mut manager = manager();
+        let original = manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        manager.update_tab_url(&original, "https://a.example/one".to_string(), Some("One".to_string())).unwrap();
+        manager.update_tab_url(&original, "https://a.example/two".to_string(), Some("Two".to_string())).unwrap();
+        manager.go_back(&original).unwrap();
+        
+        let duplicate = manager.duplicate_tab(&original).unwrap();
+        
+        let (original, duplicate) = (&manager.tabs[&original], &manager.tabs[&duplicate]);
+        assert_ne!(original.id, duplicate.id);
+        assert_eq!(duplicate.history.len(), original.history.len());
+        assert_eq!(duplicate.history.len(), 3);
+        assert_eq!(duplicate.history_index, original.history_index);
+        assert_eq!(duplicate.url, "https://a.example/one");
+        assert!(duplicate.can_go_back);
+        assert!(duplicate.can_go_forward);
+        assert_eq!(manager.get_window_tabs("w").len(), 2);
+    }
+
+    #[test]
+    fn share_codes_round_trip_and_reject_unsafe_urls() {
+        let share = TabShare {
+            url: "https://example.com/article?id=7".to_string(),
+            title: "An article".to_string(),
+            excerpt: Some("The first paragraph".to_string()),
+        };
+        
+        let decoded = decode_tab_share(&format!(" {} ", encode_tab_share(&share).unwrap())).unwrap();
+        
+        assert_eq!(decoded, share);
+        
+        let script = TabShare {
+            url: "javascript:alert(1)".to_string(),
+            title: String::new(),
+            excerpt: None,
+        };
+        assert!(decode_tab_share(&encode_tab_share(&script).unwrap()).is_err());
+        assert!(decode_tab_share("not a share code!").is_err());
+    }
+
+    #[test]
+    fn tab_groups_cluster_by_domain_and_skip_singletons() {
+        let mut manager = manager();
+        let docs = manager.create_tab("w".to_string(), "https://docs.example.com/a".to_string(), false);
+        let lone = manager.create_tab("w".to_string(), "https://other.org/".to_string(), false);
+        let www = manager.create_tab("w".to_string(), "https://www.example.com/b".to_string(), false);
+        let pinned = manager.create_tab("w".to_string(), "https://example.com/c".to_string(), false);
+        manager.pin_tab(&pinned).unwrap();
+        
+        let groups = manager.suggest_tab_groups("w");
+        
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "example.com");
+        assert_eq!(groups[0].tab_ids, vec![docs, www]);
+        assert!(!groups.iter().any(|group| group.tab_ids.contains(&lone)));
+    }
+
+    #[test]
+    fn integrity_check_detects_and_repairs_each_inconsistency() {
+        let mut manager = manager();
+        let a = manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        let b = manager.create_tab("w".to_string(), "https://b.example/".to_string(), false);
+        let c = manager.create_tab("w".to_string(), "https://c.example/".to_string(), false);
+        let d = manager.create_tab("v".to_string(), "https://d.example/".to_string(), false);
+        assert!(manager.verify_tab_integrity().is_consistent());
+        
+        let strip = manager.window_tabs.get_mut("w").unwrap();
+        strip.retain(|id| id != &c);
+        strip.extend(["ghost".to_string(), a.clone(), d.clone()]);
+        manager.active_tabs.insert("w".to_string(), "ghost".to_string());
+        manager.active_tabs.remove("v");
+        let entry = |tab_id: &str| WindowTabRef { window_id: "w".to_string(), tab_id: tab_id.to_string() };
+        
+        let report = manager.verify_tab_integrity();
+        
+        assert_eq!(report.orphan_tabs, vec![c.clone()]);
+        assert_eq!(report.missing_tabs, vec![entry("ghost")]);
+        assert_eq!(report.misplaced_tabs, vec![entry(&d)]);
+        assert_eq!(report.duplicate_tabs, vec![entry(&a)]);
+        assert_eq!(report.dangling_active_tabs, vec!["w".to_string()]);
+        assert_eq!(report.windows_without_active_tab, vec!["v".to_string()]);
+        
+        assert!(!manager.repair_tab_integrity().is_consistent());
+        
+        assert!(manager.verify_tab_integrity().is_consistent());
+        assert_eq!(manager.window_tabs["w"], vec![a.clone(), b, c]);
+        assert_eq!(manager.window_tabs["v"], vec![d.clone()]);
+        assert_eq!(manager.active_tabs["w"], a);
+        assert_eq!(manager.active_tabs["v"], d);
+    }
+
+    #[test]
+    fn bulk_closing_keeps_pinned_tabs() {
+        let mut manager = manager();
+        let pinned_left = manager.create_tab("w".to_string(), "https://pinned-left.example/".to_string(), false);
+        let left = manager.create_tab("w".to_string(), "https://left.example/".to_string(), false);
+        let anchor = manager.create_tab("w".to_string(), "https://anchor.example/".to_string(), false);
+        let pinned_right = manager.create_tab("w".to_string(), "https://pinned-right.example/".to_string(), false);
+        let right = manager.create_tab("w".to_string(), "https://right.example/".to_string(), false);
+        manager.pin_tab(&pinned_left).unwrap();
+        manager.pin_tab(&pinned_right).unwrap();
+        manager.set_active_tab("w", &right).unwrap();
+        
+        let closed = manager.close_tabs_to_right("w", &anchor).unwrap();
+        
+        assert_eq!(closed, vec![right]);
+        assert_eq!(manager.window_tabs["w"], vec![pinned_left.clone(), left.clone(), anchor.clone(), pinned_right.clone()]);
+        assert_eq!(manager.active_tabs["w"], anchor);
+        
+        let closed = manager.close_other_tabs("w", &anchor).unwrap();
+        
+        assert_eq!(closed, vec![left]);
+        assert_eq!(manager.window_tabs["w"], vec![pinned_left, anchor.clone(), pinned_right]);
+        assert_eq!(manager.active_tabs["w"], anchor);
+        assert!(manager.close_other_tabs("other", &anchor).is_err());
+    }
+
+    #[test]
+    fn closed_tabs_reopen_last_in_first_out_within_the_cap() {
+        let mut manager = manager();
+        manager.set_closed_tab_limit(2);
+        manager.create_tab("w".to_string(), "https://keep.example/".to_string(), false);
+        let urls = ["https://a.example/", "https://b.example/", "https://c.example/"];
+        for url in urls {
+            let tab_id = manager.create_tab("w".to_string(), url.to_string(), false);
+            manager.close_tab(&tab_id).unwrap();
+        }
+        let private = manager.create_tab("w".to_string(), "https://private.example/".to_string(), true);
+        manager.close_tab(&private).unwrap();
+        
+        assert_eq!(manager.closed_tabs.len(), 2);
+        
+        let open_windows = HashSet::from(["w".to_string()]);
+        let reopened: Vec<String> = std::iter::from_fn(|| manager.reopen_last_closed_tab("w", &open_windows))
+            .collect();
+        
+        let reopened_urls: Vec<&str> = reopened.iter().map(|id| manager.tabs[id].url.as_str()).collect();
+        assert_eq!(reopened_urls, vec!["https://c.example/", "https://b.example/"]);
+        assert!(manager.closed_tabs.is_empty());
+    }
+
+    #[test]
+    fn closing_the_active_tab_activates_the_previous_one() {
+        let mut manager = manager();
+        let first = manager.create_tab("w".to_string(), "https://a.example/".to_string(), false);
+        let second = manager.create_tab("w".to_string(), "https://b.example/".to_string(), false);
+        let third = manager.create_tab("w".to_string(), "https://c.example/".to_string(), false);
+        manager.pin_tab(&second).unwrap();
+        manager.set_active_tab("w", &second).unwrap();
+        manager.set_active_tab("w", &third).unwrap();
+        
+        assert_eq!(manager.get_mru_order("w"), vec![third.clone(), second.clone(), first.clone()]);
+        
+        manager.close_tab(&third).unwrap();
+        
+        assert_eq!(manager.active_tabs["w"], second);
+        
+        manager.close_tab(&second).unwrap();
+        
+        assert_eq!(manager.active_tabs["w"], first);
+        assert_eq!(manager.get_mru_order("w"), vec![first]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+/// Tags are matched case-insensitively, so they are stored trimmed and lowercased.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    Ok(tag)
+}
\ No newline at end of file
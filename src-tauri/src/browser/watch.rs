@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+use once_cell::sync::Lazy;
+use super::storage;
+
+const WATCHED_PAGES_FILE: &str = "watched_pages.json";
+const WATCH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MIN_WATCH_INTERVAL_MINUTES: u32 = 5;
+const MAX_WATCH_INTERVAL_MINUTES: u32 = 7 * 24 * 60;
+const MAX_CHANGE_EXCERPT_CHARS: usize = 200;
+
+/// Text that changes on every load without the page meaningfully changing:
+/// clock times, ISO dates and relative timestamps.
+static VOLATILE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b\d{1,2}:\d{2}(:\d{2})?\s*(am|pm)?\b|\b\d{4}-\d{2}-\d{2}(t[\d:.]+z?)?\b|\b\d+\s+(second|minute|hour|day)s?\s+ago\b").unwrap()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedPage {
+    pub url: String,
+    pub interval_minutes: u32,
+    pub added_at: chrono::DateTime<chrono::Utc>,
+    pub last_checked: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_changed: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_error: Option<String>,
+    pub content_hash: Option<String>,
+    /// Hash of each content block, kept to summarize what a change touched.
+    #[serde(default)]
+    pub block_hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageChangedEvent {
+    pub url: String,
+    pub added_blocks: usize,
+    pub removed_blocks: usize,
+    /// Start of the first block that was not on the page before.
+    pub excerpt: Option<String>,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Hashes of a page's meaningful content, as compared between checks.
+#[derive(Debug, Clone)]
+pub struct PageFingerprint {
+    pub content_hash: String,
+    pub block_hashes: Vec<String>,
+    /// Original block text, used for change excerpts.
+    pub blocks: Vec<String>,
+}
+
+static PAGE_WATCHER: Lazy<RwLock<PageWatcher>> = Lazy::new(|| {
+    RwLock::new(PageWatcher::new())
+});
+
+pub struct PageWatcher {
+    pages: HashMap<String, WatchedPage>,
+}
+
+fn hash_text(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// Fingerprints the readable content of `html`. Navigation, scripts and
+/// other boilerplate are dropped by the reader, and volatile text is masked.
+pub fn fingerprint_page(html: &str) -> PageFingerprint {
+    let (blocks, block_hashes): (Vec<String>, Vec<String>) = super::reader::readable_text_blocks(html)
+        .into_iter()
+        .filter_map(|block| {
+            let stable = VOLATILE_REGEX.replace_all(&block, "").split_whitespace().collect::<Vec<_>>().join(" ");
+            (!stable.is_empty()).then(|| (block, hash_text(&stable)))
+        })
+        .unzip();
+    
+    PageFingerprint {
+        content_hash: hash_text(&block_hashes.concat()),
+        block_hashes,
+        blocks,
+    }
+}
+
+/// Counts blocks only in `current` and only in `previous`, treating each list as a multiset.
+fn block_changes(previous: &[String], current: &[String]) -> (usize, usize) {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for hash in previous {
+        *remaining.entry(hash.as_str()).or_insert(0) += 1;
+    }
+    
+    let mut added = 0;
+    for hash in current {
+        match remaining.get_mut(hash.as_str()) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => added += 1,
+        }
+    }
+    
+    (added, remaining.values().sum())
+}
+
+impl PageWatcher {
+    pub fn new() -> Self {
+        Self {
+            pages: storage::load_json(WATCHED_PAGES_FILE).unwrap_or_default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        storage::save_json(WATCHED_PAGES_FILE, &self.pages)
+    }
+
+    /// Starts watching `url`, or changes the interval of a page already watched.
+    pub fn watch_page(&mut self, url: &str, interval_minutes: u32) -> Result<WatchedPage, String> {
+        let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("Only http and https pages can be watched".to_string());
+        }
+        if !(MIN_WATCH_INTERVAL_MINUTES..=MAX_WATCH_INTERVAL_MINUTES).contains(&interval_minutes) {
+            return Err(format!(
+                "Interval must be between {} and {} minutes",
+                MIN_WATCH_INTERVAL_MINUTES, MAX_WATCH_INTERVAL_MINUTES
+            ));
+        }
+        
+        let page = self.pages.entry(parsed.to_string()).or_insert_with(|| WatchedPage {
+            url: parsed.to_string(),
+            interval_minutes,
+            added_at: chrono::Utc::now(),
+            last_checked: None,
+            last_changed: None,
+            last_error: None,
+            content_hash: None,
+            block_hashes: Vec::new(),
+        });
+        page.interval_minutes = interval_minutes;
+        
+        Ok(page.clone())
+    }
+
+    pub fn unwatch_page(&mut self, url: &str) -> Result<(), String> {
+        let key = url::Url::parse(url).map(|parsed| parsed.to_string()).unwrap_or_else(|_| url.to_string());
+        if self.pages.remove(&key).is_none() {
+            return Err("Page is not being watched".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn get_watched_pages(&self) -> Vec<WatchedPage> {
+        let mut pages: Vec<WatchedPage> = self.pages.values().cloned().collect();
+        pages.sort_by_key(|page| page.added_at);
+        pages
+    }
+
+    /// Pages never checked, or whose interval has elapsed since the last check.
+    pub fn due_pages(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+        self.pages.values()
+            .filter(|page| page.last_checked.is_none_or(|checked| now - checked >= chrono::Duration::minutes(page.interval_minutes as i64)))
+            .map(|page| page.url.clone())
+            .collect()
+    }
+
+    /// Stores a fresh fingerprint. The first check only records a baseline;
+    /// later ones return a change summary when the content hash differs.
+    pub fn record_check(&mut self, url: &str, fingerprint: PageFingerprint, now: chrono::DateTime<chrono::Utc>) -> Option<PageChangedEvent> {
+        let page = self.pages.get_mut(url)?;
+        page.last_checked = Some(now);
+        page.last_error = None;
+        
+        let changed = page.content_hash.as_ref().is_some_and(|hash| *hash != fingerprint.content_hash);
+        let event = changed.then(|| {
+            let (added_blocks, removed_blocks) = block_changes(&page.block_hashes, &fingerprint.block_hashes);
+            let excerpt = fingerprint.blocks.iter()
+                .zip(&fingerprint.block_hashes)
+                .find(|(_, hash)| !page.block_hashes.contains(hash))
+                .map(|(block, _)| block.chars().take(MAX_CHANGE_EXCERPT_CHARS).collect());
+            
+            page.last_changed = Some(now);
+            PageChangedEvent {
+                url: page.url.clone(),
+                added_blocks,
+                removed_blocks,
+                excerpt,
+                changed_at: now,
+            }
+        });
+        
+        page.content_hash = Some(fingerprint.content_hash);
+        page.block_hashes = fingerprint.block_hashes;
+        event
+    }
+
+    pub fn record_error(&mut self, url: &str, error: String, now: chrono::DateTime<chrono::Utc>) {
+        if let Some(page) = self.pages.get_mut(url) {
+            page.last_checked = Some(now);
+            page.last_error = Some(error);
+        }
+    }
+}
+
+async fn fetch_page_html(url: &str) -> Result<String, String> {
+    let client = super::network::http_client().await?;
+    let response = super::network::send_request(client.get(url), "fetch watched page").await?;
+    if !response.status().is_success() {
+        return Err(format!("Server responded with {}", response.status()));
+    }
+    response.text()
+        .await
+        .map_err(|e| format!("Failed to read watched page: {}", e))
+}
+
+/// Periodically refetches watched pages whose interval has elapsed and emits
+/// `page://changed` when their meaningful content differs from the last check.
+pub fn start_page_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let due = PAGE_WATCHER.read().await.due_pages(chrono::Utc::now());
+            
+            for url in due {
+                let result = fetch_page_html(&url).await;
+                
+                let mut watcher = PAGE_WATCHER.write().await;
+                let now = chrono::Utc::now();
+                match result {
+                    Ok(html) => {
+                        if let Some(event) = watcher.record_check(&url, fingerprint_page(&html), now) {
+                            let _ = app.emit("page://changed", event);
+                        }
+                    }
+                    Err(e) => watcher.record_error(&url, e, now),
+                }
+                let _ = watcher.save();
+            }
+            
+            tokio::time::sleep(WATCH_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn watch_page(url: String, interval_minutes: u32) -> Result<WatchedPage, String> {
+    let mut watcher = PAGE_WATCHER.write().await;
+    let page = watcher.watch_page(&url, interval_minutes)?;
+    watcher.save()?;
+    Ok(page)
+}
+
+#[tauri::command]
+pub async fn unwatch_page(url: String) -> Result<(), String> {
+    let mut watcher = PAGE_WATCHER.write().await;
+    watcher.unwatch_page(&url)?;
+    watcher.save()
+}
+
+#[tauri::command]
+pub async fn get_watched_pages() -> Result<Vec<WatchedPage>, String> {
+    let watcher = PAGE_WATCHER.read().await;
+    Ok(watcher.get_watched_pages())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = "<html><body><nav>Home About Contact</nav><article>\
+        <p>The council approved the new cycling lanes after a long debate that lasted well into the evening session.</p>\
+        <p>Construction is expected to begin next spring and finish before the end of the following summer season.</p>\
+        </article><footer>Updated 10:42 am</footer></body></html>";
+
+    const UPDATED_ARTICLE: &str = "<html><body><nav>Home About Contact</nav><article>\
+        <p>The council approved the new cycling lanes after a long debate that lasted well into the evening session.</p>\
+        <p>Construction has been delayed by a year because the contractor withdrew from the project last week.</p>\
+        </article><footer>Updated 11:05 am</footer></body></html>";
+
+    fn watcher() -> PageWatcher {
+        PageWatcher { pages: HashMap::new() }
+    }
+
+    #[test]
+    fn watching_validates_and_updates_pages() {
+        let mut watcher = watcher();
+        
+        assert!(watcher.watch_page("ftp://example.com/", 60).is_err());
+        assert!(watcher.watch_page("https://example.com/", 1).is_err());
+        
+        let page = watcher.watch_page("https://example.com", 60).unwrap();
+        assert_eq!(page.url, "https://example.com/");
+        watcher.watch_page("https://example.com/", 120).unwrap();
+        watcher.watch_page("https://example.org/", 60).unwrap();
+        
+        let pages = watcher.get_watched_pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].url, "https://example.com/");
+        assert_eq!(pages[0].interval_minutes, 120);
+        
+        watcher.unwatch_page("https://example.com").unwrap();
+        assert!(watcher.unwatch_page("https://example.com/").is_err());
+        assert_eq!(watcher.get_watched_pages().len(), 1);
+    }
+
+    #[test]
+    fn content_changes_are_detected_and_volatile_text_ignored() {
+        let mut watcher = watcher();
+        let url = watcher.watch_page("https://news.example/", 60).unwrap().url;
+        let now = chrono::Utc::now();
+        
+        assert!(watcher.record_check(&url, fingerprint_page(ARTICLE), now).is_none());
+        let retimed = ARTICLE.replace("10:42 am", "10:57 am");
+        assert!(watcher.record_check(&url, fingerprint_page(&retimed), now).is_none());
+        
+        let event = watcher.record_check(&url, fingerprint_page(UPDATED_ARTICLE), now).unwrap();
+        assert_eq!(event.added_blocks, 1);
+        assert_eq!(event.removed_blocks, 1);
+        assert!(event.excerpt.unwrap().starts_with("Construction has been delayed"));
+        assert_eq!(watcher.get_watched_pages()[0].last_changed, Some(now));
+    }
+
+    #[test]
+    fn pages_are_due_once_their_interval_elapses() {
+        let mut watcher = watcher();
+        let checked = watcher.watch_page("https://checked.example/", 60).unwrap().url;
+        let unchecked = watcher.watch_page("https://unchecked.example/", 60).unwrap().url;
+        let now = chrono::Utc::now();
+        watcher.record_error(&checked, "timed out".to_string(), now);
+        
+        assert_eq!(watcher.due_pages(now + chrono::Duration::minutes(59)), vec![unchecked]);
+        let mut due = watcher.due_pages(now + chrono::Duration::minutes(60));
+        due.sort();
+        assert_eq!(due, vec![checked, "https://unchecked.example/".to_string()]);
+    }
+}
\ No newline at end of file
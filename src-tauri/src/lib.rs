@@ -1,17 +1,31 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 mod browser;
 
+/// Version of this build, checked against a plugin's `min_browser_version`.
+pub const BROWSER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+use tauri::Manager;
 use browser::{
-    engine::{create_browser_window, close_browser_window, get_browser_window, get_all_windows, create_engine_tab, close_engine_tab, update_engine_tab_url, set_engine_active_tab, get_engine_window_tabs, get_engine_active_tab, create_webview_tab, show_webview_tab, hide_webview_tab, close_webview_tab, navigate_webview_tab},
-    tabs::{create_tab, close_tab, update_tab_url, get_tab, get_all_tabs, set_active_tab, duplicate_tab, move_tab, pin_tab, unpin_tab, mute_tab, unmute_tab, reload_tab, stop_tab_loading, go_back, go_forward, zoom_in, zoom_out, reset_zoom},
-    bookmarks::{add_bookmark, create_bookmark_folder, delete_bookmark, delete_bookmark_folder, update_bookmark, move_bookmark, search_bookmarks, get_bookmark_tree, get_folder_contents, export_bookmarks, import_bookmarks},
-    history::{add_history_visit, remove_history_entry, clear_history, search_history, get_recent_history, get_most_visited, get_history_by_date, get_history_stats, get_history_suggestions, update_history_favicon, export_history, import_history},
-    downloads::{start_download, cancel_download, pause_download, resume_download, remove_download, clear_completed_downloads, get_downloads, get_active_downloads, get_download_stats, set_download_directory, get_download_progress, export_downloads},
-    settings::{get_settings, update_general_settings, update_appearance_settings, update_search_settings, update_download_settings, update_advanced_settings, add_search_engine, remove_search_engine, set_default_search_engine, reset_settings_to_defaults, export_settings, import_settings, get_search_url, get_suggestion_url},
-    filters::{get_site_shields, update_filter_lists, get_global_stats, should_block_request},
-    privacy::{update_privacy_settings_privacy, update_site_shields_privacy, load_privacy_settings, check_url},
-    session::{create_session, save_current_session, restore_session, add_window_to_session, remove_window_from_session, update_window_in_session, add_tab_to_window, remove_tab_from_window, update_tab_in_window, update_tab_scroll_position, set_session_active_tab, get_saved_sessions, delete_session, clear_old_sessions, export_session, import_session, get_current_session, enable_auto_save},
-    plugins::{install_plugin, uninstall_plugin, enable_plugin, disable_plugin, get_plugin, get_all_plugins, get_enabled_plugins, update_plugin_setting, get_plugin_setting, trigger_plugin_event, has_plugin_permission, get_plugins_by_hook, search_plugins, export_plugin_settings, import_plugin_settings, get_plugin_stats, validate_plugin_manifest},
+    engine::{create_browser_window, close_browser_window, get_browser_window, get_all_windows, create_engine_tab, close_engine_tab, update_engine_tab_url, set_engine_active_tab, get_engine_window_tabs, get_engine_active_tab, create_webview_tab, show_webview_tab, hide_webview_tab, close_webview_tab, navigate_webview_tab, restore_last_closed_window},
+    tabs::{create_tab, close_tab, close_tabs_to_right, close_other_tabs, update_tab_url, get_tab, get_all_tabs, get_window_tabs, get_active_tab, get_mru_order, set_active_tab, duplicate_tab, move_tab, pin_tab, unpin_tab, mute_tab, unmute_tab, reload_tab, stop_tab_loading, go_back, go_forward, zoom_in, zoom_out, reset_zoom, open_startup_tabs, refresh_restored_tabs, set_tab_audible, set_memory_pressure_policy, get_memory_pressure_policy, start_memory_pressure_monitor, start_tab_restore, capture_window_layout, restore_window_layout, verify_tab_integrity, repair_tab_integrity, export_tab_share, import_tab_share, get_recently_closed_windows, reopen_last_closed_tab, get_recently_closed, set_closed_tab_limit, restore_tabs, suggest_tab_groups},
+    bookmarks::{add_bookmark, is_bookmarked, create_bookmark_folder, delete_bookmark, delete_bookmark_folder, update_bookmark, move_bookmark, move_bookmark_folder, search_bookmarks, get_bookmark_tree, get_folder_contents, export_bookmarks, export_bookmarks_html, import_bookmarks, import_bookmarks_html, import_bookmarks_batch, auto_categorize_bookmarks, suggest_bookmark_folder, add_bookmark_tag, remove_bookmark_tag, get_all_tags, get_bookmarks_by_tag, record_bookmark_visit, get_most_visited_bookmarks},
+    history::{add_history_visit, set_history_recording, is_history_recording, remove_history_entry, clear_history, preview_clear_history, search_history, get_recent_history, get_most_visited, get_history_by_date, get_history_range, get_history_by_domain, clear_history_for_domain, get_history_stats, get_history_suggestions, update_history_favicon, export_history, import_history, get_browsing_sessions},
+    downloads::{start_download, start_download_with_headers, cancel_download, pause_download, resume_download, remove_download, clear_completed_downloads, get_downloads, get_active_downloads, get_queued_downloads, set_max_concurrent_downloads, get_download_stats, set_download_directory, get_download_progress, get_download_progress_detailed, export_downloads, tag_download, untag_download, get_downloads_by_tag},
+    settings::{get_settings, update_general_settings, update_privacy_settings, update_appearance_settings, update_search_settings, update_download_settings, update_advanced_settings, add_search_engine, remove_search_engine, set_default_search_engine, reset_settings_to_defaults, export_settings, import_settings, get_search_url, get_search_url_for_engine, get_suggestion_url, set_dns_over_https, add_startup_tab, remove_startup_tab, get_startup_tabs, add_sensitive_domain, remove_sensitive_domain, get_sensitive_domains, set_data_saver, get_data_usage_policy, resolve_theme, handle_os_theme_changed, set_domain_css, get_domain_css, clear_domain_css, list_search_engines, reorder_search_engines, block_search_domain, unblock_search_domain, get_search_domain_blocklist, filter_blocked_search_results, set_kiosk_allowlist, set_kiosk_mode, get_kiosk_settings, set_network_settings, get_network_settings},
+    filters::{get_site_shields, update_filter_lists, import_filter_list_from_text, get_filter_list_rules, get_global_stats, get_session_stats, reset_session_stats, should_block_request, intercept_request, get_cosmetic_filters, test_filter_rule, compute_privacy_score, record_request, report_fingerprinting_attempt, deduplicate_filter_rules, count_total_rules, record_blocked_request, get_top_blocked_trackers, reset_global_stats, reset_shield_stats, start_filter_list_updater},
+    privacy::{load_filter_lists, add_filter_list, remove_filter_list, toggle_filter_list, update_filter_list, load_site_shields, update_site_shields_privacy, reset_site_shields, load_blocking_stats, reset_blocking_stats, load_privacy_settings, update_privacy_settings_privacy, check_url, add_custom_rule, remove_custom_rule, get_custom_rules, export_privacy_data, import_privacy_data},
+    session::{create_session, save_current_session, save_session_as, restore_session, add_window_to_session, remove_window_from_session, update_window_in_session, add_tab_to_window, remove_tab_from_window, update_tab_in_window, update_tab_scroll_position, set_session_active_tab, get_saved_sessions, rename_session, delete_session, clear_old_sessions, set_max_saved_sessions, export_session, import_session, get_current_session, enable_auto_save, recover_session, start_session_auto_save, clear_crash_recovery},
+    doh::{resolve_host, clear_dns_cache},
+    metadata::get_page_metadata,
+    lookalike::check_lookalike,
+    reports::{generate_stats_report, export_stats_report},
+    favicons::{get_favicon, clear_favicon_cache},
+    security::{get_tab_security_info, report_mixed_content},
+    redirects::unwrap_tracking_link,
+    reader::fetch_readable_page,
+    storage::{get_profile_directory, set_profile_directory},
+    watch::{watch_page, unwatch_page, get_watched_pages, start_page_watcher},
+    plugins::{install_plugin, uninstall_plugin, enable_plugin, disable_plugin, get_plugin, get_dependents, get_all_plugins, get_enabled_plugins, update_plugin_setting, get_plugin_setting, trigger_plugin_event, get_denied_events, has_plugin_permission, get_plugins_by_hook, search_plugins, export_plugin_settings, import_plugin_settings, get_plugin_stats, validate_plugin_manifest},
 };
 
 #[tauri::command]
@@ -27,6 +41,19 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_shell::init())
+        .setup(|app| {
+            start_memory_pressure_monitor(app.handle().clone());
+            start_tab_restore(app.handle().clone());
+            start_page_watcher(app.handle().clone());
+            start_filter_list_updater(app.handle().clone());
+            start_session_auto_save();
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::ThemeChanged(theme) = event {
+                handle_os_theme_changed(window.app_handle().clone(), *theme);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             create_browser_window,
@@ -44,11 +71,17 @@ pub fn run() {
             hide_webview_tab,
             close_webview_tab,
             navigate_webview_tab,
+            restore_last_closed_window,
             create_tab,
             close_tab,
+            close_tabs_to_right,
+            close_other_tabs,
             update_tab_url,
             get_tab,
             get_all_tabs,
+            get_window_tabs,
+            get_active_tab,
+            get_mru_order,
             set_active_tab,
             duplicate_tab,
             move_tab,
@@ -63,30 +96,68 @@ pub fn run() {
             zoom_in,
             zoom_out,
             reset_zoom,
+            open_startup_tabs,
+            refresh_restored_tabs,
+            set_tab_audible,
+            set_memory_pressure_policy,
+            get_memory_pressure_policy,
+            capture_window_layout,
+            restore_window_layout,
+            verify_tab_integrity,
+            repair_tab_integrity,
+            export_tab_share,
+            import_tab_share,
+            get_recently_closed_windows,
+            reopen_last_closed_tab,
+            get_recently_closed,
+            set_closed_tab_limit,
+            restore_tabs,
+            suggest_tab_groups,
             add_bookmark,
+            is_bookmarked,
             create_bookmark_folder,
             delete_bookmark,
             delete_bookmark_folder,
             update_bookmark,
             move_bookmark,
+            move_bookmark_folder,
             search_bookmarks,
             get_bookmark_tree,
             get_folder_contents,
             export_bookmarks,
+            export_bookmarks_html,
             import_bookmarks,
+            import_bookmarks_html,
+            import_bookmarks_batch,
+            auto_categorize_bookmarks,
+            suggest_bookmark_folder,
+            add_bookmark_tag,
+            remove_bookmark_tag,
+            get_all_tags,
+            get_bookmarks_by_tag,
+            record_bookmark_visit,
+            get_most_visited_bookmarks,
             add_history_visit,
+            set_history_recording,
+            is_history_recording,
             remove_history_entry,
             clear_history,
+            preview_clear_history,
             search_history,
             get_recent_history,
             get_most_visited,
             get_history_by_date,
+            get_history_range,
+            get_history_by_domain,
+            clear_history_for_domain,
             get_history_stats,
             get_history_suggestions,
             update_history_favicon,
             export_history,
             import_history,
+            get_browsing_sessions,
             start_download,
+            start_download_with_headers,
             cancel_download,
             pause_download,
             resume_download,
@@ -94,12 +165,19 @@ pub fn run() {
             clear_completed_downloads,
             get_downloads,
             get_active_downloads,
+            get_queued_downloads,
+            set_max_concurrent_downloads,
             get_download_stats,
             set_download_directory,
             get_download_progress,
+            get_download_progress_detailed,
             export_downloads,
+            tag_download,
+            untag_download,
+            get_downloads_by_tag,
             get_settings,
             update_general_settings,
+            update_privacy_settings,
             update_appearance_settings,
             update_search_settings,
             update_download_settings,
@@ -111,17 +189,90 @@ pub fn run() {
             export_settings,
             import_settings,
             get_search_url,
+            block_search_domain,
+            unblock_search_domain,
+            get_search_domain_blocklist,
+            filter_blocked_search_results,
+            get_search_url_for_engine,
             get_suggestion_url,
+            set_dns_over_https,
+            add_startup_tab,
+            remove_startup_tab,
+            get_startup_tabs,
+            add_sensitive_domain,
+            remove_sensitive_domain,
+            get_sensitive_domains,
+            set_data_saver,
+            get_data_usage_policy,
+            resolve_theme,
+            set_domain_css,
+            get_domain_css,
+            clear_domain_css,
+            list_search_engines,
+            reorder_search_engines,
+            set_kiosk_allowlist,
+            set_kiosk_mode,
+            get_kiosk_settings,
+            set_network_settings,
+            get_network_settings,
+            resolve_host,
+            clear_dns_cache,
+            get_page_metadata,
+            check_lookalike,
+            generate_stats_report,
+            export_stats_report,
+            get_favicon,
+            clear_favicon_cache,
+            get_tab_security_info,
+            report_mixed_content,
+            unwrap_tracking_link,
+            fetch_readable_page,
+            get_profile_directory,
+            set_profile_directory,
+            watch_page,
+            unwatch_page,
+            get_watched_pages,
             get_site_shields,
             update_filter_lists,
+            import_filter_list_from_text,
+            get_filter_list_rules,
             get_global_stats,
+            get_session_stats,
+            reset_session_stats,
             should_block_request,
-            update_privacy_settings_privacy,
+            intercept_request,
+            get_cosmetic_filters,
+            test_filter_rule,
+            compute_privacy_score,
+            record_request,
+            report_fingerprinting_attempt,
+            deduplicate_filter_rules,
+            count_total_rules,
+            record_blocked_request,
+            get_top_blocked_trackers,
+            reset_global_stats,
+            reset_shield_stats,
+            load_filter_lists,
+            add_filter_list,
+            remove_filter_list,
+            toggle_filter_list,
+            update_filter_list,
+            load_site_shields,
             update_site_shields_privacy,
+            reset_site_shields,
+            load_blocking_stats,
+            reset_blocking_stats,
             load_privacy_settings,
+            update_privacy_settings_privacy,
             check_url,
+            add_custom_rule,
+            remove_custom_rule,
+            get_custom_rules,
+            export_privacy_data,
+            import_privacy_data,
             create_session,
             save_current_session,
+            save_session_as,
             restore_session,
             add_window_to_session,
             remove_window_from_session,
@@ -132,22 +283,27 @@ pub fn run() {
             update_tab_scroll_position,
             set_session_active_tab,
             get_saved_sessions,
+            rename_session,
             delete_session,
             clear_old_sessions,
+            set_max_saved_sessions,
             export_session,
             import_session,
             get_current_session,
             enable_auto_save,
+            recover_session,
             install_plugin,
             uninstall_plugin,
             enable_plugin,
             disable_plugin,
             get_plugin,
+            get_dependents,
             get_all_plugins,
             get_enabled_plugins,
             update_plugin_setting,
             get_plugin_setting,
             trigger_plugin_event,
+            get_denied_events,
             has_plugin_permission,
             get_plugins_by_hook,
             search_plugins,
@@ -156,6 +312,11 @@ pub fn run() {
             get_plugin_stats,
             validate_plugin_manifest
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                clear_crash_recovery();
+            }
+        });
 }